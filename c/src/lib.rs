@@ -222,6 +222,29 @@ pub extern "C" fn substrait_validator_config_ignore_unknown_fields(
     true
 }
 
+/// Instructs the validator to only build the parts of the parse tree needed
+/// for diagnostics and data types. This is faster and uses less memory, but
+/// exported trees lack comments and descriptions.
+///
+/// Returns whether the function was successful. If false is returned, retrieve
+/// the error message with substrait_validator_get_last_error().
+#[no_mangle]
+pub extern "C" fn substrait_validator_config_diagnostics_only(config: *mut ConfigHandle) -> bool {
+    // Check for null.
+    if config.is_null() {
+        set_last_error("received null configuration handle");
+        return false;
+    }
+
+    // UNSAFE: unpack configuration handle. Assumes that the pointer was
+    // created by substrait_validator_config_new(), or behavior is undefined.
+    let config = unsafe { &mut (*config).config };
+
+    // Update configuration and return success.
+    config.diagnostics_only();
+    true
+}
+
 /// Explicitly allows a protobuf message type for use in advanced extensions,
 /// despite the fact that the validator can't validate it. If an advanced
 /// extension is encountered that isn't explicitly allowed, a warning is
@@ -605,6 +628,49 @@ pub extern "C" fn substrait_validator_config_max_urn_resolution_depth(
     true
 }
 
+/// Applies the settings in the given YAML configuration file to the
+/// configuration.
+///
+/// Returns whether the function was successful. If false is returned, retrieve
+/// the error message with substrait_validator_get_last_error().
+#[no_mangle]
+pub extern "C" fn substrait_validator_config_load_file(
+    config: *mut ConfigHandle,
+    path: *const libc::c_char,
+) -> bool {
+    // Check for nulls.
+    if config.is_null() {
+        set_last_error("received null configuration handle");
+        return false;
+    }
+    if path.is_null() {
+        set_last_error("received null path");
+        return false;
+    }
+
+    // UNSAFE: unpack configuration handle. Assumes that the pointer was
+    // created by substrait_validator_config_new(), or behavior is undefined.
+    let config = unsafe { &mut (*config).config };
+
+    // UNSAFE: unpack path string. Assumes that the pointer points to a
+    // null-terminated string.
+    let path = unsafe { std::ffi::CStr::from_ptr(path) };
+    let path = match path.to_str() {
+        Ok(p) => p,
+        Err(e) => {
+            set_last_error(format!("received invalid path: {e}"));
+            return false;
+        }
+    };
+
+    // Update configuration and return success.
+    if let Err(e) = config.load_file(path) {
+        set_last_error(e.to_string());
+        return false;
+    }
+    true
+}
+
 /// Parse/validation result handle.
 pub struct ResultHandle {
     pub result: substrait_validator::ParseResult,
@@ -649,6 +715,43 @@ pub extern "C" fn substrait_validator_parse(
     Box::into_raw(handle)
 }
 
+/// Parses the given byte buffer as a simple extension YAML file, validating
+/// it on its own rather than as part of a plan, using the given
+/// configuration. If a null pointer is passed for the configuration, the
+/// default configuration is used.
+///
+/// Returns a handle to the parse result, like substrait_validator_parse().
+#[no_mangle]
+pub extern "C" fn substrait_validator_parse_extension(
+    data: *const u8,
+    size: u64,
+    config: *const ConfigHandle,
+) -> *mut ResultHandle {
+    // Catch null pointers.
+    if data.is_null() {
+        set_last_error("received null input buffer");
+        return std::ptr::null_mut();
+    }
+
+    // UNSAFE: convert the incoming buffer information into a slice.
+    let data = unsafe { std::slice::from_raw_parts(data, size.try_into().unwrap()) };
+
+    // Perform the actual parsing.
+    let result = if config.is_null() {
+        substrait_validator::validate_extension(data, &substrait_validator::Config::default())
+    } else {
+        // UNSAFE: unpack configuration handle. Assumes that the pointer was
+        // created by substrait_validator_config_new(), or behavior is undefined.
+        substrait_validator::validate_extension(data, unsafe { &(*config).config })
+    };
+
+    // Create a box to store the return value handle on the stack.
+    let handle = Box::new(ResultHandle { result });
+
+    // Convert the box to its raw pointer and relinquish ownership.
+    Box::into_raw(handle)
+}
+
 /// Frees memory associated with a parse result handle. No-op if given a
 /// nullptr.
 #[no_mangle]
@@ -805,6 +908,38 @@ pub extern "C" fn substrait_validator_export_diagnostics(
     )
 }
 
+/// Same as substrait_validator_export_diagnostics(), but instead returns a
+/// buffer filled with a Graphviz DOT graph of the relations in the plan.
+#[no_mangle]
+pub extern "C" fn substrait_validator_export_dot(
+    handle: *const ResultHandle,
+    size: *mut u64,
+) -> *mut u8 {
+    export(substrait_validator::export::Format::Dot, handle, size)
+}
+
+/// Same as substrait_validator_export_diagnostics(), but instead returns a
+/// buffer filled with an EXPLAIN-like plain-text description of the relation
+/// trees of the plan.
+#[no_mangle]
+pub extern "C" fn substrait_validator_export_explain(
+    handle: *const ResultHandle,
+    size: *mut u64,
+) -> *mut u8 {
+    export(substrait_validator::export::Format::Explain, handle, size)
+}
+
+/// Same as substrait_validator_export_diagnostics(), but instead returns a
+/// buffer filled with a Markdown report of the diagnostics and relations of
+/// the plan.
+#[no_mangle]
+pub extern "C" fn substrait_validator_export_markdown(
+    handle: *const ResultHandle,
+    size: *mut u64,
+) -> *mut u8 {
+    export(substrait_validator::export::Format::Markdown, handle, size)
+}
+
 /// Same as substrait_validator_export_diagnostics(), but instead returns a
 /// buffer filled with a HTML-based human-readable description of the parsed
 /// plan.
@@ -816,6 +951,37 @@ pub extern "C" fn substrait_validator_export_html(
     export(substrait_validator::export::Format::Html, handle, size)
 }
 
+/// Same as substrait_validator_export_diagnostics(), but instead returns a
+/// buffer filled with a JSON object listing the diagnostics and the validity
+/// of the plan.
+#[no_mangle]
+pub extern "C" fn substrait_validator_export_json(
+    handle: *const ResultHandle,
+    size: *mut u64,
+) -> *mut u8 {
+    export(substrait_validator::export::Format::Json, handle, size)
+}
+
+/// Same as substrait_validator_export_diagnostics(), but instead returns a
+/// buffer filled with a SARIF 2.1.0 log of the diagnostics.
+#[no_mangle]
+pub extern "C" fn substrait_validator_export_sarif(
+    handle: *const ResultHandle,
+    size: *mut u64,
+) -> *mut u8 {
+    export(substrait_validator::export::Format::Sarif, handle, size)
+}
+
+/// Same as substrait_validator_export_diagnostics(), but instead returns a
+/// buffer filled with a JUnit XML document with a single testcase.
+#[no_mangle]
+pub extern "C" fn substrait_validator_export_junit(
+    handle: *const ResultHandle,
+    size: *mut u64,
+) -> *mut u8 {
+    export(substrait_validator::export::Format::Junit, handle, size)
+}
+
 /// Same as substrait_validator_export_diagnostics(), but instead returns a
 /// substrait.validator.Node message in its binary serialization format. The
 /// buffer is null-terminated, but note that protobuf serialization is a binary
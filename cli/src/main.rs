@@ -0,0 +1,741 @@
+// SPDX-License-Identifier: Apache-2.0
+
+//! Command-line interface for the Substrait validator.
+//!
+//! This is a standalone counterpart of the `substrait-validator` command
+//! provided by the Python package, for environments where Python is not
+//! available. It supports the same options, except that only the binary
+//! protobuf and protobuf JSON formats are supported for plans; YAML and JDOT
+//! plans remain exclusive to the Python package. YAML input is instead
+//! interpreted as a simple extension file, which is validated on its own.
+
+use clap::Parser;
+use prost::Message;
+use prost_reflect::ReflectMessage;
+use std::io::IsTerminal;
+use std::io::Read;
+use std::io::Write;
+use substrait_validator::input::profile::ConsumerProfile;
+use substrait_validator::input::proto::substrait::validator;
+use substrait_validator::input::proto::substrait::Plan;
+use substrait_validator::input::resolver::DirectoryResolver;
+use substrait_validator::input::resolver::TemplateResolver;
+use substrait_validator::Classification;
+use substrait_validator::Level;
+use substrait_validator::Pattern;
+
+/// Validate or convert the substrait.Plan represented by INFILE (or stdin
+/// using "-").
+///
+/// This version of the validator is EXPERIMENTAL. Please report issues via
+/// https://github.com/substrait-io/substrait-validator/issues/new.
+///
+/// The following formats are supported:
+///
+///  - proto: binary serialization format of protobuf.
+///
+///  - json: JSON serialization format of protobuf.
+///
+///  - yaml**: a simple extension file, validated on its own rather than as
+///    part of a plan.
+///
+///  - diag*: list of validator diagnostic messages.
+///
+///  - diag-json*: validator diagnostic messages as a single JSON object.
+///
+///  - sarif*: diagnostics in SARIF format, for code scanning tools.
+///
+///  - junit*: diagnostics as a JUnit XML test report.
+///
+///  - dot*: the relation trees of the plan as a Graphviz DOT graph.
+///
+///  - explain*: the relation trees of the plan as indented plain text.
+///
+///  - markdown*: a Markdown report, e.g. for pull request comments.
+///
+///  - html*: all information known about the plan in HTML format.
+///
+/// *output-only, and not supported in -mconvert mode.
+///
+/// **input-only, and not supported in -mconvert mode.
+///
+/// When validation is enabled, the output message type will be
+/// substrait.validator.ParseResult. If you just want to convert between
+/// different representations of the substrait.Plan message, use -mconvert.
+#[derive(Parser, Debug)]
+#[command(
+    name = "substrait-validator",
+    disable_version_flag = true,
+    verbatim_doc_comment
+)]
+struct Args {
+    /// Input file, or "-" for stdin.
+    infile: Option<String>,
+
+    /// Input file type. "ext" uses the extension of the input file,
+    /// defaulting to "proto" if there is none.
+    #[arg(long, value_enum, ignore_case = true, default_value_t = InType::Ext)]
+    in_type: InType,
+
+    /// Specifies the verbosity for writing diagnostics to stderr.
+    #[arg(short, long, value_enum, ignore_case = true, default_value_t = Verbosity::Warn)]
+    verbosity: Verbosity,
+
+    /// Output file. "-" may be used to select stdout.
+    #[arg(short = 'O', long)]
+    out_file: Option<String>,
+
+    /// Output file type. "ext" uses the extension of the output file,
+    /// defaulting to "diag" if there is none.
+    #[arg(long, value_enum, ignore_case = true, default_value_t = OutType::Ext)]
+    out_type: OutType,
+
+    /// Validation mode. "convert" disables all but protobuf's internal
+    /// validation, and can be used to convert between different
+    /// representations of substrait.Plan. "ignore" runs validation, but
+    /// ignores the result (i.e. the program always returns 0 and emits an
+    /// output file if requested). "loose" fails only if the validator can
+    /// prove that the plan is invalid. "strict" fails if it cannot prove that
+    /// it is valid.
+    #[arg(short, long, value_enum, ignore_case = true, default_value_t = Mode::Loose)]
+    mode: Mode,
+
+    /// Do not generate warnings for unknown protobuf fields that are set to
+    /// their protobuf-defined default value. For JSON input, this also
+    /// allows fields that are not known to the validator at all, in both
+    /// validation and convert mode.
+    #[arg(long)]
+    ignore_unknown_fields: bool,
+
+    /// Only build the parts of the parse tree needed for diagnostics and data
+    /// types. This is faster and uses less memory, but the exported tree
+    /// lacks comments and descriptions.
+    #[arg(long)]
+    diagnostics_only: bool,
+
+    /// Explicitly allow the given protobuf type URL(s) to be used in protobuf
+    /// Any messages. Supports glob syntax.
+    #[arg(long, value_name = "PATTERN")]
+    allow_proto_any: Vec<String>,
+
+    /// Clamps the error level of diagnostics with diagnostic code or class
+    /// CODE to at least MIN and at most MAX. CODE may also be the name of the
+    /// class. For example, --diagnostic-level 1 warn error will override the
+    /// level of info diagnostics with code 1 to warning, leaving the other
+    /// levels unchanged.
+    #[arg(long, num_args = 3, value_names = ["CODE", "MIN", "MAX"])]
+    diagnostic_level: Vec<String>,
+
+    /// Overrides extension URNs in the plan that match PATTERN with URN. Set
+    /// URN to "-" to disable resolution of matching URNs. Supports glob
+    /// syntax. For example, "--override-urn extension:com.example:* -"
+    /// disables resolution of that owner's extensions.
+    #[arg(long, num_args = 2, value_names = ["PATTERN", "URN"])]
+    override_urn: Vec<String>,
+
+    /// Resolves extension URNs using the YAML files in the given directory
+    /// (recursively), identifying each file by its "urn" key. May be
+    /// specified more than once.
+    #[arg(long, value_name = "DIR")]
+    urn_dir: Vec<std::path::PathBuf>,
+
+    /// Resolves extension URNs matching PATTERN by reading the file at the
+    /// path obtained by substituting {urn}, {owner}, and {id} in TEMPLATE.
+    /// For example, "--urn-template extension:com.example:* ext/{id}.yaml".
+    /// May be specified more than once; the first matching pattern is used.
+    #[arg(long, num_args = 2, value_names = ["PATTERN", "TEMPLATE"])]
+    urn_template: Vec<String>,
+
+    /// Loads validator configuration from the given YAML file before applying
+    /// the other configuration options.
+    #[arg(long, value_name = "FILE")]
+    config: Option<std::path::PathBuf>,
+
+    /// Checks the plan against the consumer capability profile in the given
+    /// YAML file, emitting diagnostics for anything the consumer does not
+    /// support.
+    #[arg(long, value_name = "FILE")]
+    consumer_profile: Option<std::path::PathBuf>,
+
+    /// Sets the maximum recursion depth for resolving transitive
+    /// dependencies. You can specify a negative number to disable the limit,
+    /// or set this to zero to disable URN resolution entirely.
+    #[arg(long, allow_negative_numbers = true)]
+    urn_depth: Option<i64>,
+
+    /// Stops validation when the parse tree gets deeper than this.
+    #[arg(long, value_name = "DEPTH")]
+    max_tree_depth: Option<usize>,
+
+    /// Stops validation when the parse tree gets more nodes than this.
+    #[arg(long, value_name = "COUNT")]
+    max_node_count: Option<usize>,
+
+    /// Stops validation when the string and binary data in the input exceeds
+    /// this many bytes.
+    #[arg(long, value_name = "BYTES")]
+    max_literal_bytes: Option<usize>,
+
+    /// Stops validation after this many milliseconds.
+    #[arg(long, value_name = "MS")]
+    time_limit: Option<u64>,
+
+    /// Show a list of all known diagnostic codes and exit.
+    #[arg(long)]
+    help_diagnostics: bool,
+
+    /// Print the version of the validator and exit.
+    #[arg(long)]
+    version: bool,
+
+    /// Print the version of Substrait that the validator was built against and
+    /// exit.
+    #[arg(long)]
+    substrait_version: bool,
+}
+
+/// Input file types.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, clap::ValueEnum)]
+enum InType {
+    Ext,
+    Proto,
+    Json,
+    Yaml,
+}
+
+/// Output file types.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, clap::ValueEnum)]
+enum OutType {
+    Ext,
+    Diag,
+    DiagJson,
+    Sarif,
+    Junit,
+    Dot,
+    Explain,
+    Markdown,
+    Html,
+    Proto,
+    Json,
+}
+
+/// Validation modes.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, clap::ValueEnum)]
+enum Mode {
+    Convert,
+    Ignore,
+    Loose,
+    Strict,
+}
+
+/// Verbosity levels for writing diagnostics to stderr.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, clap::ValueEnum)]
+enum Verbosity {
+    Info,
+    Warn,
+    Error,
+    Fatal,
+    Quiet,
+}
+
+impl From<Level> for Verbosity {
+    fn from(level: Level) -> Self {
+        match level {
+            Level::Info => Verbosity::Info,
+            Level::Warning => Verbosity::Warn,
+            Level::Error => Verbosity::Error,
+        }
+    }
+}
+
+/// Writes diagnostics and fatal errors to stderr.
+struct Reporter {
+    /// Minimum level for diagnostics to be written.
+    verbosity: Verbosity,
+
+    /// Whether to use ANSI escape codes to color the level.
+    color: bool,
+}
+
+impl Reporter {
+    /// Emits a diagnostic message to stderr.
+    fn emit(
+        &self,
+        level: Verbosity,
+        original_level: Verbosity,
+        code: Option<u32>,
+        source: Option<String>,
+        msg: &str,
+    ) {
+        // Only print the diagnostic if the configured verbosity is high
+        // enough.
+        if level < self.verbosity {
+            return;
+        }
+
+        // Format the level.
+        let (name, style) = match level {
+            Verbosity::Info => ("Info", "32"),
+            Verbosity::Warn => ("Warning", "33"),
+            Verbosity::Error => ("Error", "1;31"),
+            Verbosity::Fatal | Verbosity::Quiet => ("Fatal error", "1;31"),
+        };
+        let mut formatted = if self.color {
+            format!("\x1b[{style}m{name}\x1b[0m")
+        } else {
+            name.to_string()
+        };
+
+        // Format extra information written within parentheses.
+        let mut parens = vec![];
+        if original_level != level {
+            parens.push(format!(
+                "{} {}",
+                if original_level > level {
+                    "reduced from"
+                } else {
+                    "promoted from"
+                },
+                match original_level {
+                    Verbosity::Info => "info",
+                    Verbosity::Warn => "warning",
+                    Verbosity::Error => "error",
+                    Verbosity::Fatal | Verbosity::Quiet => "fatal",
+                }
+            ));
+        }
+        if let Some(code) = code {
+            parens.push(format!("code {code:04}"));
+        }
+        if !parens.is_empty() {
+            formatted += &format!(" ({})", parens.join(", "));
+        }
+        formatted += ":\n";
+
+        // Append source information, if known.
+        if let Some(source) = source {
+            formatted += &format!("  at {source}:\n");
+        }
+
+        // Append the actual message.
+        formatted += "  ";
+        formatted += &msg.replace('\n', "\n  ");
+        formatted += "\n";
+
+        eprintln!("{formatted}");
+    }
+
+    /// Emits a fatal error message and exits with code 1.
+    fn fatal<S: std::fmt::Display>(&self, msg: S) -> ! {
+        self.emit(
+            Verbosity::Fatal,
+            Verbosity::Fatal,
+            None,
+            None,
+            &msg.to_string(),
+        );
+        std::process::exit(1)
+    }
+}
+
+/// Deduces the file type from the extension of the given file, using the
+/// given mapping, or returns the given default if there is no file, no
+/// extension, or the extension is not known.
+fn deduce_type<T: Copy>(file: Option<&str>, mapping: &[(&str, T)], default: T) -> T {
+    file.and_then(|file| std::path::Path::new(file).extension())
+        .and_then(|ext| ext.to_str())
+        .and_then(|ext| {
+            let ext = ext.to_lowercase();
+            mapping.iter().find(|(x, _)| *x == ext).map(|(_, t)| *t)
+        })
+        .unwrap_or(default)
+}
+
+/// Parses a diagnostic level name.
+fn parse_level(level: &str) -> Result<Level, String> {
+    match level.to_lowercase().as_str() {
+        "info" => Ok(Level::Info),
+        "warn" | "warning" => Ok(Level::Warning),
+        "error" => Ok(Level::Error),
+        _ => Err(format!(
+            "invalid level {level:?}; must be \"info\", \"warning\", or \"error\""
+        )),
+    }
+}
+
+/// Parses a diagnostic classification, specified either by its code or its
+/// name.
+fn parse_classification(class: &str) -> Result<Classification, String> {
+    let classification = match class.parse::<u32>() {
+        Ok(code) => Classification::from_code(code),
+        Err(_) => substrait_validator::iter_diagnostics().find(|x| x.name() == class),
+    };
+    classification.ok_or_else(|| format!("unknown diagnostic class {class}"))
+}
+
+/// Parses a glob pattern.
+fn parse_pattern(pattern: &str) -> Result<Pattern, String> {
+    Pattern::new(pattern).map_err(|e| format!("invalid pattern {pattern:?}: {e}"))
+}
+
+/// Builds the validator configuration from the command-line arguments.
+fn build_config(args: &Args) -> Result<substrait_validator::Config, String> {
+    let mut config = substrait_validator::Config::new();
+    if let Some(path) = &args.config {
+        config.load_file(path).map_err(|e| e.to_string())?;
+    }
+    if args.ignore_unknown_fields {
+        config.ignore_unknown_fields();
+    }
+    if args.diagnostics_only {
+        config.diagnostics_only();
+    }
+    for pattern in &args.allow_proto_any {
+        config.allow_proto_any_url(parse_pattern(pattern)?);
+    }
+    for override_args in args.diagnostic_level.chunks(3) {
+        let [class, minimum, maximum] = override_args else {
+            unreachable!("clap guarantees three values per occurrence");
+        };
+        config.override_diagnostic_level(
+            parse_classification(class)?,
+            parse_level(minimum)?,
+            parse_level(maximum)?,
+        );
+    }
+    for override_args in args.override_urn.chunks(2) {
+        let [pattern, resolve_as] = override_args else {
+            unreachable!("clap guarantees two values per occurrence");
+        };
+        let resolve_as = if resolve_as == "-" {
+            None
+        } else {
+            Some(resolve_as.clone())
+        };
+        config.override_urn(parse_pattern(pattern)?, resolve_as);
+    }
+    if !args.urn_dir.is_empty() {
+        let mut resolver = DirectoryResolver::new();
+        for dir in &args.urn_dir {
+            resolver.add_directory(dir).map_err(|e| e.to_string())?;
+        }
+        config.add_urn_directory_resolver(resolver);
+    }
+    if !args.urn_template.is_empty() {
+        let mut resolver = TemplateResolver::new();
+        for template_args in args.urn_template.chunks(2) {
+            let [pattern, template] = template_args else {
+                unreachable!("clap guarantees two values per occurrence");
+            };
+            resolver.add_template(parse_pattern(pattern)?, template.clone());
+        }
+        config.add_urn_template_resolver(resolver);
+    }
+    if let Some(depth) = args.urn_depth {
+        config.set_max_urn_resolution_depth(usize::try_from(depth).ok());
+    }
+    if let Some(depth) = args.max_tree_depth {
+        config.set_max_tree_depth(Some(depth));
+    }
+    if let Some(count) = args.max_node_count {
+        config.set_max_node_count(Some(count));
+    }
+    if let Some(bytes) = args.max_literal_bytes {
+        config.set_max_literal_bytes(Some(bytes));
+    }
+    if let Some(limit) = args.time_limit {
+        config.set_time_limit(Some(std::time::Duration::from_millis(limit)));
+    }
+    if let Some(path) = &args.consumer_profile {
+        config.set_consumer_profile(ConsumerProfile::read(path).map_err(|e| e.to_string())?);
+    }
+    Ok(config)
+}
+
+/// Prints the tree of known diagnostic codes.
+fn print_diagnostic_codes() {
+    println!("The following diagnostic codes are defined:\n");
+    let mut classes = substrait_validator::iter_diagnostics().collect::<Vec<_>>();
+    classes.sort_by_key(|x| x.code());
+    fn print(classes: &[Classification], class: Classification, first: &str, next: &str) {
+        println!(
+            "{first}{:04} ({}): {}.",
+            class.code(),
+            class.name(),
+            class.description()
+        );
+        let children = classes
+            .iter()
+            .filter(|x| x.code() != 0 && Classification::parent(x.code()) == class.code())
+            .collect::<Vec<_>>();
+        for (index, child) in children.iter().enumerate() {
+            if index + 1 < children.len() {
+                print(
+                    classes,
+                    **child,
+                    &format!("{next} |- "),
+                    &format!("{next} |  "),
+                );
+            } else {
+                print(
+                    classes,
+                    **child,
+                    &format!("{next} '- "),
+                    &format!("{next}    "),
+                );
+            }
+        }
+    }
+    print(&classes, Classification::Unclassified, "", "");
+}
+
+fn main() {
+    let args = Args::parse();
+
+    if args.help_diagnostics {
+        print_diagnostic_codes();
+        return;
+    }
+    if args.substrait_version {
+        println!("{}", substrait_validator::substrait_version());
+        return;
+    }
+    if args.version {
+        println!("{}", substrait_validator::version());
+        return;
+    }
+
+    let reporter = Reporter {
+        verbosity: args.verbosity,
+        color: std::io::stderr().is_terminal(),
+    };
+
+    // Check input file.
+    let Some(in_file) = args.infile.as_deref() else {
+        eprintln!("Missing input file. Try --help for usage information.");
+        std::process::exit(2);
+    };
+
+    // Handle automatic format deduction.
+    let in_type = match args.in_type {
+        InType::Ext => deduce_type(
+            Some(in_file),
+            &[
+                ("json", InType::Json),
+                ("yaml", InType::Yaml),
+                ("yml", InType::Yaml),
+            ],
+            InType::Proto,
+        ),
+        in_type => in_type,
+    };
+    let out_type = match args.out_type {
+        OutType::Ext => deduce_type(
+            args.out_file.as_deref(),
+            &[
+                ("json", OutType::Json),
+                ("txt", OutType::Diag),
+                ("sarif", OutType::Sarif),
+                ("xml", OutType::Junit),
+                ("dot", OutType::Dot),
+                ("gv", OutType::Dot),
+                ("md", OutType::Markdown),
+                ("html", OutType::Html),
+                ("htm", OutType::Html),
+            ],
+            OutType::Diag,
+        ),
+        out_type => out_type,
+    };
+
+    // Read input file.
+    let mut in_data = vec![];
+    if in_file == "-" {
+        if let Err(e) = std::io::stdin().read_to_end(&mut in_data) {
+            reporter.fatal(format!("failed to read from stdin: {e}"));
+        }
+    } else {
+        match std::fs::read(in_file) {
+            Ok(data) => in_data = data,
+            Err(e) => reporter.fatal(format!("failed to read input file: {e}")),
+        }
+    }
+
+    // Emits the given output data as specified on the command line.
+    let emit_output = |data: &[u8]| {
+        let result = match args.out_file.as_deref() {
+            Some("-") => std::io::stdout().write_all(data),
+            Some(file) => std::fs::write(file, data),
+            None => Ok(()),
+        };
+        if let Err(e) = result {
+            reporter.fatal(format!("failed to write output: {e}"));
+        }
+    };
+
+    // Handle convert-only mode.
+    if args.mode == Mode::Convert {
+        let plan = match in_type {
+            InType::Yaml => Err(String::from("extension files cannot be converted")),
+            InType::Json => std::str::from_utf8(&in_data)
+                .map_err(|e| e.to_string())
+                .and_then(|text| {
+                    let mut deserializer = serde_json::Deserializer::from_str(text);
+                    let plan = prost_reflect::DynamicMessage::deserialize_with_options(
+                        Plan::default().descriptor(),
+                        &mut deserializer,
+                        &prost_reflect::DeserializeOptions::new()
+                            .deny_unknown_fields(!args.ignore_unknown_fields),
+                    )
+                    .and_then(|plan| deserializer.end().map(|_| plan))
+                    .map_err(|e| e.to_string())?;
+                    plan.transcode_to::<Plan>().map_err(|e| e.to_string())
+                }),
+            _ => Plan::decode(in_data.as_slice()).map_err(|e| e.to_string()),
+        }
+        .unwrap_or_else(|e| reporter.fatal(format!("failed to decode input file: {e}")));
+        match out_type {
+            OutType::Proto => emit_output(&plan.encode_to_vec()),
+            OutType::Json => match serde_json::to_vec_pretty(&plan.transcode_to_dynamic()) {
+                Ok(data) => emit_output(&data),
+                Err(e) => reporter.fatal(format!("failed to encode plan as JSON: {e}")),
+            },
+            _ => reporter.fatal(format!(
+                "cannot emit protobuf message in {out_type:?} format"
+            )),
+        }
+        return;
+    }
+
+    // Construct parser/validator configuration.
+    let config = build_config(&args).unwrap_or_else(|e| reporter.fatal(e));
+
+    // Run the parser/validator.
+    let result = match in_type {
+        InType::Yaml => substrait_validator::validate_extension(&in_data, &config),
+        InType::Json => substrait_validator::parse_with_format(
+            &in_data,
+            substrait_validator::InputFormat::Json,
+            &config,
+        ),
+        _ => substrait_validator::parse_with_format(
+            &in_data,
+            substrait_validator::InputFormat::Proto,
+            &config,
+        ),
+    };
+
+    // Emit diagnostics to stderr.
+    for diagnostic in result.iter_diagnostics() {
+        let mut source = diagnostic.path.to_string();
+        if let Some(span) = &diagnostic.span {
+            let file = span.file.as_deref().unwrap_or(in_file);
+            source += &format!(" ({file}:{})", span.start);
+        }
+        reporter.emit(
+            diagnostic.adjusted_level.into(),
+            diagnostic.original_level.into(),
+            Some(diagnostic.cause.classification.code()),
+            Some(source),
+            &diagnostic.cause.to_string(),
+        );
+    }
+
+    // Check validity.
+    let validity = result.check();
+    match args.mode {
+        Mode::Loose if validity == substrait_validator::Validity::Invalid => {
+            reporter.fatal("plan is invalid")
+        }
+        Mode::Strict if validity != substrait_validator::Validity::Valid => {
+            reporter.fatal("failed to prove that plan is valid")
+        }
+        _ => {}
+    }
+
+    // Emit output file.
+    if args.out_file.is_none() {
+        return;
+    }
+    let format = match out_type {
+        OutType::Diag => substrait_validator::export::Format::Diagnostics,
+        OutType::DiagJson => substrait_validator::export::Format::Json,
+        OutType::Sarif => substrait_validator::export::Format::Sarif,
+        OutType::Junit => substrait_validator::export::Format::Junit,
+        OutType::Dot => substrait_validator::export::Format::Dot,
+        OutType::Explain => substrait_validator::export::Format::Explain,
+        OutType::Markdown => substrait_validator::export::Format::Markdown,
+        OutType::Html => substrait_validator::export::Format::Html,
+        OutType::Proto => substrait_validator::export::Format::Proto,
+        OutType::Ext => unreachable!("output type was deduced above"),
+        OutType::Json => {
+            let message = validator::ParseResult::from(&result);
+            match serde_json::to_vec_pretty(&message.transcode_to_dynamic()) {
+                Ok(data) => emit_output(&data),
+                Err(e) => reporter.fatal(format!("failed to encode result as JSON: {e}")),
+            }
+            return;
+        }
+    };
+    let mut data = vec![];
+    let exported = if format == substrait_validator::export::Format::Sarif {
+        let artifact_uri = Some(in_file).filter(|x| *x != "-");
+        substrait_validator::export::export_sarif(&mut data, &result, artifact_uri)
+    } else {
+        result.export(&mut data, format)
+    };
+    if let Err(e) = exported {
+        reporter.fatal(format!("failed to export validation result: {e}"));
+    }
+    emit_output(&data);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn type_deduction() {
+        let mapping = [("json", InType::Json)];
+        assert_eq!(
+            deduce_type(Some("plan.JSON"), &mapping, InType::Proto),
+            InType::Json
+        );
+        assert_eq!(
+            deduce_type(Some("plan.bin"), &mapping, InType::Proto),
+            InType::Proto
+        );
+        assert_eq!(
+            deduce_type(Some("-"), &mapping, InType::Proto),
+            InType::Proto
+        );
+        assert_eq!(deduce_type(None, &mapping, InType::Proto), InType::Proto);
+    }
+
+    #[test]
+    fn diagnostic_level_overrides() {
+        let args = Args::parse_from([
+            "substrait-validator",
+            "--diagnostic-level",
+            "1",
+            "warn",
+            "error",
+            "--diagnostic-level",
+            "ProtoAny",
+            "info",
+            "info",
+        ]);
+        let config = build_config(&args).unwrap();
+        assert_eq!(
+            config.diagnostic_level_overrides[&Classification::NotYetImplemented],
+            (Level::Warning, Level::Error)
+        );
+        assert_eq!(
+            config.diagnostic_level_overrides[&Classification::ProtoAny],
+            (Level::Info, Level::Info)
+        );
+
+        let args = Args::parse_from(["substrait-validator", "--diagnostic-level", "1", "x", "y"]);
+        assert!(build_config(&args).is_err());
+    }
+}
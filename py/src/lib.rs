@@ -11,6 +11,24 @@ use pyo3::exceptions::PyValueError;
 use pyo3::types::{PyBytes, PyDict};
 use pyo3::{prelude::*, IntoPyObjectExt};
 
+/// Converts a level name to a diagnostic level.
+fn str_to_level(level: &str) -> PyResult<::substrait_validator::Level> {
+    match level {
+        "info" => Ok(::substrait_validator::Level::Info),
+        "warning" => Ok(::substrait_validator::Level::Warning),
+        "error" => Ok(::substrait_validator::Level::Error),
+        level => Err(PyValueError::new_err(format!(
+            "invalid level {level:?}; must be \"info\", \"warning\", or \"error\""
+        ))),
+    }
+}
+
+/// Converts a diagnostic code to a classification.
+fn code_to_class(class: u32) -> PyResult<::substrait_validator::Classification> {
+    ::substrait_validator::Classification::from_code(class)
+        .ok_or_else(|| PyValueError::new_err(format!("unknown diagnostic class {class}")))
+}
+
 /// Represents a validator/parser configuration.
 #[pyclass]
 struct Config {
@@ -35,6 +53,13 @@ impl Config {
         self.config.ignore_unknown_fields = true;
     }
 
+    /// Instructs the validator to only build the parts of the parse tree
+    /// needed for diagnostics and data types. This is faster and uses less
+    /// memory, but exported trees lack comments and descriptions.
+    pub fn diagnostics_only(&mut self) {
+        self.config.diagnostics_only();
+    }
+
     /// Explicitly allows a protobuf message type to be used in advanced
     /// extensions, despite the fact that the validator can't validate it. If
     /// an advanced extension is encountered that isn't explicitly allowed, a
@@ -64,24 +89,7 @@ impl Config {
         minimum: &str,
         maximum: &str,
     ) -> PyResult<()> {
-        fn str_to_level(level: &str) -> PyResult<::substrait_validator::Level> {
-            match level {
-                "info" => Ok(::substrait_validator::Level::Info),
-                "warning" => Ok(::substrait_validator::Level::Warning),
-                "error" => Ok(::substrait_validator::Level::Error),
-                level => Err(PyValueError::new_err(format!(
-                    "invalid level {level:?}; must be \"info\", \"warning\", or \"error\""
-                ))),
-            }
-        }
-        let class = match ::substrait_validator::Classification::from_code(class) {
-            Some(c) => c,
-            None => {
-                return Err(PyValueError::new_err(format!(
-                    "unknown diagnostic class {class}"
-                )))
-            }
-        };
+        let class = code_to_class(class)?;
         let minimum = str_to_level(minimum)?;
         let maximum = str_to_level(maximum)?;
         self.config
@@ -89,6 +97,37 @@ impl Config {
         Ok(())
     }
 
+    /// Sets a minimum and/or maximum error level for diagnostic messages at or
+    /// below plan paths matching the given pattern, optionally only for the
+    /// given class or group of diagnostics. The pattern is matched against
+    /// the string representation of the path, for example
+    /// "relations[*].root.input", where * and ? are wildcards and square
+    /// brackets match literally. The reason is recorded on the affected
+    /// diagnostics. Rules are tried in the order in which they were added.
+    #[pyo3(signature = (pattern, minimum, maximum, class_code=None, reason=None))]
+    pub fn override_diagnostic_level_at(
+        &mut self,
+        pattern: &str,
+        minimum: &str,
+        maximum: &str,
+        class_code: Option<u32>,
+        reason: Option<String>,
+    ) -> PyResult<()> {
+        let path_pattern = ::substrait_validator::input::config::PathPattern::new(pattern)
+            .map_err(|e| PyValueError::new_err(format!("invalid pattern {pattern:?}: {e}")))?;
+        let mut rule = ::substrait_validator::input::config::DiagnosticPathOverride::new(
+            path_pattern,
+            str_to_level(minimum)?,
+            str_to_level(maximum)?,
+        );
+        if let Some(class) = class_code {
+            rule = rule.for_class(code_to_class(class)?);
+        }
+        rule.reason = reason;
+        self.config.override_diagnostic_level_at(rule);
+        Ok(())
+    }
+
     /// Overrides the resolution behavior for extension URNs matching the given
     /// pattern. The pattern may include * and ? wildcards for glob-like
     /// matching (see https://docs.rs/glob/latest/glob/struct.Pattern.html
@@ -109,6 +148,14 @@ impl Config {
         Ok(())
     }
 
+    /// Maps a URI used by plans that still refer to extensions by URI (via
+    /// the deprecated extension_uris field) to the URN of the extension it
+    /// refers to. The well-known URIs of the standard extensions are mapped
+    /// by default.
+    pub fn map_uri_to_urn(&mut self, uri: &str, urn: &str) {
+        self.config.map_uri_to_urn(uri, urn);
+    }
+
     /// Registers an extension URN resolution function with this configuration.
     /// If the given function fails, any previously registered function will be
     /// used as a fallback. The callback function must take a single string
@@ -135,6 +182,46 @@ impl Config {
     pub fn set_max_urn_resolution_depth(&mut self, depth: Option<usize>) {
         self.config.set_max_urn_resolution_depth(depth);
     }
+
+    /// Sets limits on the resources used for validation. When a limit is
+    /// exceeded, validation stops with a diagnostic and yields a partial
+    /// result. Limits that are not specified are disabled. time_limit is in
+    /// seconds.
+    #[pyo3(signature = (max_tree_depth=None, max_node_count=None, max_literal_bytes=None, time_limit=None))]
+    pub fn set_resource_limits(
+        &mut self,
+        max_tree_depth: Option<usize>,
+        max_node_count: Option<usize>,
+        max_literal_bytes: Option<usize>,
+        time_limit: Option<f64>,
+    ) -> PyResult<()> {
+        let time_limit = time_limit
+            .map(std::time::Duration::try_from_secs_f64)
+            .transpose()
+            .map_err(|e| PyValueError::new_err(e.to_string()))?;
+        self.config.set_max_tree_depth(max_tree_depth);
+        self.config.set_max_node_count(max_node_count);
+        self.config.set_max_literal_bytes(max_literal_bytes);
+        self.config.set_time_limit(time_limit);
+        Ok(())
+    }
+
+    /// Checks plans against the consumer capability profile in the given YAML
+    /// file, emitting diagnostics for anything the consumer does not support.
+    pub fn load_consumer_profile(&mut self, path: &str) -> PyResult<()> {
+        let profile = ::substrait_validator::input::profile::ConsumerProfile::read(path)
+            .map_err(|e| PyValueError::new_err(e.to_string()))?;
+        self.config.set_consumer_profile(profile);
+        Ok(())
+    }
+
+    /// Applies the settings in the given YAML configuration file to this
+    /// configuration.
+    pub fn load_file(&mut self, path: &str) -> PyResult<()> {
+        self.config
+            .load_file(path)
+            .map_err(|e| PyValueError::new_err(e.to_string()))
+    }
 }
 
 /// Represents a Substrait plan parse tree, as parsed by the validator.
@@ -164,6 +251,23 @@ impl ResultHandle {
         }
     }
 
+    /// Parses and validates the given simple extension YAML file on its own,
+    /// rather than as part of a plan.
+    #[staticmethod]
+    #[pyo3(signature = (data, config=None))]
+    pub fn from_extension(data: &[u8], config: Option<&Config>) -> Self {
+        Self {
+            root: if let Some(config) = config {
+                ::substrait_validator::validate_extension(data, &config.config)
+            } else {
+                ::substrait_validator::validate_extension(
+                    data,
+                    &::substrait_validator::Config::default(),
+                )
+            },
+        }
+    }
+
     /// Checks the validity of the plan passed to this ParseResult during
     /// construction. Returns -1 for invalid plans, 0 for possibly valid
     /// plans (i.e. the validator was unable to prove validity either way),
@@ -211,6 +315,35 @@ impl ResultHandle {
         Ok(result)
     }
 
+    /// Exports the relation graph of the plan as a Graphviz DOT string.
+    pub fn export_dot(&self) -> PyResult<String> {
+        let mut result: Vec<u8> = vec![];
+        self.root
+            .export(&mut result, ::substrait_validator::export::Format::Dot)?;
+        let result = String::from_utf8(result)?;
+        Ok(result)
+    }
+
+    /// Exports an EXPLAIN-like plain-text description of the relation trees
+    /// of the plan as a multiline string.
+    pub fn export_explain(&self) -> PyResult<String> {
+        let mut result: Vec<u8> = vec![];
+        self.root
+            .export(&mut result, ::substrait_validator::export::Format::Explain)?;
+        let result = String::from_utf8(result)?;
+        Ok(result)
+    }
+
+    /// Exports a Markdown report of the diagnostics and relations of the plan
+    /// as a multiline string.
+    pub fn export_markdown(&self) -> PyResult<String> {
+        let mut result: Vec<u8> = vec![];
+        self.root
+            .export(&mut result, ::substrait_validator::export::Format::Markdown)?;
+        let result = String::from_utf8(result)?;
+        Ok(result)
+    }
+
     /// Exports the parse tree as a HTML multiline string, intended for
     /// debugging.
     pub fn export_html(&self) -> PyResult<String> {
@@ -221,6 +354,37 @@ impl ResultHandle {
         Ok(result)
     }
 
+    /// Exports all diagnostic messages contained in this parse result as a
+    /// JSON string, along with a summary of the validity of the plan.
+    pub fn export_json(&self) -> PyResult<String> {
+        let mut result: Vec<u8> = vec![];
+        self.root
+            .export(&mut result, ::substrait_validator::export::Format::Json)?;
+        let result = String::from_utf8(result)?;
+        Ok(result)
+    }
+
+    /// Exports all diagnostic messages contained in this parse result as a
+    /// SARIF 2.1.0 log. If specified, artifact_uri is used as the artifact
+    /// location for diagnostics that refer to the plan.
+    #[pyo3(signature = (artifact_uri=None))]
+    pub fn export_sarif(&self, artifact_uri: Option<&str>) -> PyResult<String> {
+        let mut result: Vec<u8> = vec![];
+        ::substrait_validator::export::export_sarif(&mut result, &self.root, artifact_uri)?;
+        let result = String::from_utf8(result)?;
+        Ok(result)
+    }
+
+    /// Exports this parse result as a JUnit XML document with a single
+    /// testcase.
+    pub fn export_junit(&self) -> PyResult<String> {
+        let mut result: Vec<u8> = vec![];
+        self.root
+            .export(&mut result, ::substrait_validator::export::Format::Junit)?;
+        let result = String::from_utf8(result)?;
+        Ok(result)
+    }
+
     /// Exports the entire parse tree as a substrait.validator.Node protobuf
     /// message, using binary serialization.
     pub fn export_proto(&self, py: Python) -> PyResult<Py<PyAny>> {
@@ -280,7 +444,29 @@ fn substrait_validator(_py: Python, m: &Bound<'_, PyModule>) -> PyResult<()> {
             .into_py_any(py)
     }
 
+    /// Exports the given list of (name, ResultHandle) pairs as a single
+    /// JUnit XML document, with a testsuite of the given name and a testcase
+    /// for each result.
+    #[pyfunction]
+    #[pyo3(name = "export_junit")]
+    fn export_junit_py(
+        suite_name: &str,
+        results: Vec<(String, PyRef<ResultHandle>)>,
+    ) -> PyResult<String> {
+        let mut result: Vec<u8> = vec![];
+        ::substrait_validator::export::export_junit(
+            &mut result,
+            suite_name,
+            results
+                .iter()
+                .map(|(name, handle)| (name.as_str(), &handle.root)),
+        )?;
+        let result = String::from_utf8(result)?;
+        Ok(result)
+    }
+
     let _ = m.add_function(wrap_pyfunction!(get_diagnostic_codes_py, m)?);
+    let _ = m.add_function(wrap_pyfunction!(export_junit_py, m)?);
     let _ = m.add_function(wrap_pyfunction!(get_version_py, m)?);
     let _ = m.add_function(wrap_pyfunction!(get_substrait_version_py, m)?);
     m.add_class::<Config>()?;
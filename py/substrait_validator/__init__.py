@@ -13,6 +13,7 @@ from .substrait_validator import (
     ResultHandle,
     Config as _Config,
     get_diagnostic_codes,
+    export_junit as _export_junit,
     get_version as _get_version,
     get_substrait_version as _get_substrait_version,
 )
@@ -198,6 +199,16 @@ def plan_to_result_handle(plan, config=None) -> ResultHandle:
     return ResultHandle(data, Config._unwrap(config))
 
 
+def extension_to_result_handle(extension, config=None) -> ResultHandle:
+    """Validates a simple extension YAML file on its own, rather than as part
+    of a plan, and returns its result handle object. extension must be the
+    contents of the file as bytes or str. The result handle can be passed to
+    any function that accepts one in place of a plan."""
+    if isinstance(extension, str):
+        extension = extension.encode("utf-8")
+    return ResultHandle.from_extension(extension, Config._unwrap(config))
+
+
 def plan_to_parse_result(plan, config=None) -> ParseResult:
     """Parses the given plan with the validator, and returns its parse result.
     plan can be anything supported by load_plan(), a Plan object, or a
@@ -236,6 +247,58 @@ def plan_to_diagnostics_str(plan, config=None) -> str:
     return plan_to_result_handle(plan, config).export_diagnostics()
 
 
+def plan_to_diagnostics_json(plan, config=None) -> str:
+    """Converts a plan to a JSON string listing the diagnostic messages
+    returned by the validator for that plan, along with the validity of the
+    plan. plan can be anything supported by plan_to_result_handle()."""
+    return plan_to_result_handle(plan, config).export_json()
+
+
+def plan_to_sarif(plan, config=None, artifact_uri=None) -> str:
+    """Converts a plan to a SARIF 2.1.0 log of the diagnostic messages
+    returned by the validator for that plan. plan can be anything supported by
+    plan_to_result_handle(). If specified, artifact_uri is used as the artifact
+    location for diagnostics that refer to the plan, such as the path of the
+    file it was read from."""
+    return plan_to_result_handle(plan, config).export_sarif(artifact_uri)
+
+
+def plan_to_junit(plan, config=None) -> str:
+    """Converts a plan to a JUnit XML document with a single testcase, which
+    fails if the plan is invalid. plan can be anything supported by
+    plan_to_result_handle()."""
+    return plan_to_result_handle(plan, config).export_junit()
+
+
+def plans_to_junit(suite_name: str, plans, config=None) -> str:
+    """Validates multiple plans and converts the results to a single JUnit XML
+    document, with a testcase for each plan. plans must be an iterable of
+    (name, plan) pairs, where each plan can be anything supported by
+    plan_to_result_handle()."""
+    results = [(name, plan_to_result_handle(plan, config)) for name, plan in plans]
+    return _export_junit(suite_name, results)
+
+
+def plan_to_dot(plan, config=None) -> str:
+    """Generates a Graphviz DOT graph of the relations in the given plan.
+    plan can be anything supported by plan_to_result_handle()."""
+    return plan_to_result_handle(plan, config).export_dot()
+
+
+def plan_to_explain(plan, config=None) -> str:
+    """Generates an EXPLAIN-like plain-text description of the relations in
+    the given plan, with the diagnostics listed below the relation they
+    belong to. plan can be anything supported by plan_to_result_handle()."""
+    return plan_to_result_handle(plan, config).export_explain()
+
+
+def plan_to_markdown(plan, config=None) -> str:
+    """Generates a Markdown report for the given plan, intended to be posted
+    as a pull request comment. plan can be anything supported by
+    plan_to_result_handle()."""
+    return plan_to_result_handle(plan, config).export_markdown()
+
+
 def plan_to_html(plan, config=None) -> str:
     """Generates a HTML page for the given plan to serve as documentation
     while debugging. plan can be anything supported by
@@ -327,7 +390,22 @@ def substrait_version() -> str:
 @click.option(
     "--out-type",
     type=click.Choice(
-        ["ext", "diag", "html", "proto", "json", "yaml", "jdot"], case_sensitive=False
+        [
+            "ext",
+            "diag",
+            "diag-json",
+            "sarif",
+            "junit",
+            "dot",
+            "explain",
+            "markdown",
+            "html",
+            "proto",
+            "json",
+            "yaml",
+            "jdot",
+        ],
+        case_sensitive=False,
     ),
     default="ext",
     help=(
@@ -390,6 +468,26 @@ def substrait_version() -> str:
         "that owner's extensions."
     ),
 )
+@click.option(
+    "--config",
+    "config_file",
+    type=click.Path(exists=True, dir_okay=False),
+    default=None,
+    help=(
+        "Loads validator configuration from the given YAML file before "
+        "applying the other configuration options."
+    ),
+)
+@click.option(
+    "--consumer-profile",
+    type=click.Path(exists=True, dir_okay=False),
+    default=None,
+    help=(
+        "Checks the plan against the consumer capability profile in the "
+        "given YAML file, emitting diagnostics for anything the consumer "
+        "does not support."
+    ),
+)
 @click.option(
     "--urn-depth",
     type=int,
@@ -432,6 +530,8 @@ def cli(  # noqa: C901
     allow_proto_any,
     diagnostic_level,
     override_urn,
+    config_file,
+    consumer_profile,
     urn_depth,
     help_diagnostics,
     print_version,
@@ -652,6 +752,11 @@ def cli(  # noqa: C901
             "yaml": "yaml",
             "jdot": "jdot",
             "txt": "diag",
+            "sarif": "sarif",
+            "xml": "junit",
+            "dot": "dot",
+            "gv": "dot",
+            "md": "markdown",
             "html": "html",
             "htm": "html",
         },
@@ -724,6 +829,11 @@ def cli(  # noqa: C901
 
     # Construct parser/validator configuration.
     config = Config()
+    if config_file is not None:
+        try:
+            config.load_file(config_file)
+        except ValueError as e:
+            fatal(e)
     if ignore_unknown_fields:
         config.ignore_unknown_fields()
     for pattern in allow_proto_any:
@@ -757,6 +867,11 @@ def cli(  # noqa: C901
             config.set_max_urn_resolution_depth(None)
         else:
             config.set_max_urn_resolution_depth(urn_depth)
+    if consumer_profile is not None:
+        try:
+            config.load_consumer_profile(consumer_profile)
+        except ValueError as e:
+            fatal(e)
 
     # Run the parser/validator.
     result = plan_to_result_handle(in_plan, config)
@@ -785,6 +900,19 @@ def cli(  # noqa: C901
     # Emit output file.
     if out_type == "diag":
         emit_output(plan_to_diagnostics_str(result))
+    elif out_type == "diag-json":
+        emit_output(plan_to_diagnostics_json(result))
+    elif out_type == "sarif":
+        artifact_uri = None if in_file == "-" else in_file
+        emit_output(plan_to_sarif(result, artifact_uri=artifact_uri))
+    elif out_type == "junit":
+        emit_output(plan_to_junit(result))
+    elif out_type == "dot":
+        emit_output(plan_to_dot(result))
+    elif out_type == "explain":
+        emit_output(plan_to_explain(result))
+    elif out_type == "markdown":
+        emit_output(plan_to_markdown(result))
     elif out_type == "html":
         emit_output(plan_to_html(result))
     else:
@@ -112,6 +112,75 @@ def test_valid_invalid():
         sv.check_plan_not_invalid(plan)
 
 
+def test_path_overrides():
+    """Test path-scoped diagnostic level overrides."""
+    config = sv.Config()
+    config.override_diagnostic_level(0, "error", "error")
+    config.override_diagnostic_level_at("plan", "info", "info", reason="testing")
+    plan = sv.plan_to_result_handle(BASIC_PLAN, config)
+    sv.check_plan_valid(plan)
+    diags = list(sv.plan_to_diagnostics(plan))
+    assert diags
+    for diag in diags:
+        assert diag.suppression.pattern == "plan"
+        assert diag.suppression.reason == "testing"
+
+    # Rules only apply to matching paths.
+    config = sv.Config()
+    config.override_diagnostic_level(0, "error", "error")
+    config.override_diagnostic_level_at("relations[*].unknown_field", "info", "info")
+    plan = sv.plan_to_result_handle(BASIC_PLAN, config)
+    assert sv.check_plan(plan) == -1
+
+    with pytest.raises(ValueError):
+        config.override_diagnostic_level_at("plan", "info", "info", 12345)
+
+
+def test_consumer_profile(tmp_path):
+    """Test checking plans against a consumer capability profile."""
+    (tmp_path / "profile.yaml").write_text("name: engine\nrelations: [read]\n")
+    config = sv.Config()
+    config.load_consumer_profile(str(tmp_path / "profile.yaml"))
+    diags = list(sv.plan_to_diagnostics(BASIC_PLAN, config))
+    unsupported = [diag for diag in diags if diag.cause == 8001]
+    assert unsupported
+    assert "engine does not support project relations" in unsupported[0].msg
+
+    with pytest.raises(ValueError):
+        config.load_consumer_profile(str(tmp_path / "missing.yaml"))
+
+
+def test_resource_limits():
+    """Test limiting the resources used for validation."""
+    config = sv.Config()
+    config.set_resource_limits(max_node_count=10)
+    diags = list(sv.plan_to_diagnostics(BASIC_PLAN, config))
+    assert len([diag for diag in diags if diag.cause == 9]) == 1
+
+    with pytest.raises(ValueError):
+        config.set_resource_limits(time_limit=-1.0)
+
+
+def test_diagnostics_only():
+    """Test validating without building the documentation tree."""
+    config = sv.Config()
+    config.diagnostics_only()
+    diags = [diag.msg for diag in sv.plan_to_diagnostics(BASIC_PLAN, config)]
+    assert diags == [diag.msg for diag in sv.plan_to_diagnostics(BASIC_PLAN)]
+
+
+def test_extension_validation():
+    """Test validating an extension file on its own."""
+    handle = sv.extension_to_result_handle(BASIC_YAML)
+    assert sv.check_plan(handle) != -1
+
+    handle = sv.extension_to_result_handle(b"urn: 1\n")
+    assert sv.check_plan(handle) == -1
+    diags = list(sv.plan_to_diagnostics(handle))
+    assert any(diag.cause == 2004 for diag in diags)
+    assert all(diag.path.root == "extension" for diag in diags)
+
+
 def test_resolver_callback():
     """Tests whether the extension URN resolver callback works."""
 
@@ -175,6 +244,45 @@ def test_resolver_callback():
         )
 
 
+def test_config_file(tmp_path):
+    """Tests whether configuration can be loaded from a file, including
+    resolution of extension URNs from a directory."""
+    (tmp_path / "extensions").mkdir()
+    (tmp_path / "extensions" / "types.yaml").write_text(BASIC_YAML)
+    (tmp_path / "config.yaml").write_text(
+        """
+diagnostic_level_overrides:
+  - {class: 1, max: info}
+  - {class: 7, max: info}
+  - {class: 5001, max: info}
+urn_directories: [extensions]
+max_urn_resolution_depth: -1
+"""
+    )
+
+    config = sv.Config()
+    config.load_file(str(tmp_path / "config.yaml"))
+    sv.check_plan_valid(
+        {
+            "version": {
+                "minorNumber": 1,
+                "producer": "validator-test",
+            },
+            "extensionUrns": [
+                {
+                    "extension_urn_anchor": 1,
+                    "urn": "extension:io.substrait:extension_types",
+                }
+            ],
+        },
+        config,
+    )
+
+    (tmp_path / "invalid.yaml").write_text("unknown_option: 1")
+    with pytest.raises(ValueError, match="unknown_option"):
+        sv.Config().load_file(str(tmp_path / "invalid.yaml"))
+
+
 def release_segment(version):
     """Returns the major.minor.patch prefix of a version string, dropping any
     pre-release suffix. Used to compare versions across PEP 440 and semver,
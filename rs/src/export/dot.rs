@@ -0,0 +1,129 @@
+// SPDX-License-Identifier: Apache-2.0
+
+//! This module provides an export format for the relation graph of a plan,
+//! using the Graphviz DOT language.
+//!
+//! Only relations are emitted as nodes. They are labeled with their brief
+//! description and output schema, and colored by the highest severity of the
+//! diagnostics within the relation (excluding those of its input relations).
+//! Edges point in the direction of data flow, i.e. from an input relation to
+//! the relation consuming it. Subqueries used within expressions are drawn
+//! using dashed edges.
+
+use super::relations;
+use crate::output::diagnostic;
+use crate::output::parse_result;
+use crate::output::path;
+
+/// Escapes the given string for use in a quoted DOT string.
+fn escape<S: AsRef<str>>(text: S) -> String {
+    let mut result = String::with_capacity(text.as_ref().len());
+    for c in text.as_ref().chars() {
+        match c {
+            '"' => result += "\\\"",
+            '\\' => result += "\\\\",
+            '\n' => result += "\\n",
+            c => result.push(c),
+        }
+    }
+    result
+}
+
+/// Returns the fill color for a relation based on the highest severity of
+/// its diagnostics.
+fn color(level: diagnostic::Level) -> &'static str {
+    match level {
+        diagnostic::Level::Info => "#d8f0d8",
+        diagnostic::Level::Warning => "#fbe8b0",
+        diagnostic::Level::Error => "#f8c8c8",
+    }
+}
+
+/// The graph being constructed.
+#[derive(Default)]
+struct Graph {
+    /// The node statements of the graph.
+    nodes: Vec<String>,
+
+    /// The edge statements of the graph.
+    edges: Vec<String>,
+}
+
+impl Graph {
+    /// Adds a relation node and its inputs to the graph, as well as the edge
+    /// to the relation consuming it, if any.
+    fn add_relation(&mut self, relation: &relations::Relation, consumer: Option<&str>) {
+        let id = format!("rel{}", self.nodes.len());
+        let mut label = relation.brief();
+        if let Some(data_type) = &relation.node.data_type {
+            label += &format!("\n{data_type}");
+        }
+        self.nodes.push(format!(
+            "  {id} [label=\"{}\", tooltip=\"{}\", fillcolor=\"{}\"];",
+            escape(label),
+            escape(relation.path.to_string()),
+            color(relation.level())
+        ));
+
+        if let Some(consumer) = consumer {
+            self.edges.push(format!(
+                "  {id} -> {consumer}{};",
+                if relation.subquery {
+                    " [style=dashed, label=\"subquery\"]"
+                } else {
+                    ""
+                }
+            ));
+        }
+
+        for input in relation.inputs.iter() {
+            self.add_relation(input, Some(&id));
+        }
+    }
+}
+
+/// Export the relation graph of the plan in Graphviz DOT format.
+pub fn export<T: std::io::Write>(
+    out: &mut T,
+    root_name: &'static str,
+    result: &parse_result::ParseResult,
+) -> std::io::Result<()> {
+    let mut graph = Graph::default();
+    let plan = relations::Relation::gather(&path::Path::Root(root_name), &result.root, false);
+    for relation in plan.inputs.iter() {
+        graph.add_relation(relation, None);
+    }
+
+    writeln!(out, "digraph {root_name} {{")?;
+    writeln!(out, "  node [shape=box, style=\"rounded,filled\"];")?;
+    for node in graph.nodes {
+        writeln!(out, "{node}")?;
+    }
+    for edge in graph.edges {
+        writeln!(out, "{edge}")?;
+    }
+    writeln!(out, "}}")
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::export;
+    use crate::input::config;
+
+    #[test]
+    fn relation_graph() {
+        let result = crate::parse_json(export::relations::TEST_PLAN, &config::Config::default());
+        let mut out = vec![];
+        result.export(&mut out, export::Format::Dot).unwrap();
+        let out = String::from_utf8(out).unwrap();
+
+        assert!(out.starts_with("digraph plan {"));
+        assert!(out.contains("  rel0 [label=\"Filter by true"));
+        assert!(out.contains("  rel1 [label=\""));
+        assert!(out.contains("  rel2 [label=\"Read from virtual table"));
+        assert!(out.contains("  rel1 -> rel0;"));
+        assert!(out.contains("  rel2 -> rel1;"));
+        assert!(out.contains("fillcolor=\"#f8c8c8\""));
+        assert!(!out.contains("rel3"));
+    }
+}
@@ -0,0 +1,115 @@
+// SPDX-License-Identifier: Apache-2.0
+
+//! This module provides a plain-text export format that describes the plan
+//! in a way similar to the EXPLAIN output of a database.
+//!
+//! Each relation is printed on a single line using its brief description and
+//! output schema, with its input relations indented below it. The toplevel
+//! expressions of a relation and the diagnostics that belong to it are
+//! printed directly below the relation line. Diagnostics that do not belong
+//! to any relation are printed at the top.
+
+use super::relations;
+use crate::output::parse_result;
+use crate::output::path;
+
+/// Writes the description of a relation and its inputs.
+fn write_relation<T: std::io::Write>(
+    out: &mut T,
+    relation: &relations::Relation,
+    depth: usize,
+) -> std::io::Result<()> {
+    let indent = "  ".repeat(depth);
+    write!(
+        out,
+        "{indent}- {}{}",
+        if relation.subquery { "subquery: " } else { "" },
+        relation.brief()
+    )?;
+    if let Some(data_type) = &relation.node.data_type {
+        write!(out, " -> {data_type}")?;
+    }
+    writeln!(out)?;
+
+    for (path, expression) in relation.expressions.iter() {
+        writeln!(
+            out,
+            "{indent}    {}: {}",
+            relation.relative_path(path),
+            expression.expression.as_deref().unwrap_or("?")
+        )?;
+    }
+    for diag in relation.diagnostics.iter() {
+        writeln!(out, "{indent}    {diag}")?;
+    }
+    for input in relation.inputs.iter() {
+        write_relation(out, input, depth + 1)?;
+    }
+    Ok(())
+}
+
+/// Export the relation trees of the plan as indented plain text.
+pub fn export<T: std::io::Write>(
+    out: &mut T,
+    root_name: &'static str,
+    result: &parse_result::ParseResult,
+) -> std::io::Result<()> {
+    writeln!(
+        out,
+        "Validation result: {}",
+        match result.check() {
+            parse_result::Validity::Valid => "valid",
+            parse_result::Validity::MaybeValid => "unable to determine validity",
+            parse_result::Validity::Invalid => "invalid",
+        }
+    )?;
+
+    let plan = relations::Relation::gather(&path::Path::Root(root_name), &result.root, false);
+    for diag in plan.diagnostics.iter() {
+        writeln!(out, "{diag}")?;
+    }
+    for relation in plan.inputs.iter() {
+        writeln!(out)?;
+        write_relation(out, relation, 0)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::export;
+    use crate::input::config;
+
+    #[test]
+    fn explain() {
+        let result = crate::parse_json(export::relations::TEST_PLAN, &config::Config::default());
+        let mut out = vec![];
+        result.export(&mut out, export::Format::Explain).unwrap();
+        let out = String::from_utf8(out).unwrap();
+        let lines = out.lines().collect::<Vec<_>>();
+
+        assert!(lines[0].starts_with("Validation result: "));
+        let filter = lines
+            .iter()
+            .position(|l| l.starts_with("- Filter by true"))
+            .unwrap();
+        assert_eq!(lines[filter + 1], "    condition: true");
+        let fetch = filter
+            + lines[filter..]
+                .iter()
+                .position(|l| l.starts_with("  - "))
+                .unwrap();
+        assert!(lines[fetch + 1..]
+            .iter()
+            .any(|l| l.starts_with("    - Read from virtual table")));
+
+        // Expressions are rendered even if no documentation was generated.
+        let mut config = config::Config::default();
+        config.diagnostics_only();
+        let result = crate::parse_json(export::relations::TEST_PLAN, &config);
+        let mut out = vec![];
+        result.export(&mut out, export::Format::Explain).unwrap();
+        let out = String::from_utf8(out).unwrap();
+        assert!(out.lines().any(|l| l == "    condition: true"));
+    }
+}
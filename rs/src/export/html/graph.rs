@@ -0,0 +1,170 @@
+// SPDX-License-Identifier: Apache-2.0
+
+//! Module for rendering the relation graphs of a plan as an inline SVG image
+//! for the HTML export.
+//!
+//! The layout is a simple layered tree layout: each relation is placed one
+//! layer below the relation consuming it, leaf relations are spread out
+//! horizontally in the order in which they appear in the plan, and other
+//! relations are centered above their inputs. Each relation box links to the
+//! card for the relation in the node tree.
+
+use super::{anchor_name, html_escape};
+use crate::export::relations;
+use crate::output::diagnostic;
+
+/// Width of a relation box in pixels.
+const NODE_WIDTH: usize = 180;
+
+/// Height of a relation box in pixels.
+const NODE_HEIGHT: usize = 36;
+
+/// Horizontal space between relation boxes in pixels.
+const H_GAP: usize = 16;
+
+/// Vertical space between layers in pixels.
+const V_GAP: usize = 32;
+
+/// Maximum number of characters of the brief description shown in a box.
+const MAX_LABEL: usize = 24;
+
+/// Truncates the given text to the maximum label length.
+fn truncate(text: &str) -> String {
+    if text.chars().count() > MAX_LABEL {
+        let mut result = text.chars().take(MAX_LABEL - 1).collect::<String>();
+        result.push('\u{2026}');
+        result
+    } else {
+        text.to_string()
+    }
+}
+
+/// The SVG image being constructed.
+#[derive(Default)]
+struct Canvas {
+    /// SVG elements for the edges. These are drawn first, such that the
+    /// boxes are drawn on top of them.
+    edges: Vec<String>,
+
+    /// SVG elements for the relation boxes.
+    nodes: Vec<String>,
+
+    /// Number of horizontal slots used so far.
+    slots: usize,
+
+    /// Number of layers used.
+    layers: usize,
+}
+
+impl Canvas {
+    /// Places the given relation and its inputs, and returns the horizontal
+    /// center of the relation box.
+    fn place(&mut self, relation: &relations::Relation, depth: usize) -> usize {
+        self.layers = std::cmp::max(self.layers, depth + 1);
+        let centers = relation
+            .inputs
+            .iter()
+            .map(|input| self.place(input, depth + 1))
+            .collect::<Vec<_>>();
+        let center = match (centers.first(), centers.last()) {
+            (Some(first), Some(last)) => (first + last) / 2,
+            _ => {
+                self.slots += 1;
+                (self.slots - 1) * (NODE_WIDTH + H_GAP) + H_GAP + NODE_WIDTH / 2
+            }
+        };
+
+        let top = depth * (NODE_HEIGHT + V_GAP) + V_GAP / 2;
+        for (input, input_center) in relation.inputs.iter().zip(centers) {
+            self.edges.push(format!(
+                "<line class=\"{}\" x1=\"{input_center}\" y1=\"{}\" x2=\"{center}\" y2=\"{}\" marker-end=\"url(#arrow)\"/>",
+                if input.subquery { "edge subquery" } else { "edge" },
+                top + NODE_HEIGHT + V_GAP,
+                top + NODE_HEIGHT,
+            ));
+        }
+
+        let brief = relation.brief();
+        let class = match relation.level() {
+            diagnostic::Level::Info => "ok",
+            diagnostic::Level::Warning => "warn",
+            diagnostic::Level::Error => "error",
+        };
+        self.nodes.push(format!(
+            concat!(
+                "<a href=\"#{}\"><title>{}\n{}</title>",
+                "<rect class=\"{}\" x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" rx=\"6\"/>",
+                "<text x=\"{}\" y=\"{}\">{}</text></a>"
+            ),
+            html_escape(anchor_name(&relation.path, None)),
+            html_escape(&brief),
+            html_escape(relation.path.to_string()),
+            class,
+            center - NODE_WIDTH / 2,
+            top,
+            NODE_WIDTH,
+            NODE_HEIGHT,
+            center,
+            top + NODE_HEIGHT / 2,
+            html_escape(truncate(&brief)),
+        ));
+        center
+    }
+}
+
+/// Formats the relation graphs of the plan as an SVG image, given the
+/// relation view of the root of the tree. Returns None if the plan has no
+/// relations.
+pub fn format_relation_graph(plan: &relations::Relation) -> Option<String> {
+    if plan.inputs.is_empty() {
+        return None;
+    }
+    let mut canvas = Canvas::default();
+    for relation in plan.inputs.iter() {
+        canvas.place(relation, 0);
+    }
+    let width = canvas.slots * (NODE_WIDTH + H_GAP) + H_GAP;
+    let height = canvas.layers * (NODE_HEIGHT + V_GAP);
+
+    let mut svg = vec![format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" class=\"relation_graph\" width=\"{width}\" height=\"{height}\" viewBox=\"0 0 {width} {height}\">"
+    )];
+    svg.push(
+        concat!(
+            "<defs><marker id=\"arrow\" viewBox=\"0 0 10 10\" refX=\"10\" refY=\"5\" ",
+            "markerWidth=\"8\" markerHeight=\"8\" orient=\"auto-start-reverse\">",
+            "<path d=\"M 0 0 L 10 5 L 0 10 z\"/></marker></defs>"
+        )
+        .to_string(),
+    );
+    svg.extend(canvas.edges);
+    svg.extend(canvas.nodes);
+    svg.push("</svg>".to_string());
+    Some(svg.join("\n"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::input::config;
+    use crate::output::path;
+
+    #[test]
+    fn relation_graph_svg() {
+        let result = crate::parse_json(relations::TEST_PLAN, &config::Config::default());
+        let plan = relations::Relation::gather(&path::Path::Root("plan"), &result.root, false);
+        let svg = format_relation_graph(&plan).unwrap();
+
+        assert_eq!(svg.matches("<rect ").count(), 3);
+        assert_eq!(svg.matches("<line ").count(), 2);
+        assert!(svg.contains("<rect class=\"error\""));
+        assert!(svg.contains("href=\"#plan.relations(0).rel_type(rel).rel_type(filter)\""));
+    }
+
+    #[test]
+    fn no_relations() {
+        let result = crate::parse(&b""[..], &config::Config::default());
+        let plan = relations::Relation::gather(&path::Path::Root("plan"), &result.root, false);
+        assert!(format_relation_graph(&plan).is_none());
+    }
+}
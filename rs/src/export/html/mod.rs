@@ -2,6 +2,7 @@
 
 //! This module provides a human-readable export format based on HTML.
 
+use super::relations;
 use crate::output::comment;
 use crate::output::diagnostic;
 use crate::output::parse_result;
@@ -11,6 +12,8 @@ use crate::output::type_system::data;
 use crate::output::type_system::data::class::ParameterInfo;
 use base64::{engine::general_purpose, Engine as _};
 
+mod graph;
+
 const HEADER1: &str = concat!(
     r#"
 <!DOCTYPE html>
@@ -48,8 +51,75 @@ const HEADER2: &str = concat!(
 "#
 );
 
+const TOOLBAR: &str = r#"
+<div class="toolbar">
+<input type="search" id="search" placeholder="Search node paths and descriptions" oninput="search(this.value)">
+<label><input type="checkbox" id="show_info" checked onchange="filter_levels()"> Info</label>
+<label><input type="checkbox" id="show_warn" checked onchange="filter_levels()"> Warnings</label>
+<button type="button" onclick="next_error()">Jump to next error</button>
+<span id="error_status"></span>
+<div id="search_results"></div>
+</div>
+"#;
+
 const FOOTER: &str = r#"
 <script>
+function search(query) {
+    var results = document.getElementById('search_results');
+    results.innerHTML = '';
+    query = query.trim().toLowerCase();
+    if (query.length < 2) {
+        return;
+    }
+    var cards = document.querySelectorAll('.node_tree details[id], .node_tree div.card[id]');
+    var count = 0;
+    for (var i = 0; i < cards.length; i++) {
+        var card = cards[i];
+        if (card.classList.contains('diag_info') ||
+            card.classList.contains('diag_warn') ||
+            card.classList.contains('diag_error')) {
+            continue;
+        }
+        var path = decodeURIComponent(card.id);
+        var brief = card.querySelector(':scope > summary > span.brief, :scope > span.brief');
+        var text = brief ? brief.textContent : '';
+        if (path.toLowerCase().indexOf(query) < 0 && text.toLowerCase().indexOf(query) < 0) {
+            continue;
+        }
+        if (count === 100) {
+            var more = document.createElement('div');
+            more.className = 'note';
+            more.textContent = 'More than 100 results; refine the search to see more.';
+            results.appendChild(more);
+            return;
+        }
+        var link = document.createElement('a');
+        link.href = '#' + card.id;
+        link.textContent = path + (text ? ': ' + text : '');
+        results.appendChild(link);
+        count++;
+    }
+    if (count === 0) {
+        results.innerHTML = '<div class="note">No matches.</div>';
+    }
+}
+function filter_levels() {
+    document.body.classList.toggle('hide_info', !document.getElementById('show_info').checked);
+    document.body.classList.toggle('hide_warn', !document.getElementById('show_warn').checked);
+}
+var error_index = -1;
+function next_error() {
+    var errors = document.querySelectorAll('.node_tree div.diag_error[id]');
+    var status = document.getElementById('error_status');
+    if (errors.length === 0) {
+        status.textContent = 'No errors.';
+        return;
+    }
+    error_index = (error_index + 1) % errors.length;
+    status.textContent = 'Error ' + (error_index + 1) + ' of ' + errors.length;
+    location.hash = errors[error_index].id;
+    errors[error_index].scrollIntoView({block: 'center'});
+}
 function open_cards(element) {
     if (element.tagName.toLowerCase() === 'details') {
         element.open = true;
@@ -176,13 +246,19 @@ fn format_path(path: &path::PathBuf, index: Option<usize>) -> String {
     }
 }
 
+/// Returns the name of the anchor for a node (index = None) or diagnostic
+/// (index = Some(index of NodeData entry)), without HTML escaping. Other
+/// export formats can use this to link into the HTML export.
+pub(super) fn anchor_name(path: &path::PathBuf, index: Option<usize>) -> String {
+    url_encode(path_encode(format_path(path, index)))
+}
+
 /// Formats the parameters of an <a> tag to a node or diagnostic.
 fn format_reference_parameters(path: &path::PathBuf, index: Option<usize>) -> String {
-    let path = format_path(path, index);
     format!(
         "href=\"#{}\" title=\"{}\"",
-        html_escape(url_encode(path_encode(&path))),
-        html_escape(&path)
+        html_escape(anchor_name(path, index)),
+        html_escape(format_path(path, index))
     )
 }
 
@@ -208,10 +284,7 @@ fn format_anchor(path: &path::PathBuf, index: Option<usize>) -> String {
 /// Formats the id parameter for a div/details tag for a node (index = None)
 /// or diagnostic (index = Some(index of NodeData entry)).
 fn format_id(path: &path::PathBuf, index: Option<usize>) -> String {
-    format!(
-        "id=\"{}\"",
-        html_escape(url_encode(path_encode(format_path(path, index))))
-    )
+    format!("id=\"{}\"", html_escape(anchor_name(path, index)))
 }
 
 /// Creates a span with the given class name. The text is HTML-escaped.
@@ -367,75 +440,44 @@ fn format_brief(brief: &comment::Brief) -> String {
 }
 
 // Format the relation trees.
-fn format_relation_tree(
-    path: &path::Path,
-    node: &tree::Node,
-    index: &mut usize,
-    is_root: bool,
-    in_expression: bool,
-) -> Vec<String> {
+fn format_relation_tree(plan: &relations::Relation) -> Vec<String> {
     let mut html = vec![];
+    for (index, relation) in plan.inputs.iter().enumerate() {
+        html.push("<details class=\"relation_tree\">".to_string());
+        html.push(format!("<summary>Query/relation graph #{index}</summary>"));
+        html.push("<ul class=\"tree\"><li><span class=\"root\">Sink</span><ul>".to_string());
+        html.extend(format_relation(relation));
+        html.push("</ul></li></ul>".to_string());
+        html.push("</details>".to_string());
+    }
+    html
+}
 
-    let text = node
+// Format a relation and its inputs as a list item of a relation tree.
+fn format_relation(relation: &relations::Relation) -> Vec<String> {
+    let text = relation
+        .node
         .brief
         .as_ref()
         .map(format_brief)
         .unwrap_or_else(|| String::from("unknown"));
-    let is_relation = matches!(node.class, tree::Class::Relation);
-    let is_expression = matches!(node.class, tree::Class::Expression);
-
-    if is_relation {
-        if is_root {
-            html.push("<details class=\"relation_tree\">".to_string());
-            html.push(format!(
-                "<summary>Query/relation graph #{}</summary>",
-                *index
-            ));
-            html.push("<ul class=\"tree\"><li><span class=\"root\">Sink</span><ul>".to_string());
-        };
-        html.push(format!(
-            "<li><span class=\"{}\">{text} ({})</span>",
-            if in_expression {
-                "subquery"
-            } else {
-                "data_source"
-            },
-            format_reference("link", &path.to_path_buf(), None)
-        ));
-    }
-
-    let mut has_children = false;
-    for data in node.data.iter() {
-        if let tree::NodeData::Child(child) = data {
-            let sub_html = format_relation_tree(
-                &path.with(child.path_element.clone()),
-                &child.node,
-                index,
-                is_root && !is_relation,
-                (in_expression && !is_relation) || is_expression,
-            );
-            if !sub_html.is_empty() {
-                if is_relation && !has_children {
-                    html.push("<ul>".to_string());
-                }
-                has_children = true;
-                html.extend(sub_html);
-            }
-        }
-    }
-
-    if is_relation {
-        if has_children {
-            html.push("</ul>".to_string());
-        }
-        html.push("</li>".to_string());
-        if is_root {
-            html.push("</ul></li></ul>".to_string());
-            html.push("</details>".to_string());
-            *index += 1;
+    let mut html = vec![format!(
+        "<li><span class=\"{}\">{text} ({})</span>",
+        if relation.subquery {
+            "subquery"
+        } else {
+            "data_source"
+        },
+        format_reference("link", &relation.path, None)
+    )];
+    if !relation.inputs.is_empty() {
+        html.push("<ul>".to_string());
+        for input in relation.inputs.iter() {
+            html.extend(format_relation(input));
         }
+        html.push("</ul>".to_string());
     }
-
+    html.push("</li>".to_string());
     html
 }
 
@@ -641,6 +683,22 @@ pub fn export<T: std::io::Write>(
     )?;
     writeln!(out, "</details>")?;
 
+    // Emit the toolbar for searching and filtering.
+    write!(out, "{TOOLBAR}")?;
+
+    // Emit the graphical relation graph.
+    let plan = relations::Relation::gather(&path, &result.root, false);
+    if let Some(svg) = graph::format_relation_graph(&plan) {
+        writeln!(out, "<details class=\"relation_tree\" open=\"true\">")?;
+        writeln!(out, "<summary>Relation graph</summary>")?;
+        writeln!(
+            out,
+            "<div class=\"note\">Note: data flows upwards in this graph. Click a relation to jump to its node.</div>"
+        )?;
+        writeln!(out, "<div class=\"relation_graph\">\n{svg}\n</div>")?;
+        writeln!(out, "</details>")?;
+    }
+
     // Emit the node graph.
     writeln!(out, "<details class=\"relation_tree\" open=\"true\">")?;
     writeln!(out, "<summary>Relation graphs</summary>")?;
@@ -648,8 +706,7 @@ pub fn export<T: std::io::Write>(
         out,
         "<div class=\"note\">Note: data flows upwards in these graphs.</div>"
     )?;
-    let mut index = 0;
-    for s in format_relation_tree(&path, &result.root, &mut index, true, false) {
+    for s in format_relation_tree(&plan) {
         writeln!(out, "{s}")?;
     }
     writeln!(out, "</details>")?;
@@ -688,9 +745,11 @@ pub fn export<T: std::io::Write>(
     writeln!(out, "</details>")?;
 
     // Emit protobuf-level raw node tree.
+    writeln!(out, "<div class=\"node_tree\">")?;
     for s in format_node_tree(&path, false, &result.root).0 {
         writeln!(out, "{s}")?;
     }
+    writeln!(out, "</div>")?;
 
     write!(out, "{FOOTER}")
 }
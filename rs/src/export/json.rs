@@ -0,0 +1,188 @@
+// SPDX-License-Identifier: Apache-2.0
+
+//! This module provides a structured export format for the diagnostic
+//! messages contained in the tree, using plain JSON.
+//!
+//! This is intended for tooling that wants to process validation results
+//! without depending on the protobuf bindings. The output is a single JSON
+//! object of the following form:
+//!
+//! ```text
+//! {
+//!   "validity": "valid" | "maybe_valid" | "invalid",
+//!   "counts": { "info": 0, "warning": 0, "error": 0 },
+//!   "diagnostics": [
+//!     {
+//!       "code": 1,
+//!       "name": "...",
+//!       "group": "...",
+//!       "description": "...",
+//!       "original_level": "info" | "warning" | "error",
+//!       "adjusted_level": "info" | "warning" | "error",
+//!       "message": "...",
+//!       "path": "plan.relations[0]...",
+//!       "path_elements": [ { "field": "relations", "index": 0 }, ... ],
+//!       "span": { "file": null, "start": {...}, "end": {...} },
+//!       "suppression": { "pattern": "...", "reason": "..." | null }
+//!     }
+//!   ]
+//! }
+//! ```
+//!
+//! The span key is only present when the diagnostic refers to text input. The
+//! suppression key is only present when the level of the diagnostic was
+//! adjusted by a path-scoped override rule.
+
+use crate::output::diagnostic;
+use crate::output::parse_result;
+use crate::output::path;
+use crate::output::span;
+use serde_json::json;
+
+/// Returns the JSON representation of a diagnostic level.
+fn level(level: diagnostic::Level) -> &'static str {
+    match level {
+        diagnostic::Level::Info => "info",
+        diagnostic::Level::Warning => "warning",
+        diagnostic::Level::Error => "error",
+    }
+}
+
+/// Returns the JSON representation of a validity summary.
+fn validity(validity: parse_result::Validity) -> &'static str {
+    match validity {
+        parse_result::Validity::Valid => "valid",
+        parse_result::Validity::MaybeValid => "maybe_valid",
+        parse_result::Validity::Invalid => "invalid",
+    }
+}
+
+/// Converts a path element to a JSON object.
+fn path_element(element: &path::PathElement) -> serde_json::Value {
+    match element {
+        path::PathElement::Field(field) => json!({ "field": field }),
+        path::PathElement::Repeated(field, index) => json!({ "field": field, "index": index }),
+        path::PathElement::Variant(field, variant) => {
+            json!({ "field": field, "variant": variant })
+        }
+        path::PathElement::Index(index) => json!({ "index": index }),
+    }
+}
+
+/// Converts a text position to a JSON object.
+fn position(position: &span::Position) -> serde_json::Value {
+    json!({ "line": position.line, "column": position.column })
+}
+
+/// Converts a diagnostic to a JSON object.
+fn diagnostic(diag: &diagnostic::Diagnostic) -> serde_json::Value {
+    let classification = &diag.cause.classification;
+    let mut value = json!({
+        "code": classification.code(),
+        "name": classification.name(),
+        "group": classification.group().name(),
+        "description": classification.description(),
+        "original_level": level(diag.original_level),
+        "adjusted_level": level(diag.adjusted_level),
+        "message": diag.cause.message.to_string(),
+        "path": diag.path.to_string(),
+        "path_elements": diag.path.elements.iter().map(path_element).collect::<Vec<_>>(),
+    });
+    if let Some(span) = &diag.span {
+        value["span"] = json!({
+            "file": span.file,
+            "start": position(&span.start),
+            "end": position(&span.end),
+        });
+    }
+    if let Some(suppression) = &diag.suppression {
+        value["suppression"] = json!({
+            "pattern": suppression.pattern,
+            "reason": suppression.reason,
+        });
+    }
+    value
+}
+
+/// Export the diagnostic messages of the tree as a JSON object.
+pub fn export<T: std::io::Write>(
+    out: &mut T,
+    _root_name: &'static str,
+    result: &parse_result::ParseResult,
+) -> std::io::Result<()> {
+    let mut counts = [0usize; 3];
+    let diagnostics = result
+        .iter_diagnostics()
+        .map(|diag| {
+            counts[diag.adjusted_level as usize] += 1;
+            diagnostic(diag)
+        })
+        .collect::<Vec<_>>();
+    let value = json!({
+        "validity": validity(result.check()),
+        "counts": {
+            "info": counts[diagnostic::Level::Info as usize],
+            "warning": counts[diagnostic::Level::Warning as usize],
+            "error": counts[diagnostic::Level::Error as usize],
+        },
+        "diagnostics": diagnostics,
+    });
+    serde_json::to_writer_pretty(&mut *out, &value)?;
+    writeln!(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::export;
+    use crate::input::config;
+
+    #[test]
+    fn json_diagnostics() {
+        let result = crate::parse(&b"\x12\x00"[..], &config::Config::default());
+        let mut out = vec![];
+        result.export(&mut out, export::Format::Json).unwrap();
+        let value: serde_json::Value = serde_json::from_slice(&out).unwrap();
+
+        let diagnostics = value["diagnostics"].as_array().unwrap();
+        assert_eq!(
+            value["counts"]["error"].as_u64().unwrap() as usize,
+            diagnostics
+                .iter()
+                .filter(|d| d["adjusted_level"] == "error")
+                .count()
+        );
+        assert_eq!(value["validity"], "invalid");
+        let diag = diagnostics
+            .iter()
+            .find(|d| d["adjusted_level"] == "error")
+            .unwrap();
+        assert!(diag["code"].is_u64());
+        assert!(diag["name"].is_string());
+        assert!(diag["path_elements"].is_array());
+        assert!(diag["path"].as_str().unwrap().starts_with("plan"));
+        assert!(diag.get("suppression").is_none());
+    }
+
+    #[test]
+    fn json_suppression() {
+        let mut config = config::Config::default();
+        config.override_diagnostic_level_at(
+            config::DiagnosticPathOverride::new(
+                config::PathPattern::new("plan").unwrap(),
+                crate::Level::Info,
+                crate::Level::Info,
+            )
+            .with_reason("testing"),
+        );
+        let result = crate::parse(&b"\x12\x00"[..], &config);
+        let mut out = vec![];
+        result.export(&mut out, export::Format::Json).unwrap();
+        let value: serde_json::Value = serde_json::from_slice(&out).unwrap();
+
+        assert_eq!(value["validity"], "valid");
+        for diag in value["diagnostics"].as_array().unwrap() {
+            assert_eq!(diag["suppression"]["pattern"], "plan");
+            assert_eq!(diag["suppression"]["reason"], "testing");
+        }
+    }
+}
@@ -0,0 +1,144 @@
+// SPDX-License-Identifier: Apache-2.0
+
+//! This module provides an export format based on JUnit XML, such that
+//! validation results can be reported by CI systems as test results.
+//!
+//! Each [ParseResult](parse_result::ParseResult) is emitted as a testcase.
+//! A testcase fails if the plan was proven to be invalid, in which case the
+//! error-level diagnostics are listed in the failure element, with the first
+//! diagnostic of the highest severity as the failure message. Warning-level
+//! diagnostics are listed in system-out.
+
+use crate::output::diagnostic;
+use crate::output::parse_result;
+
+/// Escapes the given string for use in XML text or attribute values. Control
+/// characters that cannot be represented in XML 1.0 are replaced with U+FFFD.
+fn escape(text: &str) -> String {
+    let mut result = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => result += "&amp;",
+            '<' => result += "&lt;",
+            '>' => result += "&gt;",
+            '"' => result += "&quot;",
+            '\'' => result += "&apos;",
+            '\t' | '\n' | '\r' => result.push(c),
+            c if c.is_control() => result.push(char::REPLACEMENT_CHARACTER),
+            c => result.push(c),
+        }
+    }
+    result
+}
+
+/// Writes a single testcase element for the given result.
+fn write_testcase<T: std::io::Write>(
+    out: &mut T,
+    suite_name: &str,
+    name: &str,
+    result: &parse_result::ParseResult,
+) -> std::io::Result<()> {
+    write!(
+        out,
+        "    <testcase name=\"{}\" classname=\"{}\"",
+        escape(name),
+        escape(suite_name)
+    )?;
+
+    let diagnostics_at = |level| {
+        result
+            .iter_diagnostics()
+            .filter(move |diag| diag.adjusted_level == level)
+            .map(|diag| format!("{diag}\n"))
+            .collect::<String>()
+    };
+    let errors = diagnostics_at(diagnostic::Level::Error);
+    let warnings = diagnostics_at(diagnostic::Level::Warning);
+    let failure = result
+        .get_diagnostic()
+        .filter(|_| result.check() == parse_result::Validity::Invalid);
+    if failure.is_none() && warnings.is_empty() {
+        return writeln!(out, "/>");
+    }
+    writeln!(out, ">")?;
+    if let Some(diag) = failure {
+        writeln!(
+            out,
+            "      <failure message=\"{}\" type=\"{}\">{}</failure>",
+            escape(&diag.cause.to_string()),
+            escape(&diag.cause.classification.name()),
+            escape(&errors)
+        )?;
+    }
+    if !warnings.is_empty() {
+        writeln!(out, "      <system-out>{}</system-out>", escape(&warnings))?;
+    }
+    writeln!(out, "    </testcase>")
+}
+
+/// Export the given results as a JUnit XML document containing a single
+/// testsuite with the given name.
+pub fn export_suite<'a, T, I>(out: &mut T, suite_name: &str, results: I) -> std::io::Result<()>
+where
+    T: std::io::Write,
+    I: IntoIterator<Item = (&'a str, &'a parse_result::ParseResult)>,
+{
+    let results = results.into_iter().collect::<Vec<_>>();
+    let tests = results.len();
+    let failures = results
+        .iter()
+        .filter(|(_, result)| result.check() == parse_result::Validity::Invalid)
+        .count();
+
+    writeln!(out, "<?xml version=\"1.0\" encoding=\"UTF-8\"?>")?;
+    writeln!(
+        out,
+        "<testsuites name=\"{}\" tests=\"{tests}\" failures=\"{failures}\" errors=\"0\">",
+        escape(suite_name)
+    )?;
+    writeln!(
+        out,
+        "  <testsuite name=\"{}\" tests=\"{tests}\" failures=\"{failures}\" errors=\"0\" skipped=\"0\">",
+        escape(suite_name)
+    )?;
+    for (name, result) in results {
+        write_testcase(out, suite_name, name, result)?;
+    }
+    writeln!(out, "  </testsuite>")?;
+    writeln!(out, "</testsuites>")
+}
+
+/// Export a single result as a JUnit XML document.
+pub fn export<T: std::io::Write>(
+    out: &mut T,
+    root_name: &'static str,
+    result: &parse_result::ParseResult,
+) -> std::io::Result<()> {
+    export_suite(out, "substrait-validator", [(root_name, result)])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::input::config;
+
+    #[test]
+    fn junit_suite() {
+        let config = config::Config::default();
+        let invalid = crate::parse(&b"\x12\x00"[..], &config);
+        let mut out = vec![];
+        export_suite(
+            &mut out,
+            "golden",
+            [("a.bin", &invalid), ("b<&>.bin", &invalid)],
+        )
+        .unwrap();
+        let out = String::from_utf8(out).unwrap();
+
+        assert!(out.contains("<testsuite name=\"golden\" tests=\"2\" failures=\"2\""));
+        assert!(out.contains("<testcase name=\"a.bin\" classname=\"golden\">"));
+        assert!(out.contains("<testcase name=\"b&lt;&amp;&gt;.bin\""));
+        let message = escape(&invalid.get_diagnostic().unwrap().cause.to_string());
+        assert!(out.contains(&format!("<failure message=\"{message}\"")));
+    }
+}
@@ -0,0 +1,246 @@
+// SPDX-License-Identifier: Apache-2.0
+
+//! This module provides a Markdown report format, intended to be posted as
+//! a comment on a pull request or similar.
+//!
+//! The report starts with a table of diagnostic counts by classification
+//! group and level, followed by a collapsible section for each relation with
+//! its description, schema, and diagnostics. Links to nodes use the same
+//! anchors as the HTML export, such that they resolve when the report is
+//! placed on the same page as, or prefixed with the location of, an HTML
+//! export of the same plan.
+
+use super::html;
+use super::relations;
+use crate::output::comment;
+use crate::output::diagnostic;
+use crate::output::parse_result;
+use crate::output::path;
+use std::collections::BTreeMap;
+
+/// Escapes characters that have a special meaning in Markdown or would be
+/// interpreted as inline HTML.
+fn escape<S: AsRef<str>>(text: S) -> String {
+    let mut result = String::with_capacity(text.as_ref().len());
+    for c in text.as_ref().chars() {
+        match c {
+            '\\' | '`' | '*' | '_' | '[' | ']' | '|' | '#' => {
+                result.push('\\');
+                result.push(c);
+            }
+            '<' => result += "&lt;",
+            '>' => result += "&gt;",
+            '&' => result += "&amp;",
+            c => result.push(c),
+        }
+    }
+    result
+}
+
+/// Escapes text for use within an HTML element, such as the summary of a
+/// details section, where Markdown escape sequences are not processed.
+fn escape_html<S: AsRef<str>>(text: S) -> String {
+    text.as_ref()
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+/// Formats a link to the given node.
+fn format_reference<S: std::fmt::Display>(text: S, path: &path::PathBuf) -> String {
+    format!("[{text}](#{})", html::anchor_name(path, None))
+}
+
+/// Formats a comment span.
+fn format_comment_span(span: &comment::Span) -> String {
+    match &span.link {
+        None => escape(&span.text),
+        Some(comment::Link::Path(path)) => format_reference(escape(&span.text), path),
+        Some(comment::Link::Url(url)) => format!("[{}](<{url}>)", escape(&span.text)),
+    }
+}
+
+/// Formats a comment using Markdown markup.
+fn format_comment(comment: &comment::Comment) -> String {
+    let mut result = String::new();
+    let mut indent = String::new();
+    for element in comment.elements().iter() {
+        match element {
+            comment::Element::Span(span) => result += &format_comment_span(span),
+            comment::Element::NewLine => result += &format!("\n\n{indent}"),
+            comment::Element::ListOpen => {
+                result += &format!("\n\n{indent}- ");
+                indent += "  ";
+            }
+            comment::Element::ListNext => {
+                result += &format!("\n{}- ", &indent[2..]);
+            }
+            comment::Element::ListClose => {
+                indent.truncate(indent.len().saturating_sub(2));
+                result += &format!("\n\n{indent}");
+            }
+        }
+    }
+    result.trim_end().to_string()
+}
+
+/// Formats a brief comment using Markdown markup.
+fn format_brief(brief: &comment::Brief) -> String {
+    brief.spans().iter().map(format_comment_span).collect()
+}
+
+/// Formats a diagnostic as a list item.
+fn format_diagnostic(diag: &diagnostic::Diagnostic) -> String {
+    format!(
+        "- **{:?}** {}: {}",
+        diag.adjusted_level,
+        format_reference(format!("`{}`", diag.path), &diag.path),
+        escape(diag.cause.to_string())
+    )
+}
+
+/// Writes the table of diagnostic counts.
+fn write_summary<T: std::io::Write>(
+    out: &mut T,
+    result: &parse_result::ParseResult,
+) -> std::io::Result<()> {
+    let mut counts = BTreeMap::new();
+    let mut totals = [0usize; 3];
+    for diag in result.iter_diagnostics() {
+        let group = diag.cause.classification.group();
+        let level = diag.adjusted_level as usize;
+        counts.entry(group.code()).or_insert((group, [0usize; 3])).1[level] += 1;
+        totals[level] += 1;
+    }
+
+    writeln!(out, "| Group | Info | Warning | Error |")?;
+    writeln!(out, "|-------|-----:|--------:|------:|")?;
+    for (group, [info, warning, error]) in counts.values() {
+        writeln!(
+            out,
+            "| {} ({}xxx) | {info} | {warning} | {error} |",
+            group.name(),
+            group.group_code()
+        )?;
+    }
+    let [info, warning, error] = totals;
+    writeln!(
+        out,
+        "| **Total** | **{info}** | **{warning}** | **{error}** |"
+    )
+}
+
+/// Writes the collapsible section for a relation.
+fn write_relation<T: std::io::Write>(
+    out: &mut T,
+    relation: &relations::Relation,
+) -> std::io::Result<()> {
+    let status = match relation.level() {
+        diagnostic::Level::Error => "error",
+        diagnostic::Level::Warning => "warning",
+        diagnostic::Level::Info => "ok",
+    };
+    let brief = relation.node.brief.as_ref();
+    writeln!(out, "<details>")?;
+    writeln!(
+        out,
+        "<summary>{} ({status})</summary>",
+        brief
+            .map(|brief| escape_html(brief.to_string()))
+            .unwrap_or_else(|| String::from("unknown relation"))
+    )?;
+    writeln!(out)?;
+    writeln!(
+        out,
+        "**Relation:** {}",
+        brief
+            .map(format_brief)
+            .unwrap_or_else(|| String::from("unknown"))
+    )?;
+    writeln!(out)?;
+    writeln!(
+        out,
+        "**Path:** {}",
+        format_reference(format!("`{}`", relation.path), &relation.path)
+    )?;
+    writeln!(out)?;
+    if let Some(data_type) = &relation.node.data_type {
+        writeln!(out, "**Schema:** `{data_type}`")?;
+        writeln!(out)?;
+    }
+    if let Some(summary) = &relation.node.summary {
+        writeln!(out, "{}", format_comment(summary))?;
+        writeln!(out)?;
+    }
+    if !relation.diagnostics.is_empty() {
+        writeln!(out, "**Diagnostics:**")?;
+        writeln!(out)?;
+        for diag in relation.diagnostics.iter() {
+            writeln!(out, "{}", format_diagnostic(diag))?;
+        }
+        writeln!(out)?;
+    }
+    writeln!(out, "</details>")?;
+    writeln!(out)
+}
+
+/// Export the diagnostics and relations of the plan as a Markdown report.
+pub fn export<T: std::io::Write>(
+    out: &mut T,
+    root_name: &'static str,
+    result: &parse_result::ParseResult,
+) -> std::io::Result<()> {
+    writeln!(out, "## Substrait validation report")?;
+    writeln!(out)?;
+    writeln!(
+        out,
+        "**Result:** {}",
+        match result.check() {
+            parse_result::Validity::Valid => "valid",
+            parse_result::Validity::MaybeValid => "unable to determine validity",
+            parse_result::Validity::Invalid => "invalid",
+        }
+    )?;
+    writeln!(out)?;
+    write_summary(out, result)?;
+
+    let plan = relations::Relation::gather(&path::Path::Root(root_name), &result.root, false);
+    if !plan.inputs.is_empty() {
+        writeln!(out)?;
+        writeln!(out, "### Relations")?;
+        writeln!(out)?;
+        for relation in plan.inputs.iter().flat_map(|relation| relation.iter()) {
+            write_relation(out, relation)?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::export;
+    use crate::input::config;
+
+    #[test]
+    fn markdown_report() {
+        let result = crate::parse_json(relations::TEST_PLAN, &config::Config::default());
+        let mut out = vec![];
+        result.export(&mut out, export::Format::Markdown).unwrap();
+        let out = String::from_utf8(out).unwrap();
+
+        assert!(out.contains("**Result:** invalid"));
+        assert!(out.contains("| **Total** |"));
+        assert_eq!(out.matches("<details>").count(), 3);
+        assert!(out.contains("<summary>Filter by true ("));
+        assert!(out.contains("<summary>Read from virtual table (error)</summary>"));
+        assert!(out.contains("(#plan.relations(0).rel_type(rel).rel_type(filter))"));
+    }
+
+    #[test]
+    fn comment_lists() {
+        let mut comment = comment::Comment::new().plain("items:");
+        comment = comment.lo().plain("a").li().plain("b").lc().plain("done");
+        assert_eq!(format_comment(&comment), "items:\n\n- a\n- b\n\ndone");
+    }
+}
@@ -4,8 +4,15 @@
 //! to a byte stream in various formats.
 
 mod diagnostics;
+mod dot;
+mod explain;
 mod html;
+mod json;
+mod junit;
+mod markdown;
 mod proto;
+mod relations;
+mod sarif;
 
 use crate::output::parse_result;
 
@@ -15,12 +22,39 @@ pub enum Format {
     /// Emit a newline-separated, flattened list of diagnostics.
     Diagnostics,
 
+    /// Emit the relation graph of the plan in Graphviz DOT format, with the
+    /// relations colored by the severity of their diagnostics.
+    Dot,
+
+    /// Emit an EXPLAIN-like plain-text description of the relation trees of
+    /// the plan, with diagnostics listed below the relation they belong to.
+    Explain,
+
     /// Emit a HTML page with detailed information about the parsed plan.
     Html,
 
+    /// Emit the diagnostics as a JSON object, along with a summary of the
+    /// validity of the plan. This is intended for tooling that does not want
+    /// to depend on the protobuf bindings.
+    Json,
+
+    /// Emit a JUnit XML document with a single testcase, which fails if the
+    /// plan is invalid. Use [export_junit()] to combine multiple results into
+    /// a single document.
+    Junit,
+
+    /// Emit a Markdown report with a summary of the diagnostics and a
+    /// collapsible section per relation, intended for pull request comments.
+    /// Links use the same anchors as the HTML export.
+    Markdown,
+
     /// Emit all parse information as a substrait.validator.Node protobuf
     /// message, using binary serialization.
     Proto,
+
+    /// Emit the diagnostics as a SARIF 2.1.0 log, for consumption by
+    /// code-scanning tools.
+    Sarif,
 }
 
 /// Exports the given doctree with the given format to the given output.
@@ -32,7 +66,36 @@ pub fn export<T: std::io::Write>(
 ) -> std::io::Result<()> {
     match format {
         Format::Diagnostics => diagnostics::export(out, root_name, result),
+        Format::Dot => dot::export(out, root_name, result),
+        Format::Explain => explain::export(out, root_name, result),
         Format::Html => html::export(out, root_name, result),
+        Format::Json => json::export(out, root_name, result),
+        Format::Markdown => markdown::export(out, root_name, result),
+        Format::Junit => junit::export(out, root_name, result),
         Format::Proto => proto::export(out, root_name, result),
+        Format::Sarif => sarif::export(out, root_name, result),
     }
 }
+
+/// Exports the diagnostics of the given result as a SARIF 2.1.0 log, like
+/// [Format::Sarif], but using the given URI as the artifact location of
+/// diagnostics that refer to the plan. The validator itself does not know
+/// where the plan came from, so the caller should pass the path or URI of the
+/// input file here, if known.
+pub fn export_sarif<T: std::io::Write>(
+    out: &mut T,
+    result: &parse_result::ParseResult,
+    artifact_uri: Option<&str>,
+) -> std::io::Result<()> {
+    sarif::export_with_artifact(out, result, artifact_uri)
+}
+
+/// Exports multiple results as a single JUnit XML document, containing a
+/// testsuite with the given name and a testcase for each (name, result) pair.
+pub fn export_junit<'a, T, I>(out: &mut T, suite_name: &str, results: I) -> std::io::Result<()>
+where
+    T: std::io::Write,
+    I: IntoIterator<Item = (&'a str, &'a parse_result::ParseResult)>,
+{
+    junit::export_suite(out, suite_name, results)
+}
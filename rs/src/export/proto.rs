@@ -15,6 +15,7 @@ use crate::output::extension;
 use crate::output::parse_result;
 use crate::output::path;
 use crate::output::primitive_data;
+use crate::output::span;
 use crate::output::tree;
 use crate::output::type_system::data;
 use crate::output::type_system::meta;
@@ -90,6 +91,36 @@ impl From<&diagnostic::Diagnostic> for validator::Diagnostic {
             cause: node.cause.classification.into(),
             msg: node.cause.to_string(),
             path: Some((&node.path).into()),
+            span: node.span.as_ref().map(|x| x.into()),
+            suppression: node.suppression.as_ref().map(|x| x.into()),
+        }
+    }
+}
+
+impl From<&diagnostic::Suppression> for validator::diagnostic::Suppression {
+    fn from(node: &diagnostic::Suppression) -> Self {
+        Self {
+            pattern: node.pattern.clone(),
+            reason: node.reason.clone().unwrap_or_default(),
+        }
+    }
+}
+
+impl From<&span::Span> for validator::Span {
+    fn from(node: &span::Span) -> Self {
+        Self {
+            file: node.file.clone().unwrap_or_default(),
+            start: Some((&node.start).into()),
+            end: Some((&node.end).into()),
+        }
+    }
+}
+
+impl From<&span::Position> for validator::span::Position {
+    fn from(node: &span::Position) -> Self {
+        Self {
+            line: node.line as u64,
+            column: node.column as u64,
         }
     }
 }
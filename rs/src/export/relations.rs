@@ -0,0 +1,190 @@
+// SPDX-License-Identifier: Apache-2.0
+
+//! Module for building a relation-level view of a parse tree, shared by the
+//! export formats that describe the relation trees of a plan.
+//!
+//! The view only retains the relation nodes of the tree, along with the
+//! information that belongs to each relation: its toplevel expressions and
+//! the diagnostics within it that are not part of one of its input
+//! relations. Relations used as subqueries within expressions are treated as
+//! inputs of the relation containing the expression.
+
+use crate::output::diagnostic;
+use crate::output::path;
+use crate::output::tree;
+
+/// A relation in the relation-level view of a tree.
+pub struct Relation<'a> {
+    /// Path to the relation node.
+    pub path: path::PathBuf,
+
+    /// The relation node.
+    pub node: &'a tree::Node,
+
+    /// Whether this relation is used as a subquery by its consumer.
+    pub subquery: bool,
+
+    /// The toplevel expressions of this relation, i.e. those that are not
+    /// part of another expression, along with their paths.
+    pub expressions: Vec<(path::PathBuf, &'a tree::Node)>,
+
+    /// The diagnostics that belong to this relation, i.e. those that are not
+    /// part of one of its input relations.
+    pub diagnostics: Vec<&'a diagnostic::Diagnostic>,
+
+    /// The input relations, in the order in which they appear in the tree.
+    pub inputs: Vec<Relation<'a>>,
+}
+
+impl<'a> Relation<'a> {
+    /// Builds the view for the given node and everything below it. The node
+    /// itself is treated as a relation regardless of its class, such that
+    /// this can also be used for the root of the tree; the inputs of the root
+    /// are then the relation trees of the plan, and its diagnostics are those
+    /// that do not belong to any relation.
+    pub fn gather(path: &path::Path, node: &'a tree::Node, subquery: bool) -> Self {
+        let mut relation = Relation {
+            path: path.to_path_buf(),
+            node,
+            subquery,
+            expressions: vec![],
+            diagnostics: vec![],
+            inputs: vec![],
+        };
+        relation.gather_children(path, node, false);
+        relation
+    }
+
+    /// Gathers information from the children of the given node, up to but
+    /// not including input relations.
+    fn gather_children(&mut self, path: &path::Path, node: &'a tree::Node, in_expression: bool) {
+        for data in node.data.iter() {
+            match data {
+                tree::NodeData::Diagnostic(diag) => self.diagnostics.push(diag),
+                tree::NodeData::Child(child) => {
+                    let path = path.with(child.path_element.clone());
+                    match child.node.class {
+                        tree::Class::Relation => {
+                            self.inputs
+                                .push(Relation::gather(&path, &child.node, in_expression));
+                        }
+                        tree::Class::Expression => {
+                            if !in_expression {
+                                self.expressions.push((path.to_path_buf(), &child.node));
+                            }
+                            self.gather_children(&path, &child.node, true);
+                        }
+                        _ => self.gather_children(&path, &child.node, in_expression),
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    /// Returns the brief description of the relation as plain text.
+    pub fn brief(&self) -> String {
+        self.node
+            .brief
+            .as_ref()
+            .map(ToString::to_string)
+            .unwrap_or_else(|| String::from("unknown"))
+    }
+
+    /// Returns the highest severity of the diagnostics of this relation,
+    /// excluding those of its inputs.
+    pub fn level(&self) -> diagnostic::Level {
+        self.diagnostics
+            .iter()
+            .map(|diag| diag.adjusted_level)
+            .max()
+            .unwrap_or(diagnostic::Level::Info)
+    }
+
+    /// Returns the path of the given descendant of this relation relative to
+    /// the relation, without leading period.
+    pub fn relative_path(&self, path: &path::PathBuf) -> String {
+        path.elements[self.path.elements.len()..]
+            .iter()
+            .map(ToString::to_string)
+            .collect::<String>()
+            .trim_start_matches('.')
+            .to_string()
+    }
+
+    /// Returns an iterator over this relation and all relations below it, in
+    /// depth-first order, parents before their inputs.
+    pub fn iter(&self) -> impl Iterator<Item = &Relation<'a>> + '_ {
+        let mut stack = vec![self];
+        std::iter::from_fn(move || {
+            let relation = stack.pop()?;
+            stack.extend(relation.inputs.iter().rev());
+            Some(relation)
+        })
+    }
+}
+
+/// Protobuf JSON plan shared by the tests of the export formats that use
+/// this module. It consists of a filter with a literal condition over a fetch
+/// over a read, the latter of which has an error because the nullability of
+/// its schema is not specified.
+#[cfg(test)]
+pub const TEST_PLAN: &str = r#"{
+    "version": { "minorNumber": 87, "producer": "export-test" },
+    "relations": [ { "rel": { "filter": {
+        "input": { "fetch": {
+            "input": { "read": {
+                "baseSchema": {
+                    "names": ["a"],
+                    "struct": { "types": [ { "bool": { "nullability": "NULLABILITY_REQUIRED" } } ] }
+                },
+                "virtualTable": {}
+            } },
+            "count": 1
+        } },
+        "condition": { "literal": { "boolean": true } }
+    } } } ]
+}"#;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::input::config;
+
+    #[test]
+    fn relation_view() {
+        let result = crate::parse_json(TEST_PLAN, &config::Config::default());
+        let root = Relation::gather(&path::Path::Root("plan"), &result.root, false);
+        assert_eq!(root.inputs.len(), 1);
+
+        let filter = &root.inputs[0];
+        assert_eq!(
+            filter.path.to_string(),
+            "plan.relations[0].rel_type<rel>.rel_type<filter>"
+        );
+        assert!(!filter.subquery);
+        assert_eq!(filter.expressions.len(), 1);
+        assert_eq!(filter.relative_path(&filter.expressions[0].0), "condition");
+        assert_eq!(filter.inputs.len(), 1);
+
+        let fetch = &filter.inputs[0];
+        assert_eq!(fetch.inputs.len(), 1);
+        let read = &fetch.inputs[0];
+        assert!(read.inputs.is_empty());
+        assert_eq!(read.level(), diagnostic::Level::Error);
+
+        let paths = root
+            .iter()
+            .map(|relation| relation.path.clone())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            paths,
+            vec![
+                root.path.clone(),
+                filter.path.clone(),
+                fetch.path.clone(),
+                read.path.clone()
+            ]
+        );
+    }
+}
@@ -0,0 +1,219 @@
+// SPDX-License-Identifier: Apache-2.0
+
+//! This module provides an export format based on the Static Analysis Results
+//! Interchange Format (SARIF) version 2.1.0, such that the diagnostics can be
+//! uploaded to code-scanning tools.
+//!
+//! Each [`Classification`](diagnostic::Classification) that occurs in the
+//! tree is emitted as a rule, using the four-digit code as identifier. Each
+//! diagnostic is emitted as a result referring to that rule, with the path
+//! into the plan as a logical location. A physical location is added as well
+//! if the file the diagnostic refers to is known. For extension files, their
+//! URN is used as URI. The validator does not know where the plan itself came
+//! from, so for the plan, the artifact URI passed to [export_with_artifact()]
+//! is used, if any. Diagnostics whose level was adjusted
+//! by a path-scoped override rule carry an external suppression, with the
+//! reason for the rule as justification.
+
+use crate::output::diagnostic;
+use crate::output::parse_result;
+use crate::output::span;
+use serde_json::json;
+use std::collections::BTreeMap;
+
+/// The JSON schema for the version of SARIF that is emitted.
+const SCHEMA: &str = "https://json.schemastore.org/sarif-2.1.0.json";
+
+/// Returns the SARIF level for a diagnostic level.
+fn level(level: diagnostic::Level) -> &'static str {
+    match level {
+        diagnostic::Level::Info => "note",
+        diagnostic::Level::Warning => "warning",
+        diagnostic::Level::Error => "error",
+    }
+}
+
+/// Returns the rule identifier for a classification.
+fn rule_id(classification: &diagnostic::Classification) -> String {
+    format!("{:04}", classification.code())
+}
+
+/// Converts a classification to a SARIF reportingDescriptor object.
+fn rule(classification: &diagnostic::Classification) -> serde_json::Value {
+    json!({
+        "id": rule_id(classification),
+        "name": classification.name(),
+        "shortDescription": { "text": classification.description() },
+        "properties": { "group": classification.group().name() },
+    })
+}
+
+/// Converts the location of a diagnostic to a SARIF physicalLocation object,
+/// if it refers to a known file. Spans without a file refer to the plan,
+/// which is identified by artifact_uri. Note that SARIF end columns are
+/// exclusive, whereas ours are inclusive.
+fn physical_location(
+    span: Option<&span::Span>,
+    artifact_uri: Option<&str>,
+) -> Option<serde_json::Value> {
+    let Some(span) = span else {
+        return artifact_uri.map(|uri| json!({ "artifactLocation": { "uri": uri } }));
+    };
+    span.file.as_deref().or(artifact_uri).map(|uri| {
+        json!({
+            "artifactLocation": { "uri": uri },
+            "region": {
+                "startLine": span.start.line,
+                "startColumn": span.start.column,
+                "endLine": span.end.line,
+                "endColumn": span.end.column + 1,
+            },
+        })
+    })
+}
+
+/// Converts a diagnostic to a SARIF result object.
+fn result(
+    diag: &diagnostic::Diagnostic,
+    rule_index: usize,
+    artifact_uri: Option<&str>,
+) -> serde_json::Value {
+    let mut location = json!({
+        "logicalLocations": [{
+            "fullyQualifiedName": diag.path.to_string(),
+            "kind": "element",
+        }],
+    });
+    if let Some(physical) = physical_location(diag.span.as_ref(), artifact_uri) {
+        location["physicalLocation"] = physical;
+    }
+    let mut value = json!({
+        "ruleId": rule_id(&diag.cause.classification),
+        "ruleIndex": rule_index,
+        "level": level(diag.adjusted_level),
+        "message": { "text": diag.cause.to_string() },
+        "locations": [location],
+    });
+    if let Some(suppression) = &diag.suppression {
+        let mut entry = json!({
+            "kind": "external",
+            "properties": { "pattern": suppression.pattern },
+        });
+        if let Some(reason) = &suppression.reason {
+            entry["justification"] = json!(reason);
+        }
+        value["suppressions"] = json!([entry]);
+    }
+    value
+}
+
+/// Export the diagnostic messages of the tree as a SARIF log.
+pub fn export<T: std::io::Write>(
+    out: &mut T,
+    _root_name: &'static str,
+    result: &parse_result::ParseResult,
+) -> std::io::Result<()> {
+    export_with_artifact(out, result, None)
+}
+
+/// Export the diagnostic messages of the tree as a SARIF log, using the given
+/// URI as the artifact location of diagnostics that refer to the plan.
+pub fn export_with_artifact<T: std::io::Write>(
+    out: &mut T,
+    result: &parse_result::ParseResult,
+    artifact_uri: Option<&str>,
+) -> std::io::Result<()> {
+    // Gather the rules that are referenced, ordered by code.
+    let classifications = result
+        .iter_diagnostics()
+        .map(|diag| (diag.cause.classification.code(), diag.cause.classification))
+        .collect::<BTreeMap<_, _>>();
+    let rule_indices = classifications
+        .keys()
+        .enumerate()
+        .map(|(index, code)| (*code, index))
+        .collect::<BTreeMap<_, _>>();
+
+    let results = result
+        .iter_diagnostics()
+        .map(|diag| {
+            self::result(
+                diag,
+                rule_indices[&diag.cause.classification.code()],
+                artifact_uri,
+            )
+        })
+        .collect::<Vec<_>>();
+    let value = json!({
+        "$schema": SCHEMA,
+        "version": "2.1.0",
+        "runs": [{
+            "tool": {
+                "driver": {
+                    "name": "substrait-validator",
+                    "version": env!("CARGO_PKG_VERSION"),
+                    "informationUri": env!("CARGO_PKG_REPOSITORY"),
+                    "rules": classifications.values().map(rule).collect::<Vec<_>>(),
+                },
+            },
+            "results": results,
+        }],
+    });
+    serde_json::to_writer_pretty(&mut *out, &value)?;
+    writeln!(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::export;
+    use crate::input::config;
+
+    #[test]
+    fn sarif_log() {
+        let result = crate::parse(&b"\x12\x00"[..], &config::Config::default());
+        let mut out = vec![];
+        result.export(&mut out, export::Format::Sarif).unwrap();
+        let value: serde_json::Value = serde_json::from_slice(&out).unwrap();
+
+        assert_eq!(value["version"], "2.1.0");
+        let run = &value["runs"][0];
+        let rules = run["tool"]["driver"]["rules"].as_array().unwrap();
+        let results = run["results"].as_array().unwrap();
+        assert_eq!(results.len(), result.iter_diagnostics().count());
+        for sarif_result in results {
+            let index = sarif_result["ruleIndex"].as_u64().unwrap() as usize;
+            assert_eq!(rules[index]["id"], sarif_result["ruleId"]);
+            assert!(
+                sarif_result["locations"][0]["logicalLocations"][0]["fullyQualifiedName"]
+                    .as_str()
+                    .unwrap()
+                    .starts_with("plan")
+            );
+        }
+        assert!(results.iter().any(|r| r["level"] == "error"));
+        assert!(results
+            .iter()
+            .all(|r| r["locations"][0].get("physicalLocation").is_none()));
+    }
+
+    #[test]
+    fn sarif_artifact_uri() {
+        let json = r#"{"relations": [{"root": {"input": {"read": {}}}}]}"#;
+        let result = crate::parse_json(json, &config::Config::default());
+        let mut out = vec![];
+        export::export_sarif(&mut out, &result, Some("plans/plan.json")).unwrap();
+        let value: serde_json::Value = serde_json::from_slice(&out).unwrap();
+
+        // Diagnostics for the plan refer to the given artifact, with a
+        // region if their location in the JSON text is known.
+        let results = value["runs"][0]["results"].as_array().unwrap();
+        assert!(!results.is_empty());
+        for sarif_result in results {
+            let physical = &sarif_result["locations"][0]["physicalLocation"];
+            assert_eq!(physical["artifactLocation"]["uri"], "plans/plan.json");
+        }
+        assert!(results.iter().any(|r| {
+            r["locations"][0]["physicalLocation"]["region"]["startLine"].as_u64() == Some(1)
+        }));
+    }
+}
@@ -6,9 +6,15 @@
 //! the validator to configure it. Alternatively, the default configuration can
 //! be constructed by using the [`std::default::Default`] trait.
 
+use crate::input::profile;
+use crate::input::registry;
+use crate::input::resolver;
 use crate::output::diagnostic;
+use crate::output::path;
 pub use glob;
 use std::collections::HashMap;
+use std::sync::atomic;
+use std::sync::Arc;
 
 /// Trait object representing some immutable binary data.
 pub type BinaryData = Box<dyn AsRef<[u8]>>;
@@ -24,6 +30,30 @@ pub type ErrorData = Box<dyn std::error::Error>;
 pub type UrnResolver =
     Box<dyn Fn(&str) -> std::result::Result<BinaryData, ErrorData> + Send + Sync>;
 
+/// Token that can be used to cancel validation from another thread. Clones
+/// of a token share their state.
+#[derive(Clone, Debug, Default)]
+pub struct CancellationToken(Arc<atomic::AtomicBool>);
+
+impl CancellationToken {
+    /// Creates a new token.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Requests cancellation of all validation runs using this token.
+    /// Validation stops at the next node it visits and yields a partial
+    /// result.
+    pub fn cancel(&self) {
+        self.0.store(true, atomic::Ordering::Relaxed);
+    }
+
+    /// Returns whether cancellation has been requested.
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(atomic::Ordering::Relaxed)
+    }
+}
+
 /// Configuration structure.
 pub struct Config {
     /// When set, do not generate warnings for unknown protobuf fields that are
@@ -51,6 +81,12 @@ pub struct Config {
     pub diagnostic_level_overrides:
         HashMap<diagnostic::Classification, (diagnostic::Level, diagnostic::Level)>,
 
+    /// Allows the level of diagnostic messages to be overridden for specific
+    /// locations in the plan. The first rule that matches a diagnostic takes
+    /// precedence over diagnostic_level_overrides, and is recorded in the
+    /// diagnostic's suppression field.
+    pub diagnostic_path_overrides: Vec<DiagnosticPathOverride>,
+
     /// Allows extension URNs from the plan to be remapped (Some(mapping)) or
     /// ignored (None). All resolution can effectively be disabled by just
     /// adding a rule that maps * to None. Furthermore, this can be used to
@@ -58,6 +94,14 @@ pub struct Config {
     /// resolve.
     pub urn_overrides: Vec<(glob::Pattern, Option<String>)>,
 
+    /// Maps the URIs used by legacy extension declarations (i.e. plans that
+    /// populate the deprecated extension_uris field) to the URNs of the
+    /// extensions they refer to. These URNs are then resolved like any other.
+    /// The well-known URIs of the standard extension files are recognized
+    /// even if they are not listed here; see
+    /// [`legacy_uris`](crate::input::legacy_uris).
+    pub uri_urn_mappings: HashMap<String, String>,
+
     /// Optional callback function for resolving extension URNs. If specified,
     /// all URNs (after processing urn_overrides) are looked up using this
     /// function. The function takes the URN as its argument, and should either
@@ -70,6 +114,44 @@ pub struct Config {
     /// resolved this many levels deep. Setting this to zero effectively
     /// disables extension URN resolution altogether.
     pub max_urn_resolution_depth: Option<usize>,
+
+    /// Optional cache for parsed extension files, shared between parse calls.
+    /// If specified, each extension is only resolved and parsed the first
+    /// time it is encountered; subsequent uses replay the cached parse tree
+    /// and diagnostics.
+    pub extension_registry: Option<Arc<registry::ExtensionRegistry>>,
+
+    /// Optional description of the subset of Substrait supported by the
+    /// consumer that the plan is validated for. If specified, diagnostics in
+    /// the Unsupported group are emitted for anything the plan uses outside
+    /// of it.
+    pub consumer_profile: Option<profile::ConsumerProfile>,
+
+    /// Optional limit on the depth of the parse tree. When exceeded, or when
+    /// any of the other resource limits is exceeded, validation stops with a
+    /// ResourceLimitExceeded diagnostic and yields a partial result.
+    pub max_tree_depth: Option<usize>,
+
+    /// Optional limit on the number of nodes in the parse tree.
+    pub max_node_count: Option<usize>,
+
+    /// Optional limit on the total size of the string and binary data in the
+    /// input, such as literals and virtual tables.
+    pub max_literal_bytes: Option<usize>,
+
+    /// Optional limit on the time spent traversing the input.
+    pub time_limit: Option<std::time::Duration>,
+
+    /// Optional token for cancelling validation from another thread.
+    pub cancellation_token: Option<CancellationToken>,
+
+    /// When set, the parse tree is only built to the extent needed to carry
+    /// diagnostics and data types: comments, briefs, and summaries are not
+    /// generated, and unrecognized fields are not traversed and only
+    /// reported. This is intended for callers that only need the validity of
+    /// the plan or its diagnostics, and substantially reduces memory usage
+    /// and validation time.
+    pub diagnostics_only: bool,
 }
 
 // TODO: enable URN resolution by default once all that works. Then this can
@@ -80,9 +162,19 @@ impl Default for Config {
             ignore_unknown_fields: Default::default(),
             allowed_proto_any_urls: Default::default(),
             diagnostic_level_overrides: Default::default(),
+            diagnostic_path_overrides: Default::default(),
             urn_overrides: Default::default(),
+            uri_urn_mappings: Default::default(),
             urn_resolver: Default::default(),
             max_urn_resolution_depth: Some(0),
+            extension_registry: Default::default(),
+            consumer_profile: Default::default(),
+            max_tree_depth: Default::default(),
+            max_node_count: Default::default(),
+            max_literal_bytes: Default::default(),
+            time_limit: Default::default(),
+            cancellation_token: Default::default(),
+            diagnostics_only: Default::default(),
         }
     }
 }
@@ -102,6 +194,13 @@ impl Config {
         self.ignore_unknown_fields = true;
     }
 
+    /// Instructs the validator to only build the parts of the parse tree
+    /// needed for diagnostics and data types. The resulting tree can still be
+    /// exported, but lacks most of the information that is normally included.
+    pub fn diagnostics_only(&mut self) {
+        self.diagnostics_only = true;
+    }
+
     /// Explicitly allows a protobuf message type to be used in advanced
     /// extensions, despite the fact that the validator can't validate it. If
     /// an advanced extension is encountered that isn't explicitly allowed, a
@@ -123,6 +222,13 @@ impl Config {
             .insert(class, (minimum, maximum));
     }
 
+    /// Overrides the level of diagnostics emitted at or below plan paths
+    /// matching the rule's pattern. Rules are tried in the order in which
+    /// they were added.
+    pub fn override_diagnostic_level_at(&mut self, rule: DiagnosticPathOverride) {
+        self.diagnostic_path_overrides.push(rule);
+    }
+
     /// Overrides the resolution behavior for extension URNs matching the given
     /// pattern. If resolve_as is None, the URN will not be resolved; if it is
     /// Some(s), it will be resolved as if the URN in the plan had been s.
@@ -131,6 +237,13 @@ impl Config {
             .push((pattern, resolve_as.map(|s| s.into())));
     }
 
+    /// Maps a URI used by legacy extension declarations to the URN of the
+    /// extension it refers to. Any previous mapping for this URI is
+    /// overridden.
+    pub fn map_uri_to_urn<S: Into<String>, T: Into<String>>(&mut self, uri: S, urn: T) {
+        self.uri_urn_mappings.insert(uri.into(), urn.into());
+    }
+
     /// Registers an extension URN resolution function with this configuration.
     /// If the given function fails, any previously registered function will be
     /// used as a fallback.
@@ -150,10 +263,374 @@ impl Config {
         }));
     }
 
+    /// Registers a resolver that looks up extension URNs in an index of
+    /// extension files built by scanning directory trees. If the URN is not
+    /// in the index, any previously registered function will be used as a
+    /// fallback. A URN declared by more than one file fails to resolve, and
+    /// is reported as such.
+    pub fn add_urn_directory_resolver(&mut self, resolver: resolver::DirectoryResolver) {
+        let previous = self.urn_resolver.take();
+        self.urn_resolver = Some(Box::new(move |urn| match resolver.resolve(urn) {
+            Ok(d) => Ok(Box::new(d)),
+            Err(e @ resolver::ResolutionError::NotFound(_)) => match &previous {
+                Some(f) => f.as_ref()(urn),
+                None => Err(Box::new(e)),
+            },
+            Err(e) => Err(Box::new(e)),
+        }));
+    }
+
+    /// Registers a resolver that maps extension URNs to file paths using
+    /// templates. If it fails to resolve a URN, any previously registered
+    /// function will be used as a fallback.
+    pub fn add_urn_template_resolver(&mut self, resolver: resolver::TemplateResolver) {
+        self.add_urn_resolver(move |urn| resolver.resolve(urn));
+    }
+
     /// Sets the maximum recursion depth for URN resolution, in the presence of
     /// transitive dependencies. Setting this to None disables the limit,
     /// setting this to zero disables URN resolution entirely.
     pub fn set_max_urn_resolution_depth(&mut self, depth: Option<usize>) {
         self.max_urn_resolution_depth = depth;
     }
+
+    /// Attaches a registry that caches parsed extension files across parse
+    /// calls. The same registry can be attached to multiple configurations
+    /// and used from multiple threads, as long as these configurations
+    /// resolve URNs in the same way.
+    pub fn set_extension_registry(&mut self, registry: Arc<registry::ExtensionRegistry>) {
+        self.extension_registry = Some(registry);
+    }
+
+    /// Sets the capability profile of the consumer that the plan is validated
+    /// for.
+    pub fn set_consumer_profile(&mut self, profile: profile::ConsumerProfile) {
+        self.consumer_profile = Some(profile);
+    }
+
+    /// Sets the maximum depth of the parse tree. None disables the limit.
+    pub fn set_max_tree_depth(&mut self, depth: Option<usize>) {
+        self.max_tree_depth = depth;
+    }
+
+    /// Sets the maximum number of nodes in the parse tree. None disables the
+    /// limit.
+    pub fn set_max_node_count(&mut self, count: Option<usize>) {
+        self.max_node_count = count;
+    }
+
+    /// Sets the maximum total size of the string and binary data in the
+    /// input. None disables the limit.
+    pub fn set_max_literal_bytes(&mut self, bytes: Option<usize>) {
+        self.max_literal_bytes = bytes;
+    }
+
+    /// Sets the maximum time spent traversing the input. None disables the
+    /// limit.
+    pub fn set_time_limit(&mut self, limit: Option<std::time::Duration>) {
+        self.time_limit = limit;
+    }
+
+    /// Attaches a token that can be used to cancel validation from another
+    /// thread.
+    pub fn set_cancellation_token(&mut self, token: CancellationToken) {
+        self.cancellation_token = Some(token);
+    }
+
+    /// Returns the level that a diagnostic of the given class that was
+    /// emitted with the given level should be reported at, according to
+    /// diagnostic_level_overrides.
+    pub fn adjust_diagnostic_level(
+        &self,
+        class: diagnostic::Classification,
+        level: diagnostic::Level,
+    ) -> diagnostic::Level {
+        let (min, max) = self
+            .diagnostic_level_overrides
+            .get(&class)
+            .or_else(|| self.diagnostic_level_overrides.get(&class.group()))
+            .or_else(|| {
+                self.diagnostic_level_overrides
+                    .get(&diagnostic::Classification::Unclassified)
+            })
+            .unwrap_or(&(diagnostic::Level::Info, diagnostic::Level::Error));
+        level.clamp(*min, *max)
+    }
+
+    /// Evaluates the adjusted level of the given diagnostic based on its
+    /// original level, according to diagnostic_path_overrides and
+    /// diagnostic_level_overrides.
+    pub fn adjust_diagnostic(&self, diag: &mut diagnostic::Diagnostic) {
+        let class = diag.cause.classification;
+        match self
+            .diagnostic_path_overrides
+            .iter()
+            .find(|rule| rule.matches(class, &diag.path))
+        {
+            Some(rule) => {
+                diag.adjusted_level = diag.original_level.clamp(rule.minimum, rule.maximum);
+                diag.suppression = Some(diagnostic::Suppression {
+                    pattern: rule.pattern.as_str().to_string(),
+                    reason: rule.reason.clone(),
+                });
+            }
+            None => {
+                diag.adjusted_level = self.adjust_diagnostic_level(class, diag.original_level);
+                diag.suppression = None;
+            }
+        }
+    }
+}
+
+/// Glob-like pattern for the string representation of a plan path, for
+/// example `relations[*].root.input.extension_single`. `*` matches any
+/// sequence of characters and `?` matches any single character; unlike for
+/// [`glob::Pattern`], square brackets only match themselves, so they can be
+/// used for indices. The root of the path (usually `plan`) may be omitted.
+///
+/// Paths normally render the selected variant of a oneof field as
+/// `.field<variant>`, for example `relations[0].rel_type<root>`. Patterns may
+/// either use that form or refer to the variant by its bare name, as in
+/// `relations[0].root`, but not mix both forms.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PathPattern {
+    /// The pattern as written.
+    text: String,
+
+    /// The equivalent glob pattern.
+    pattern: glob::Pattern,
+}
+
+impl PathPattern {
+    /// Parses a path pattern.
+    pub fn new(text: &str) -> Result<Self, glob::PatternError> {
+        let mut escaped = String::with_capacity(text.len());
+        for c in text.chars() {
+            match c {
+                '[' => escaped.push_str("[[]"),
+                ']' => escaped.push_str("[]]"),
+                c => escaped.push(c),
+            }
+        }
+        Ok(Self {
+            text: text.to_string(),
+            pattern: glob::Pattern::new(&escaped)?,
+        })
+    }
+
+    /// Returns the pattern as written.
+    pub fn as_str(&self) -> &str {
+        &self.text
+    }
+
+    /// Returns whether the given path or any of its ancestors matches this
+    /// pattern.
+    pub fn matches(&self, path: &path::PathBuf) -> bool {
+        if self.pattern.matches(path.root) {
+            return true;
+        }
+
+        // Match both the normal string representation of the path and the
+        // one with bare variant names, each with and without root.
+        let mut absolute = [path.root.to_string(), path.root.to_string()];
+        let mut relative = [String::new(), String::new()];
+        for (index, element) in path.elements.iter().enumerate() {
+            let bare = match element {
+                path::PathElement::Variant(_, variant) => format!(".{variant}"),
+                element => element.to_string(),
+            };
+            for (form, element) in [element.to_string(), bare].into_iter().enumerate() {
+                absolute[form] += &element;
+                relative[form] += match index {
+                    0 => element.strip_prefix('.').unwrap_or(&element),
+                    _ => &element,
+                };
+                if self.pattern.matches(&absolute[form]) || self.pattern.matches(&relative[form]) {
+                    return true;
+                }
+            }
+        }
+        false
+    }
+}
+
+impl std::fmt::Display for PathPattern {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.text)
+    }
+}
+
+/// Level override rule for diagnostics emitted at or below particular plan
+/// paths.
+#[derive(Clone, Debug, PartialEq)]
+pub struct DiagnosticPathOverride {
+    /// The paths that the rule applies to.
+    pub pattern: PathPattern,
+
+    /// The class of diagnostics that the rule applies to. This may also be
+    /// a group. If None, the rule applies to all diagnostics.
+    pub class: Option<diagnostic::Classification>,
+
+    /// The minimum level for matching diagnostics.
+    pub minimum: diagnostic::Level,
+
+    /// The maximum level for matching diagnostics.
+    pub maximum: diagnostic::Level,
+
+    /// Why the diagnostics are overridden, for reporting purposes.
+    pub reason: Option<String>,
+}
+
+impl DiagnosticPathOverride {
+    /// Creates a rule that clamps the level of all diagnostics at or below
+    /// paths matching the given pattern to the given range.
+    pub fn new(
+        pattern: PathPattern,
+        minimum: diagnostic::Level,
+        maximum: diagnostic::Level,
+    ) -> Self {
+        Self {
+            pattern,
+            class: None,
+            minimum,
+            maximum,
+            reason: None,
+        }
+    }
+
+    /// Restricts the rule to the given class or group of diagnostics.
+    pub fn for_class(mut self, class: diagnostic::Classification) -> Self {
+        self.class = Some(class);
+        self
+    }
+
+    /// Sets the reason for the override.
+    pub fn with_reason<S: Into<String>>(mut self, reason: S) -> Self {
+        self.reason = Some(reason.into());
+        self
+    }
+
+    /// Returns whether the rule applies to a diagnostic of the given class
+    /// at the given path.
+    pub fn matches(&self, class: diagnostic::Classification, path: &path::PathBuf) -> bool {
+        self.class
+            .is_none_or(|x| x == class || (x.group() == x && x == class.group()))
+            && self.pattern.matches(path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn path(elements: Vec<path::PathElement>) -> path::PathBuf {
+        path::PathBuf {
+            root: "plan",
+            elements,
+        }
+    }
+
+    #[test]
+    fn path_patterns() {
+        let path = path(vec![
+            path::PathElement::Repeated("relations".to_string(), 1),
+            path::PathElement::Variant("rel_type".to_string(), "root".to_string()),
+            path::PathElement::Field("input".to_string()),
+        ]);
+        assert_eq!(path.to_string(), "plan.relations[1].rel_type<root>.input");
+
+        let matches = |pattern: &str| PathPattern::new(pattern).unwrap().matches(&path);
+        assert!(matches("plan"));
+        assert!(matches("relations[*]"));
+        assert!(matches("plan.relations[1].*"));
+        assert!(matches("*.input"));
+        assert!(!matches("relations[0]"));
+        assert!(!matches("relations"));
+        assert!(!matches("plan.relations[1].rel_type<root>.input.x"));
+
+        // Variants can also be referred to by their bare name.
+        assert!(matches("relations[*].root.input"));
+        assert!(matches("plan.relations[1].root"));
+        assert!(!matches("relations[*].rel_type.input"));
+
+        // The example from the documentation.
+        let extension_path = path::PathBuf {
+            root: "plan",
+            elements: vec![
+                path::PathElement::Repeated("relations".to_string(), 0),
+                path::PathElement::Variant("rel_type".to_string(), "root".to_string()),
+                path::PathElement::Field("input".to_string()),
+                path::PathElement::Variant("rel_type".to_string(), "extension_single".to_string()),
+                path::PathElement::Field("detail".to_string()),
+            ],
+        };
+        let pattern = PathPattern::new("relations[*].root.input.extension_single").unwrap();
+        assert!(pattern.matches(&extension_path));
+    }
+
+    #[test]
+    fn path_overrides() {
+        let mut config = Config::new();
+        config.override_diagnostic_level(
+            diagnostic::Classification::ProtoAny,
+            diagnostic::Level::Error,
+            diagnostic::Level::Error,
+        );
+        config.override_diagnostic_level_at(
+            DiagnosticPathOverride::new(
+                PathPattern::new("relations[*].extension").unwrap(),
+                diagnostic::Level::Info,
+                diagnostic::Level::Info,
+            )
+            .for_class(diagnostic::Classification::Proto)
+            .with_reason("known"),
+        );
+
+        let path = path(vec![
+            path::PathElement::Repeated("relations".to_string(), 0),
+            path::PathElement::Field("extension".to_string()),
+            path::PathElement::Field("detail".to_string()),
+        ]);
+        let mut diag =
+            diag!(path, Warning, ProtoAny, "test").adjust_level(diagnostic::Level::Warning);
+        config.adjust_diagnostic(&mut diag);
+        assert_eq!(diag.adjusted_level, diagnostic::Level::Info);
+        assert_eq!(
+            diag.suppression,
+            Some(diagnostic::Suppression {
+                pattern: "relations[*].extension".to_string(),
+                reason: Some("known".to_string()),
+            })
+        );
+
+        diag.path.elements.remove(1);
+        config.adjust_diagnostic(&mut diag);
+        assert_eq!(diag.adjusted_level, diagnostic::Level::Error);
+        assert_eq!(diag.suppression, None);
+    }
+
+    #[test]
+    fn directory_resolver_duplicates() {
+        let dir = std::env::temp_dir().join(format!(
+            "substrait-validator-duplicates-{}",
+            std::process::id()
+        ));
+        std::fs::create_dir_all(&dir).unwrap();
+        for name in ["a.yaml", "b.yaml"] {
+            std::fs::write(dir.join(name), "urn: extension:test:dup\n").unwrap();
+        }
+        let resolver = resolver::DirectoryResolver::scan(&dir).unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        // A URN declared by multiple files is reported when it is resolved,
+        // rather than falling back to a previously registered resolver.
+        let mut config = Config::new();
+        config.add_urn_resolver(|_| Ok::<_, std::io::Error>(b"fallback".to_vec()));
+        config.add_urn_directory_resolver(resolver);
+        let resolve = config.urn_resolver.as_ref().unwrap();
+        let error = resolve("extension:test:dup").err().unwrap().to_string();
+        assert!(error.contains("declared by multiple files"), "{error}");
+        let data = resolve("extension:test:other").unwrap();
+        assert_eq!(data.as_ref().as_ref(), b"fallback");
+    }
 }
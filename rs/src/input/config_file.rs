@@ -0,0 +1,467 @@
+// SPDX-License-Identifier: Apache-2.0
+
+//! This module provides a serializable representation of the validator
+//! configuration.
+//!
+//! [`Config`] can't be serialized as a whole, because it may contain
+//! callbacks. A [`ConfigFile`] covers everything that can be expressed
+//! declaratively instead, and can be loaded from a YAML (or JSON) file with
+//! [`Config::from_file()`] or [`Config::load_file()`]. This allows a single
+//! validation policy file to be used from all the bindings. It looks like
+//! this:
+//!
+//! ```yaml
+//! ignore_unknown_fields: true
+//! # Only build the parse tree to the extent needed for diagnostics.
+//! diagnostics_only: false
+//! allowed_proto_any_urls:
+//!   - type.googleapis.com/com.example.*
+//! diagnostic_level_overrides:
+//!   # Classes can be specified by code or by name. min defaults to info, max
+//!   # defaults to error.
+//!   - class: 1
+//!     min: warning
+//!   - class: YamlResolutionFailed
+//!     max: info
+//! diagnostic_path_overrides:
+//!   # Applies to diagnostics at or below matching paths. Square brackets
+//!   # match literally; the class is optional.
+//!   - path: relations[*].root.input.extension_single
+//!     class: ProtoAny
+//!     max: info
+//!     reason: the consumer supports these extensions
+//! urn_overrides:
+//!   # resolve_as can be omitted or set to null to disable resolution.
+//!   - pattern: extension:com.example:*
+//!     resolve_as: extension:com.example:stable
+//! uri_urn_mappings:
+//!   # For plans that still refer to extensions by URI.
+//!   https://example.com/extensions/functions.yaml: extension:com.example:functions
+//! urn_directories:
+//!   - extensions
+//! urn_templates:
+//!   - pattern: extension:org.example:*
+//!     template: /opt/extensions/{id}.yaml
+//! # A negative value disables the limit.
+//! max_urn_resolution_depth: 3
+//! # Either the path to a profile file or an inline profile; see
+//! # [`ConsumerProfile`].
+//! consumer_profile: engine-profile.yaml
+//! # Resource limits, for validating plans from untrusted sources.
+//! max_tree_depth: 1000
+//! max_node_count: 1000000
+//! max_literal_bytes: 67108864
+//! time_limit_ms: 10000
+//! ```
+//!
+//! Relative paths in `urn_directories`, `urn_templates`, and
+//! `consumer_profile` are interpreted relative to the directory containing
+//! the configuration file.
+
+use crate::input::config::glob;
+use crate::input::config::Config;
+use crate::input::config::DiagnosticPathOverride;
+use crate::input::config::PathPattern;
+use crate::input::profile::ConsumerProfile;
+use crate::input::resolver;
+use crate::output::diagnostic;
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::path::PathBuf;
+
+/// Error type for loading configuration files.
+#[derive(Debug, thiserror::Error)]
+pub enum ConfigFileError {
+    #[error("failed to read {}: {source}", .path.display())]
+    Io {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+
+    #[error("invalid configuration: {0}")]
+    Yaml(#[from] serde_yaml::Error),
+
+    #[error("invalid pattern {pattern:?}: {source}")]
+    Pattern {
+        pattern: String,
+        source: glob::PatternError,
+    },
+
+    #[error("unknown diagnostic class {0}")]
+    UnknownClassification(String),
+
+    #[error("{0}")]
+    Resolver(#[from] resolver::ResolutionError),
+}
+
+/// Serializable validator configuration.
+#[derive(Clone, Debug, Default, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ConfigFile {
+    /// See [`Config::ignore_unknown_fields()`].
+    pub ignore_unknown_fields: bool,
+
+    /// See [`Config::diagnostics_only()`].
+    pub diagnostics_only: bool,
+
+    /// Glob patterns for [`Config::allowed_proto_any_urls`].
+    pub allowed_proto_any_urls: Vec<String>,
+
+    /// Entries for [`Config::diagnostic_level_overrides`].
+    pub diagnostic_level_overrides: Vec<DiagnosticLevelOverride>,
+
+    /// Entries for [`Config::diagnostic_path_overrides`].
+    pub diagnostic_path_overrides: Vec<DiagnosticPathLevelOverride>,
+
+    /// Entries for [`Config::urn_overrides`].
+    pub urn_overrides: Vec<UrnOverride>,
+
+    /// Entries for [`Config::uri_urn_mappings`], in addition to the default
+    /// ones.
+    pub uri_urn_mappings: BTreeMap<String, String>,
+
+    /// Directory trees to resolve extension URNs from; see
+    /// [`resolver::DirectoryResolver`]. A URN declared by more than one file
+    /// in these directories is an error when a plan refers to it.
+    pub urn_directories: Vec<PathBuf>,
+
+    /// Path templates to resolve extension URNs with; see
+    /// [`resolver::TemplateResolver`].
+    pub urn_templates: Vec<UrnTemplate>,
+
+    /// See [`Config::max_urn_resolution_depth`]. A negative value disables
+    /// the limit. If not specified, the default is retained.
+    pub max_urn_resolution_depth: Option<i64>,
+
+    /// See [`Config::consumer_profile`].
+    pub consumer_profile: Option<ConsumerProfileSpec>,
+
+    /// See [`Config::max_tree_depth`].
+    pub max_tree_depth: Option<usize>,
+
+    /// See [`Config::max_node_count`].
+    pub max_node_count: Option<usize>,
+
+    /// See [`Config::max_literal_bytes`].
+    pub max_literal_bytes: Option<usize>,
+
+    /// See [`Config::time_limit`], in milliseconds.
+    pub time_limit_ms: Option<u64>,
+}
+
+/// A consumer profile, specified inline or by the path to its file.
+#[derive(Clone, Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(untagged)]
+pub enum ConsumerProfileSpec {
+    File(PathBuf),
+    Inline(ConsumerProfile),
+}
+
+/// A diagnostic class, specified by its code or by its name.
+#[derive(Clone, Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(untagged)]
+pub enum ClassificationSpec {
+    Code(u32),
+    Name(String),
+}
+
+impl ClassificationSpec {
+    /// Returns the classification this refers to.
+    pub fn resolve(&self) -> Result<diagnostic::Classification, ConfigFileError> {
+        match self {
+            ClassificationSpec::Code(code) => diagnostic::Classification::from_code(*code)
+                .ok_or_else(|| ConfigFileError::UnknownClassification(code.to_string())),
+            ClassificationSpec::Name(name) => crate::iter_diagnostics()
+                .find(|x| &x.name() == name)
+                .ok_or_else(|| ConfigFileError::UnknownClassification(name.clone())),
+        }
+    }
+}
+
+/// Level limits for a class of diagnostics.
+#[derive(Clone, Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct DiagnosticLevelOverride {
+    /// The class of diagnostics to override the level for.
+    pub class: ClassificationSpec,
+
+    /// The minimum level.
+    #[serde(default = "default_min_level")]
+    pub min: diagnostic::Level,
+
+    /// The maximum level.
+    #[serde(default = "default_max_level")]
+    pub max: diagnostic::Level,
+}
+
+/// Level limits for diagnostics at or below particular plan paths.
+#[derive(Clone, Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct DiagnosticPathLevelOverride {
+    /// Pattern for the paths to override the level for; see [`PathPattern`].
+    pub path: String,
+
+    /// The class of diagnostics to override the level for, or None for all
+    /// classes.
+    #[serde(default)]
+    pub class: Option<ClassificationSpec>,
+
+    /// The minimum level.
+    #[serde(default = "default_min_level")]
+    pub min: diagnostic::Level,
+
+    /// The maximum level.
+    #[serde(default = "default_max_level")]
+    pub max: diagnostic::Level,
+
+    /// Why the level is overridden.
+    #[serde(default)]
+    pub reason: Option<String>,
+}
+
+fn default_min_level() -> diagnostic::Level {
+    diagnostic::Level::Info
+}
+
+fn default_max_level() -> diagnostic::Level {
+    diagnostic::Level::Error
+}
+
+/// Remapping rule for extension URNs.
+#[derive(Clone, Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct UrnOverride {
+    /// Glob pattern for the URNs to remap.
+    pub pattern: String,
+
+    /// The URN to resolve instead, or None to disable resolution.
+    #[serde(default)]
+    pub resolve_as: Option<String>,
+}
+
+/// Path template for resolving extension URNs.
+#[derive(Clone, Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct UrnTemplate {
+    /// Glob pattern for the URNs to resolve with this template.
+    pub pattern: String,
+
+    /// The path template; see [`resolver::TemplateResolver`].
+    pub template: String,
+}
+
+/// Parses a glob pattern.
+fn parse_pattern(pattern: &str) -> Result<glob::Pattern, ConfigFileError> {
+    glob::Pattern::new(pattern).map_err(|source| ConfigFileError::Pattern {
+        pattern: pattern.to_string(),
+        source,
+    })
+}
+
+impl ConfigFile {
+    /// Parses a configuration file from YAML (or JSON) text.
+    pub fn from_yaml(text: &str) -> Result<Self, ConfigFileError> {
+        Ok(serde_yaml::from_str(text)?)
+    }
+
+    /// Reads a configuration file.
+    pub fn read<P: AsRef<Path>>(path: P) -> Result<Self, ConfigFileError> {
+        let path = path.as_ref();
+        let text = std::fs::read_to_string(path).map_err(|source| ConfigFileError::Io {
+            path: path.to_path_buf(),
+            source,
+        })?;
+        Self::from_yaml(&text)
+    }
+
+    /// Serializes the configuration file to YAML.
+    pub fn to_yaml(&self) -> Result<String, ConfigFileError> {
+        Ok(serde_yaml::to_string(self)?)
+    }
+
+    /// Applies the settings in this file to the given configuration. Relative
+    /// paths are interpreted relative to base_dir.
+    pub fn apply(&self, config: &mut Config, base_dir: &Path) -> Result<(), ConfigFileError> {
+        if self.ignore_unknown_fields {
+            config.ignore_unknown_fields();
+        }
+        if self.diagnostics_only {
+            config.diagnostics_only();
+        }
+        for pattern in &self.allowed_proto_any_urls {
+            config.allow_proto_any_url(parse_pattern(pattern)?);
+        }
+        for entry in &self.diagnostic_level_overrides {
+            config.override_diagnostic_level(entry.class.resolve()?, entry.min, entry.max);
+        }
+        for entry in &self.diagnostic_path_overrides {
+            let pattern =
+                PathPattern::new(&entry.path).map_err(|source| ConfigFileError::Pattern {
+                    pattern: entry.path.clone(),
+                    source,
+                })?;
+            let mut rule = DiagnosticPathOverride::new(pattern, entry.min, entry.max);
+            if let Some(class) = &entry.class {
+                rule = rule.for_class(class.resolve()?);
+            }
+            rule.reason = entry.reason.clone();
+            config.override_diagnostic_level_at(rule);
+        }
+        for entry in &self.urn_overrides {
+            config.override_urn(parse_pattern(&entry.pattern)?, entry.resolve_as.clone());
+        }
+        for (uri, urn) in &self.uri_urn_mappings {
+            config.map_uri_to_urn(uri, urn);
+        }
+        if !self.urn_directories.is_empty() {
+            let mut resolver = resolver::DirectoryResolver::new();
+            for dir in &self.urn_directories {
+                resolver.add_directory(base_dir.join(dir))?;
+            }
+            config.add_urn_directory_resolver(resolver);
+        }
+        if !self.urn_templates.is_empty() {
+            let mut resolver = resolver::TemplateResolver::new();
+            for entry in &self.urn_templates {
+                resolver.add_template(
+                    parse_pattern(&entry.pattern)?,
+                    base_dir.join(&entry.template).to_string_lossy(),
+                );
+            }
+            config.add_urn_template_resolver(resolver);
+        }
+        if let Some(depth) = self.max_urn_resolution_depth {
+            config.set_max_urn_resolution_depth(usize::try_from(depth).ok());
+        }
+        match &self.consumer_profile {
+            Some(ConsumerProfileSpec::File(path)) => {
+                config.set_consumer_profile(ConsumerProfile::read(base_dir.join(path))?)
+            }
+            Some(ConsumerProfileSpec::Inline(profile)) => {
+                config.set_consumer_profile(profile.clone())
+            }
+            None => {}
+        }
+        if let Some(depth) = self.max_tree_depth {
+            config.set_max_tree_depth(Some(depth));
+        }
+        if let Some(count) = self.max_node_count {
+            config.set_max_node_count(Some(count));
+        }
+        if let Some(bytes) = self.max_literal_bytes {
+            config.set_max_literal_bytes(Some(bytes));
+        }
+        if let Some(limit) = self.time_limit_ms {
+            config.set_time_limit(Some(std::time::Duration::from_millis(limit)));
+        }
+        Ok(())
+    }
+}
+
+impl Config {
+    /// Creates a configuration from the given configuration file; see
+    /// [`ConfigFile`] for the format.
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self, ConfigFileError> {
+        let mut config = Self::new();
+        config.load_file(path)?;
+        Ok(config)
+    }
+
+    /// Applies the settings in the given configuration file to this
+    /// configuration; see [`ConfigFile`] for the format.
+    pub fn load_file<P: AsRef<Path>>(&mut self, path: P) -> Result<(), ConfigFileError> {
+        let path = path.as_ref();
+        let base_dir = path.parent().unwrap_or_else(|| Path::new(""));
+        ConfigFile::read(path)?.apply(self, base_dir)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse() {
+        let file = ConfigFile::from_yaml(
+            "
+            ignore_unknown_fields: true
+            diagnostic_level_overrides:
+              - class: 1
+                min: warn
+              - class: YamlResolutionFailed
+                max: info
+            diagnostic_path_overrides:
+              - path: relations[*].extension_single
+                max: info
+                reason: known
+            urn_overrides:
+              - pattern: extension:com.example:*
+            uri_urn_mappings:
+              https://example.com/a.yaml: extension:com.example:a
+            max_urn_resolution_depth: -1
+            consumer_profile:
+              relations: [read]
+            max_tree_depth: 100
+            time_limit_ms: 1500
+            ",
+        )
+        .unwrap();
+        assert!(file.ignore_unknown_fields);
+        assert_eq!(
+            file.diagnostic_level_overrides[0].class.resolve().unwrap(),
+            diagnostic::Classification::NotYetImplemented
+        );
+        assert_eq!(
+            file.diagnostic_level_overrides[0].min,
+            diagnostic::Level::Warning
+        );
+        assert_eq!(
+            file.diagnostic_level_overrides[1].class.resolve().unwrap(),
+            diagnostic::Classification::YamlResolutionFailed
+        );
+        assert_eq!(
+            file.diagnostic_level_overrides[1].min,
+            diagnostic::Level::Info
+        );
+        assert_eq!(file.urn_overrides[0].resolve_as, None);
+
+        let mut config = Config::new();
+        file.apply(&mut config, Path::new("")).unwrap();
+        assert!(config.ignore_unknown_fields);
+        assert_eq!(config.diagnostic_path_overrides[0].class, None);
+        assert_eq!(
+            config.diagnostic_path_overrides[0].reason.as_deref(),
+            Some("known")
+        );
+        assert_eq!(
+            config.uri_urn_mappings["https://example.com/a.yaml"],
+            "extension:com.example:a"
+        );
+        assert_eq!(config.max_urn_resolution_depth, None);
+        assert_eq!(config.max_tree_depth, Some(100));
+        assert_eq!(config.max_node_count, None);
+        assert_eq!(
+            config.time_limit,
+            Some(std::time::Duration::from_millis(1500))
+        );
+        assert!(!config
+            .consumer_profile
+            .as_ref()
+            .unwrap()
+            .supports_relation("filter"));
+        assert_eq!(
+            ConfigFile::from_yaml(&file.to_yaml().unwrap()).unwrap(),
+            file
+        );
+    }
+
+    #[test]
+    fn errors() {
+        assert!(ConfigFile::from_yaml("unknown_option: 1").is_err());
+        let file = ConfigFile::from_yaml("diagnostic_level_overrides: [{class: Nope}]").unwrap();
+        assert!(matches!(
+            file.apply(&mut Config::new(), Path::new("")),
+            Err(ConfigFileError::UnknownClassification(_))
+        ));
+    }
+}
@@ -0,0 +1,63 @@
+// SPDX-License-Identifier: Apache-2.0
+
+//! This module provides the well-known URIs of the standard extension files.
+//!
+//! Before extensions were identified by URN, plans referred to the standard
+//! extensions by the location of their YAML file, usually on GitHub. Plans
+//! that still use the legacy URI-based extension declarations and refer to
+//! one of these locations are mapped to the URN of the corresponding standard
+//! extension, without requiring any configuration. Mappings in
+//! [`Config::uri_urn_mappings`](crate::Config::uri_urn_mappings) take
+//! precedence over these.
+
+use std::collections::HashMap;
+use substrait_extensions::extensions::SIMPLE_EXTENSIONS;
+
+/// Locations from which the standard extension files used to be referenced.
+/// The file name of the extension is appended to these.
+const PREFIXES: &[&str] = &[
+    "https://github.com/substrait-io/substrait/blob/main/extensions/",
+    "https://raw.githubusercontent.com/substrait-io/substrait/main/extensions/",
+    "/extensions/",
+    "/",
+];
+
+/// Map from the well-known URIs of the standard extension files to the URNs
+/// of the corresponding extensions.
+static TABLE: once_cell::sync::Lazy<HashMap<String, &'static str>> =
+    once_cell::sync::Lazy::new(|| {
+        SIMPLE_EXTENSIONS
+            .iter()
+            .flat_map(|(urn, _)| {
+                let file_name = format!("{}.yaml", urn.rsplit(':').next().unwrap_or_default());
+                PREFIXES
+                    .iter()
+                    .map(move |prefix| (format!("{prefix}{file_name}"), *urn))
+            })
+            .collect()
+    });
+
+/// Returns the URN of the standard extension that used to be referred to by
+/// the given URI, if any.
+pub fn lookup(uri: &str) -> Option<&'static str> {
+    TABLE.get(uri).copied()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn well_known() {
+        for uri in [
+            "https://github.com/substrait-io/substrait/blob/main/extensions/functions_arithmetic.yaml",
+            "/functions_arithmetic.yaml",
+        ] {
+            assert_eq!(
+                lookup(uri),
+                Some("extension:io.substrait:functions_arithmetic")
+            );
+        }
+        assert_eq!(lookup("/unknown.yaml"), None);
+    }
+}
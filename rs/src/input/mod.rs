@@ -6,6 +6,24 @@
 //! validator.
 
 pub mod config;
+pub mod config_file;
+pub mod legacy_uris;
+pub mod profile;
 pub mod proto;
+pub mod registry;
+pub mod resolver;
+pub mod source_map;
 pub mod traits;
 pub mod yaml;
+
+/// Supported serialization formats for the plan passed to the validator.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum Format {
+    /// A substrait.Plan protobuf message, using binary serialization.
+    #[default]
+    Proto,
+
+    /// A substrait.Plan protobuf message, using the canonical protobuf JSON
+    /// serialization.
+    Json,
+}
@@ -0,0 +1,316 @@
+// SPDX-License-Identifier: Apache-2.0
+
+//! This module provides consumer capability profiles.
+//!
+//! A plan can be valid Substrait and still be unusable for a particular
+//! consumer, because consumers generally only implement a subset of the
+//! specification. A [`ConsumerProfile`] describes that subset. When one is
+//! attached to the configuration via
+//! [`Config::set_consumer_profile()`](crate::Config::set_consumer_profile()),
+//! the validator emits diagnostics in the
+//! [`Unsupported`](crate::Classification::Unsupported) group wherever the plan
+//! uses something outside of it.
+//!
+//! Profiles are normally loaded from a YAML (or JSON) file, such that
+//! consumers can publish theirs. Every section is optional; omitting a
+//! section means that the consumer does not restrict that aspect of the plan.
+//! A profile looks like this:
+//!
+//! ```yaml
+//! name: example-engine
+//! # Relation types, named after the fields of the Rel message's oneof.
+//! relations: [read, filter, project, aggregate, sort, fetch, join]
+//! # Expression types, named after the fields of the Expression message's
+//! # oneof.
+//! expressions: [literal, selection, scalar_function, if_then, cast]
+//! # Data type classes, with inclusive ranges for their integer parameters.
+//! # User-defined types are specified by name.
+//! types:
+//!   boolean: {}
+//!   i32: {}
+//!   i64: {}
+//!   string: {}
+//!   varchar:
+//!     parameters: [{max: 65535}]
+//!   decimal:
+//!     parameters: [{max: 38}, {max: 38}]
+//!   struct: {}
+//! # Glob patterns for function compound names, optionally prefixed with the
+//! # URN of the extension that defines the function and ::.
+//! functions:
+//!   - "add:*"
+//!   - "extension:io.substrait:functions_comparison::equal:*"
+//! # File formats, named after the fields of the FileOrFiles message's oneof.
+//! file_formats: [parquet]
+//! ```
+
+use crate::input::config::glob;
+use crate::input::config_file::ConfigFileError;
+use crate::output::type_system::data;
+use std::collections::BTreeMap;
+use std::collections::BTreeSet;
+use std::path::Path;
+
+/// Description of the subset of Substrait supported by a consumer.
+#[derive(Clone, Debug, Default, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ConsumerProfile {
+    /// The name of the consumer, used in diagnostic messages.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+
+    /// The supported relation types, or None if not restricted.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub relations: Option<BTreeSet<String>>,
+
+    /// The supported expression types, or None if not restricted.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub expressions: Option<BTreeSet<String>>,
+
+    /// The supported data type classes, or None if not restricted.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub types: Option<BTreeMap<String, TypeSupport>>,
+
+    /// Glob patterns for the supported functions, or None if not restricted.
+    /// The patterns are compiled when the profile is loaded.
+    #[serde(skip_serializing_if = "Option::is_none", with = "patterns")]
+    pub functions: Option<Vec<glob::Pattern>>,
+
+    /// The supported file formats, or None if not restricted.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub file_formats: Option<BTreeSet<String>>,
+}
+
+/// (De)serialization of lists of glob patterns as lists of strings.
+mod patterns {
+    use crate::input::config::glob;
+    use serde::Deserialize;
+
+    pub fn serialize<S: serde::Serializer>(
+        patterns: &Option<Vec<glob::Pattern>>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        match patterns {
+            Some(patterns) => serializer.collect_seq(patterns.iter().map(glob::Pattern::as_str)),
+            None => serializer.serialize_none(),
+        }
+    }
+
+    pub fn deserialize<'de, D: serde::Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Option<Vec<glob::Pattern>>, D::Error> {
+        Option::<Vec<String>>::deserialize(deserializer)?
+            .map(|patterns| {
+                patterns
+                    .iter()
+                    .map(|pattern| {
+                        glob::Pattern::new(pattern).map_err(|e| {
+                            serde::de::Error::custom(format!("invalid pattern {pattern:?}: {e}"))
+                        })
+                    })
+                    .collect()
+            })
+            .transpose()
+    }
+}
+
+/// Limitations of a consumer for a supported data type class.
+#[derive(Clone, Debug, Default, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct TypeSupport {
+    /// Supported ranges for the integer parameters of the type class, by
+    /// parameter index. Parameters beyond the end of this list are not
+    /// restricted.
+    pub parameters: Vec<ParameterRange>,
+}
+
+/// Inclusive range for an integer type parameter.
+#[derive(Clone, Debug, Default, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ParameterRange {
+    /// The minimum value, if any.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub min: Option<i64>,
+
+    /// The maximum value, if any.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max: Option<i64>,
+}
+
+impl ParameterRange {
+    /// Returns whether the given value lies within this range.
+    pub fn contains(&self, value: i64) -> bool {
+        self.min.is_none_or(|min| value >= min) && self.max.is_none_or(|max| value <= max)
+    }
+}
+
+impl std::fmt::Display for ParameterRange {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match (self.min, self.max) {
+            (Some(min), Some(max)) => write!(f, "{min}..={max}"),
+            (Some(min), None) => write!(f, ">= {min}"),
+            (None, Some(max)) => write!(f, "<= {max}"),
+            (None, None) => write!(f, "any value"),
+        }
+    }
+}
+
+/// Reason why a data type is not supported by a consumer.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum TypeSupportError {
+    /// The type class is not supported at all.
+    Class(String),
+
+    /// The integer parameter with the given index lies outside the supported
+    /// range.
+    Parameter {
+        class: String,
+        index: usize,
+        value: i64,
+        range: String,
+    },
+}
+
+/// Returns the name used to refer to the given class in a profile, or None
+/// if the class is unresolved.
+fn class_name(class: &data::Class) -> Option<String> {
+    match class {
+        data::Class::Simple(simple) => Some(simple.to_string()),
+        data::Class::Compound(compound) => Some(compound.to_string().to_ascii_lowercase()),
+        data::Class::UserDefined(user_defined) => user_defined.name.name().map(str::to_string),
+        data::Class::Unresolved => None,
+    }
+}
+
+impl ConsumerProfile {
+    /// Parses a profile from YAML (or JSON) text.
+    pub fn from_yaml(text: &str) -> Result<Self, ConfigFileError> {
+        Ok(serde_yaml::from_str(text)?)
+    }
+
+    /// Reads a profile from a file.
+    pub fn read<P: AsRef<Path>>(path: P) -> Result<Self, ConfigFileError> {
+        let path = path.as_ref();
+        let text = std::fs::read_to_string(path).map_err(|source| ConfigFileError::Io {
+            path: path.to_path_buf(),
+            source,
+        })?;
+        Self::from_yaml(&text)
+    }
+
+    /// Serializes the profile to YAML.
+    pub fn to_yaml(&self) -> Result<String, ConfigFileError> {
+        Ok(serde_yaml::to_string(self)?)
+    }
+
+    /// Returns the name of the consumer for use in diagnostic messages.
+    pub fn consumer_name(&self) -> &str {
+        self.name.as_deref().unwrap_or("the consumer")
+    }
+
+    /// Returns whether the given relation type is supported.
+    pub fn supports_relation(&self, relation: &str) -> bool {
+        self.relations.as_ref().is_none_or(|x| x.contains(relation))
+    }
+
+    /// Returns whether the given expression type is supported.
+    pub fn supports_expression(&self, expression: &str) -> bool {
+        self.expressions
+            .as_ref()
+            .is_none_or(|x| x.contains(expression))
+    }
+
+    /// Returns whether the given file format is supported.
+    pub fn supports_file_format(&self, format: &str) -> bool {
+        self.file_formats
+            .as_ref()
+            .is_none_or(|x| x.contains(format))
+    }
+
+    /// Returns whether the function with the given compound name, defined in
+    /// the extension with the given URN, is supported.
+    pub fn supports_function(&self, urn: Option<&str>, name: &str) -> bool {
+        let qualified = urn.map(|urn| format!("{urn}::{name}"));
+        self.functions.as_ref().is_none_or(|patterns| {
+            patterns.iter().any(|pattern| {
+                pattern.matches(name) || qualified.as_ref().is_some_and(|x| pattern.matches(x))
+            })
+        })
+    }
+
+    /// Checks whether the given data type is supported. Only the outermost
+    /// type is checked; the types it is parameterized with are not.
+    pub fn check_type(&self, data_type: &data::Type) -> Result<(), TypeSupportError> {
+        let Some(types) = &self.types else {
+            return Ok(());
+        };
+        let Some(class) = class_name(data_type.class()) else {
+            return Ok(());
+        };
+        let Some(support) = types.get(&class) else {
+            return Err(TypeSupportError::Class(class));
+        };
+        for (index, range) in support.parameters.iter().enumerate() {
+            if let Some(value) = data_type.integer_parameter(index) {
+                if !range.contains(value) {
+                    return Err(TypeSupportError::Parameter {
+                        class,
+                        index,
+                        value,
+                        range: range.to_string(),
+                    });
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse() {
+        let profile = ConsumerProfile::from_yaml(
+            "
+            name: test
+            relations: [read, project]
+            functions: ['add:*', 'ext::sub:i32_i32']
+            types:
+              i32: {}
+              decimal:
+                parameters: [{min: 1, max: 38}]
+            ",
+        )
+        .unwrap();
+        assert_eq!(profile.consumer_name(), "test");
+        assert!(profile.supports_relation("read"));
+        assert!(!profile.supports_relation("filter"));
+        assert!(profile.supports_expression("cast"));
+        assert!(profile.supports_file_format("orc"));
+        assert!(profile.supports_function(None, "add:i32_i32"));
+        assert!(profile.supports_function(Some("ext"), "sub:i32_i32"));
+        assert!(!profile.supports_function(None, "sub:i32_i32"));
+        assert!(!profile.supports_function(Some("other"), "sub:i32_i32"));
+        assert_eq!(
+            ConsumerProfile::from_yaml(&profile.to_yaml().unwrap()).unwrap(),
+            profile
+        );
+
+        let range = &profile.types.as_ref().unwrap()["decimal"].parameters[0];
+        assert!(range.contains(38));
+        assert!(!range.contains(39));
+        assert!(!range.contains(0));
+    }
+
+    #[test]
+    fn errors() {
+        assert!(ConsumerProfile::from_yaml("relation: [read]").is_err());
+        let error = ConsumerProfile::from_yaml("functions: ['[']").unwrap_err();
+        assert!(
+            error.to_string().contains("invalid pattern \"[\""),
+            "{error}"
+        );
+    }
+}
@@ -0,0 +1,187 @@
+// SPDX-License-Identifier: Apache-2.0
+
+//! This module provides a cache for parsed extension files that can be shared
+//! between parse calls.
+//!
+//! Resolving an extension URN and parsing the YAML file it refers to
+//! (including validating it against the extension file schema) is relatively
+//! expensive, and tends to dominate validation time when many plans referring
+//! to the same extensions are validated. An [`ExtensionRegistry`] can be
+//! attached to the [`Config`](crate::Config) via
+//! [`Config::set_extension_registry()`](crate::Config::set_extension_registry())
+//! to avoid this. It is populated lazily as extensions are encountered, and
+//! can safely be shared between threads through an [`Arc`].
+//!
+//! When a cached extension is used, the parse tree of the extension file is
+//! grafted into the tree for the plan, such that the diagnostics emitted
+//! while parsing it are still reported for every plan. Their levels are
+//! re-evaluated using the configuration of the parse call. Note however that
+//! URNs are resolved only once, so a registry should only be shared between
+//! configurations that resolve URNs in the same way (i.e. that use the same
+//! URN overrides and resolvers).
+
+use crate::output::extension;
+use crate::output::path;
+use crate::output::tree;
+use std::collections::HashMap;
+use std::collections::HashSet;
+use std::sync::Arc;
+use std::sync::RwLock;
+
+/// The path root used for extension files parsed into a registry entry. When
+/// an entry is replayed, paths using this root are rebased onto the path of
+/// the node that refers to the extension.
+pub(crate) const ROOT: &str = "extension";
+
+/// A cached extension file.
+#[derive(Debug)]
+pub(crate) struct Entry {
+    /// The parsed extension.
+    pub definition: Arc<extension::simple::module::Definition>,
+
+    /// The node for the URN that the extension was resolved for, including
+    /// the parse tree of the extension file and any diagnostics emitted while
+    /// resolving it. Paths in this subtree are relative to [`ROOT`].
+    pub node: tree::Node,
+
+    /// The URNs resolved to form this entry, including the URN of the
+    /// extension itself and its transitive dependencies.
+    urns: HashSet<String>,
+
+    /// The number of levels of URN resolution needed to form this entry.
+    depth: usize,
+}
+
+impl Entry {
+    /// Creates a new entry.
+    pub fn new(definition: Arc<extension::simple::module::Definition>, node: tree::Node) -> Self {
+        let mut urns = HashSet::new();
+        let depth = collect_resolved_urns(&node, 0, &mut urns);
+        Self {
+            definition,
+            node,
+            urns,
+            depth,
+        }
+    }
+
+    /// Returns whether this entry can be used in place of resolving the
+    /// extension at the given URN recursion stack. This is not the case if
+    /// resolution would run into a cyclic dependency or the configured
+    /// recursion limit.
+    pub fn is_applicable(&self, urn_stack: &[String], max_depth: Option<usize>) -> bool {
+        max_depth.is_none_or(|max_depth| urn_stack.len() + self.depth <= max_depth)
+            && !urn_stack.iter().any(|urn| self.urns.contains(urn))
+    }
+}
+
+/// Collects the URNs resolved within the given subtree, and returns the
+/// number of levels of URN resolution it contains plus the given depth.
+fn collect_resolved_urns(node: &tree::Node, depth: usize, urns: &mut HashSet<String>) -> usize {
+    let depth = if let tree::NodeType::ResolvedUrn(urn) = &node.node_type {
+        urns.insert(urn.clone());
+        depth + 1
+    } else {
+        depth
+    };
+    node.data
+        .iter()
+        .filter_map(|data| match data {
+            tree::NodeData::Child(child) => Some(collect_resolved_urns(&child.node, depth, urns)),
+            _ => None,
+        })
+        .fold(depth, usize::max)
+}
+
+/// Thread-safe cache for parsed extension files, keyed by the URN they were
+/// referenced by.
+#[derive(Debug, Default)]
+pub struct ExtensionRegistry {
+    entries: RwLock<HashMap<String, Arc<Entry>>>,
+}
+
+impl ExtensionRegistry {
+    /// Creates an empty registry.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the cached entry for the given URN, if any.
+    pub(crate) fn get(&self, urn: &str) -> Option<Arc<Entry>> {
+        self.entries.read().unwrap().get(urn).cloned()
+    }
+
+    /// Inserts an entry for the given URN. If another thread already inserted
+    /// an entry for it in the meantime, that entry is retained.
+    pub(crate) fn insert(&self, urn: &str, entry: Entry) {
+        self.entries
+            .write()
+            .unwrap()
+            .entry(urn.to_string())
+            .or_insert_with(|| Arc::new(entry));
+    }
+
+    /// Returns the parsed extension for the given URN, if it has been cached.
+    pub fn definition(&self, urn: &str) -> Option<Arc<extension::simple::module::Definition>> {
+        self.get(urn).map(|entry| entry.definition.clone())
+    }
+
+    /// Returns whether an extension has been cached for the given URN.
+    pub fn contains(&self, urn: &str) -> bool {
+        self.entries.read().unwrap().contains_key(urn)
+    }
+
+    /// Returns the URNs for which extensions have been cached.
+    pub fn urns(&self) -> Vec<String> {
+        self.entries.read().unwrap().keys().cloned().collect()
+    }
+
+    /// Returns the number of cached extensions.
+    pub fn len(&self) -> usize {
+        self.entries.read().unwrap().len()
+    }
+
+    /// Returns whether no extensions have been cached yet.
+    pub fn is_empty(&self) -> bool {
+        self.entries.read().unwrap().is_empty()
+    }
+
+    /// Removes all cached extensions, for instance because the files that the
+    /// URNs resolve to have changed.
+    pub fn clear(&self) {
+        self.entries.write().unwrap().clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn resolved(urn: &str, children: Vec<tree::Node>) -> tree::Node {
+        let mut node = tree::Node::from(tree::NodeType::ResolvedUrn(urn.to_string()));
+        node.data = children
+            .into_iter()
+            .map(|child| {
+                tree::NodeData::Child(tree::Child {
+                    path_element: path::PathElement::Field("data".to_string()),
+                    node: Arc::new(child),
+                    recognized: true,
+                })
+            })
+            .collect();
+        node
+    }
+
+    #[test]
+    fn applicability() {
+        let node = resolved("a", vec![resolved("b", vec![]), resolved("c", vec![])]);
+        let entry = Entry::new(Default::default(), node);
+        assert_eq!(entry.depth, 2);
+
+        let stack = ["x".to_string()];
+        assert!(entry.is_applicable(&[], None));
+        assert!(entry.is_applicable(&stack, Some(3)));
+        assert!(!entry.is_applicable(&stack, Some(2)));
+        assert!(!entry.is_applicable(&["b".to_string()], None));
+    }
+}
@@ -0,0 +1,307 @@
+// SPDX-License-Identifier: Apache-2.0
+
+//! This module provides built-in resolvers for extension URNs.
+//!
+//! Two resolvers are provided:
+//!
+//!  - [`DirectoryResolver`] scans one or more directory trees for extension
+//!    YAML files and indexes them by the URN declared in their `urn` key;
+//!  - [`TemplateResolver`] maps URNs matching a glob pattern to a file path
+//!    derived from a template.
+//!
+//! They are registered with the configuration via
+//! [`Config::add_urn_directory_resolver()`](crate::Config::add_urn_directory_resolver())
+//! and
+//! [`Config::add_urn_template_resolver()`](crate::Config::add_urn_template_resolver()).
+//! Like any other resolver, they are only consulted after `urn_overrides` has
+//! been applied, and if they fail to resolve a URN, resolution falls back to
+//! any previously registered resolver and finally the standard extensions
+//! bundled with the validator.
+
+use crate::input::config::glob;
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::path::PathBuf;
+
+/// Error type for the built-in resolvers.
+#[derive(Debug, thiserror::Error)]
+pub enum ResolutionError {
+    #[error("{0} is not known to this resolver")]
+    NotFound(String),
+
+    #[error(
+        "{urn} is declared by multiple files: {}",
+        .paths.iter().map(|x| x.display().to_string()).collect::<Vec<_>>().join(", ")
+    )]
+    Duplicate { urn: String, paths: Vec<PathBuf> },
+
+    #[error("{urn} cannot be mapped to a file: {reason}")]
+    IllegalPath { urn: String, reason: String },
+
+    #[error("failed to read {}: {source}", .path.display())]
+    Io {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+
+    #[error("failed to scan {}: {source}", .path.display())]
+    Scan {
+        path: PathBuf,
+        source: walkdir::Error,
+    },
+}
+
+/// Reads the file at the given path.
+fn read(path: &Path) -> Result<Vec<u8>, ResolutionError> {
+    std::fs::read(path).map_err(|source| ResolutionError::Io {
+        path: path.to_path_buf(),
+        source,
+    })
+}
+
+/// Resolver that looks URNs up in an index of extension YAML files, built by
+/// scanning directory trees and reading the `urn` key of each file.
+#[derive(Clone, Debug, Default)]
+pub struct DirectoryResolver {
+    /// Map from URN to the files that declare it. A URN declared by more than
+    /// one file cannot be resolved.
+    index: BTreeMap<String, Vec<PathBuf>>,
+}
+
+impl DirectoryResolver {
+    /// Creates a resolver with an empty index.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a resolver for the extension files in the given directory tree.
+    pub fn scan<P: AsRef<Path>>(root: P) -> Result<Self, ResolutionError> {
+        let mut resolver = Self::new();
+        resolver.add_directory(root)?;
+        Ok(resolver)
+    }
+
+    /// Adds the extension files in the given directory tree to the index.
+    /// Files are recognized by their `.yaml` or `.yml` file extension. Files
+    /// that cannot be parsed or that do not declare a URN are skipped.
+    pub fn add_directory<P: AsRef<Path>>(&mut self, root: P) -> Result<(), ResolutionError> {
+        let root = root.as_ref();
+        for entry in walkdir::WalkDir::new(root).sort_by_file_name() {
+            let entry = entry.map_err(|source| ResolutionError::Scan {
+                path: root.to_path_buf(),
+                source,
+            })?;
+            let path = entry.path();
+            let is_yaml = path
+                .extension()
+                .and_then(|ext| ext.to_str())
+                .is_some_and(|ext| ext == "yaml" || ext == "yml");
+            if !entry.file_type().is_file() || !is_yaml {
+                continue;
+            }
+
+            // If a plan depends on a file that was skipped here, resolution
+            // fails and is reported then.
+            let urn = std::fs::read(path)
+                .ok()
+                .and_then(|data| serde_yaml::from_slice::<serde_yaml::Value>(&data).ok())
+                .and_then(|yaml| yaml.get("urn")?.as_str().map(str::to_string));
+            if let Some(urn) = urn {
+                self.index.entry(urn).or_default().push(path.to_path_buf());
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns the URNs in the index, in sorted order.
+    pub fn urns(&self) -> impl Iterator<Item = &str> + '_ {
+        self.index.keys().map(|urn| &urn[..])
+    }
+
+    /// Returns the URNs that are declared by more than one file, along with
+    /// the paths to these files, in sorted order.
+    pub fn duplicates(&self) -> impl Iterator<Item = (&str, &[PathBuf])> + '_ {
+        self.index
+            .iter()
+            .filter(|(_, paths)| paths.len() > 1)
+            .map(|(urn, paths)| (&urn[..], &paths[..]))
+    }
+
+    /// Returns the path to the file declaring the given URN.
+    pub fn path_for(&self, urn: &str) -> Result<&Path, ResolutionError> {
+        match self.index.get(urn).map(|paths| &paths[..]) {
+            Some([path]) => Ok(path.as_path()),
+            Some(paths) if !paths.is_empty() => Err(ResolutionError::Duplicate {
+                urn: urn.to_string(),
+                paths: paths.to_vec(),
+            }),
+            _ => Err(ResolutionError::NotFound(urn.to_string())),
+        }
+    }
+
+    /// Resolves the given URN to the contents of the file declaring it.
+    pub fn resolve(&self, urn: &str) -> Result<Vec<u8>, ResolutionError> {
+        read(self.path_for(urn)?)
+    }
+}
+
+/// Resolver that maps URNs matching glob patterns to file paths derived from
+/// templates. The following placeholders are substituted in a template:
+///
+///  - `{urn}`: the complete URN;
+///  - `{owner}`: the owner part of an `extension:<OWNER>:<ID>` URN;
+///  - `{id}`: the ID part of an `extension:<OWNER>:<ID>` URN.
+///
+/// For example, the pattern `extension:com.example:*` with template
+/// `/opt/extensions/{id}.yaml` resolves `extension:com.example:functions` to
+/// `/opt/extensions/functions.yaml`.
+///
+/// Each substituted value must form a single, non-empty path component, such
+/// that a URN cannot refer to files outside of the directory implied by the
+/// template. URNs for which this is not the case, for example because their
+/// ID contains a `/` or is `..`, fail to resolve.
+#[derive(Clone, Debug, Default)]
+pub struct TemplateResolver {
+    /// The pattern-template pairs, in the order in which they are tried.
+    rules: Vec<(glob::Pattern, String)>,
+}
+
+impl TemplateResolver {
+    /// Creates a resolver without any templates.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a template for the URNs matching the given pattern. Templates are
+    /// tried in the order in which they were added.
+    pub fn add_template<S: Into<String>>(&mut self, pattern: glob::Pattern, template: S) {
+        self.rules.push((pattern, template.into()));
+    }
+
+    /// Returns the path that the given URN maps to.
+    pub fn path_for(&self, urn: &str) -> Result<PathBuf, ResolutionError> {
+        self.rules
+            .iter()
+            .find(|(pattern, _)| pattern.matches(urn))
+            .ok_or_else(|| ResolutionError::NotFound(urn.to_string()))
+            .and_then(|(_, template)| expand_template(template, urn).map(PathBuf::from))
+    }
+
+    /// Resolves the given URN to the contents of the file it maps to.
+    pub fn resolve(&self, urn: &str) -> Result<Vec<u8>, ResolutionError> {
+        read(&self.path_for(urn)?)
+    }
+}
+
+/// Substitutes the placeholders in a path template for the given URN.
+fn expand_template(template: &str, urn: &str) -> Result<String, ResolutionError> {
+    let (owner, id) = match urn.splitn(3, ':').collect::<Vec<_>>()[..] {
+        [_, owner, id] => (owner, id),
+        _ => ("", urn),
+    };
+    let substitutions = [("{urn}", urn), ("{owner}", owner), ("{id}", id)];
+    let mut result = String::with_capacity(template.len());
+    let mut remainder = template;
+    while let Some(start) = remainder.find('{') {
+        result += &remainder[..start];
+        remainder = &remainder[start..];
+        let Some(&(placeholder, value)) = substitutions
+            .iter()
+            .find(|(placeholder, _)| remainder.starts_with(placeholder))
+        else {
+            result.push('{');
+            remainder = &remainder[1..];
+            continue;
+        };
+        check_path_component(urn, placeholder, value)?;
+        result += value;
+        remainder = &remainder[placeholder.len()..];
+    }
+    result += remainder;
+    Ok(result)
+}
+
+/// Checks that the value substituted for a placeholder in a path template is
+/// a single, non-empty path component.
+fn check_path_component(urn: &str, placeholder: &str, value: &str) -> Result<(), ResolutionError> {
+    let mut components = Path::new(value).components();
+    let is_single_component = matches!(components.next(), Some(std::path::Component::Normal(_)))
+        && components.next().is_none();
+    if is_single_component && !value.contains(['/', '\\']) {
+        Ok(())
+    } else {
+        Err(ResolutionError::IllegalPath {
+            urn: urn.to_string(),
+            reason: format!("{value:?} is not a valid file name for {placeholder}"),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn templates() {
+        let mut resolver = TemplateResolver::new();
+        resolver.add_template(
+            glob::Pattern::new("extension:com.example:*").unwrap(),
+            "/ext/{owner}/{id}.yaml",
+        );
+        resolver.add_template(glob::Pattern::new("*").unwrap(), "{urn}");
+        assert_eq!(
+            resolver.path_for("extension:com.example:funcs").unwrap(),
+            PathBuf::from("/ext/com.example/funcs.yaml")
+        );
+        assert_eq!(
+            resolver.path_for("extension:org.other:funcs").unwrap(),
+            PathBuf::from("extension:org.other:funcs")
+        );
+
+        // Substitutions can't escape the directory implied by the template.
+        for urn in [
+            "extension:com.example:../secrets",
+            "extension:com.example:..",
+            "extension:com.example:a/b",
+            "extension:com.example:a\\b",
+            "extension:com.example:/etc/passwd",
+            "extension:com.example:",
+        ] {
+            assert!(
+                matches!(
+                    resolver.path_for(urn),
+                    Err(ResolutionError::IllegalPath { .. })
+                ),
+                "{urn}"
+            );
+        }
+    }
+
+    #[test]
+    fn duplicates() {
+        let mut resolver = DirectoryResolver::new();
+        resolver
+            .index
+            .insert("a".to_string(), vec![PathBuf::from("a.yaml")]);
+        resolver.index.insert(
+            "b".to_string(),
+            vec![PathBuf::from("b1.yaml"), PathBuf::from("b2.yaml")],
+        );
+        assert_eq!(resolver.path_for("a").unwrap(), Path::new("a.yaml"));
+        assert!(matches!(
+            resolver.path_for("b"),
+            Err(ResolutionError::Duplicate { .. })
+        ));
+        assert!(matches!(
+            resolver.path_for("c"),
+            Err(ResolutionError::NotFound(_))
+        ));
+        assert_eq!(
+            resolver
+                .duplicates()
+                .map(|(urn, _)| urn)
+                .collect::<Vec<_>>(),
+            ["b"]
+        );
+    }
+}
@@ -0,0 +1,724 @@
+// SPDX-License-Identifier: Apache-2.0
+
+//! Module for mapping tree paths back to locations in textual input.
+//!
+//! JSON plans are decoded using prost-reflect and YAML extension files using
+//! serde_yaml, neither of which retains the location of the values it
+//! produces. This module therefore separately scans the text, recording the
+//! byte range of every value by the [`path::PathBuf`] of the node it
+//! corresponds to, such that the location of a node can be looked up
+//! afterwards. JSON text is scanned in a single pass by a minimal tokenizer;
+//! YAML text is scanned using the marked event stream of libyaml (the same
+//! parser serde_yaml uses).
+//!
+//! Object members and mapping entries are recorded as
+//! [`path::PathElement::Field`]s and array elements as
+//! [`path::PathElement::Index`]es. For YAML, these are exactly the paths of
+//! the parse tree. Protobuf paths are matched against them by splitting
+//! repeated fields into a field and an index, by looking up oneof variants by
+//! their variant name, and by also accepting the lowerCamelCase JSON names of
+//! fields.
+//!
+//! Lookups fall back to the closest enclosing value for which the location is
+//! known, for example when a path refers to a node that has no textual
+//! counterpart.
+
+use crate::output::path;
+use crate::output::span;
+use std::collections::HashMap;
+use std::marker::PhantomData;
+use std::mem::MaybeUninit;
+use std::ops::Range;
+
+/// Returns the path of the child of the given path with the given element.
+fn child(path: &path::PathBuf, element: path::PathElement) -> path::PathBuf {
+    let mut path = path.clone();
+    path.elements.push(element);
+    path
+}
+
+/// Converts a protobuf field name to the name used for it by the canonical
+/// protobuf JSON format.
+fn lower_camel_case(name: &str) -> String {
+    let mut result = String::with_capacity(name.len());
+    let mut capitalize = false;
+    for c in name.chars() {
+        if c == '_' {
+            capitalize = true;
+        } else if capitalize {
+            result.extend(c.to_uppercase());
+            capitalize = false;
+        } else {
+            result.push(c);
+        }
+    }
+    result
+}
+
+/// Converts a path element to the sequence of steps through the recorded
+/// values it corresponds to. Each step lists the alternatives for the
+/// element of the recorded path, in order of preference.
+fn steps(element: &path::PathElement) -> Vec<Vec<path::PathElement>> {
+    let names = |name: &String| {
+        let mut names = vec![path::PathElement::Field(name.clone())];
+        if name.contains('_') {
+            names.push(path::PathElement::Field(lower_camel_case(name)));
+        }
+        names
+    };
+    match element {
+        path::PathElement::Field(field) => vec![names(field)],
+        path::PathElement::Repeated(field, index) => {
+            vec![names(field), vec![path::PathElement::Index(*index)]]
+        }
+        path::PathElement::Variant(_, variant) => vec![names(variant)],
+        path::PathElement::Index(index) => vec![vec![path::PathElement::Index(*index)]],
+    }
+}
+
+/// Container that is being scanned.
+struct Container {
+    /// Path of the container, or None if its location is not recorded.
+    path: Option<path::PathBuf>,
+
+    /// Offset of the first byte of the container.
+    start: usize,
+
+    /// Offset just past the last byte scanned for the container so far.
+    end: usize,
+
+    /// Whether the container is delimited by brackets or braces, rather than
+    /// by indentation.
+    flow: bool,
+
+    /// What the next value scanned within the container represents.
+    next: Next,
+}
+
+/// What the next value scanned within a [`Container`] represents.
+enum Next {
+    /// The key of an object member or mapping entry.
+    Key,
+
+    /// The value of an object member or mapping entry with the given key, or
+    /// with a key that is not a string.
+    Value(Option<String>),
+
+    /// The array element with the given index.
+    Index(usize),
+}
+
+impl Container {
+    /// Creates a container starting at the given offset.
+    fn new(path: Option<path::PathBuf>, start: usize, flow: bool, mapping: bool) -> Self {
+        Self {
+            path,
+            start,
+            end: start,
+            flow,
+            next: if mapping { Next::Key } else { Next::Index(0) },
+        }
+    }
+
+    /// Advances to the next value in the container. key is the value of the
+    /// scanned value if it is a string. Returns the path of the value, if its
+    /// location is to be recorded; keys are not recorded.
+    fn next_value(&mut self, key: Option<String>) -> Option<path::PathBuf> {
+        match std::mem::replace(&mut self.next, Next::Key) {
+            Next::Key => {
+                self.next = Next::Value(key);
+                None
+            }
+            Next::Value(key) => self
+                .path
+                .as_ref()
+                .zip(key)
+                .map(|(path, key)| child(path, path::PathElement::Field(key))),
+            Next::Index(index) => {
+                self.next = Next::Index(index + 1);
+                self.path
+                    .as_ref()
+                    .map(|path| child(path, path::PathElement::Index(index)))
+            }
+        }
+    }
+}
+
+/// Single-pass tokenizer for JSON text, recording the byte range of every
+/// value by its path.
+struct JsonScanner<'a> {
+    /// The text being scanned.
+    text: &'a str,
+
+    /// Offset of the next byte to scan.
+    offset: usize,
+
+    /// Containers that are currently open, innermost last.
+    open: Vec<Container>,
+
+    /// Byte ranges of the values scanned so far.
+    values: HashMap<path::PathBuf, Range<usize>>,
+}
+
+impl<'a> JsonScanner<'a> {
+    /// Scans the given JSON text, returning the byte ranges of all values
+    /// keyed by their path, or None if the text is not valid JSON.
+    fn scan(text: &'a str, root: &path::PathBuf) -> Option<HashMap<path::PathBuf, Range<usize>>> {
+        let mut scanner = Self {
+            text,
+            offset: 0,
+            open: vec![],
+            values: HashMap::new(),
+        };
+        let mut opened = scanner.scan_value(Some(root.clone()))?;
+        while let Some(container) = scanner.open.last() {
+            let object = !matches!(container.next, Next::Index(_));
+            scanner.skip_whitespace();
+            match scanner.peek()? {
+                b'}' if object => {
+                    scanner.offset += 1;
+                    scanner.close();
+                    opened = false;
+                }
+                b']' if !object => {
+                    scanner.offset += 1;
+                    scanner.close();
+                    opened = false;
+                }
+                b',' if !opened => {
+                    scanner.offset += 1;
+                    let path = scanner.scan_key()?;
+                    opened = scanner.scan_value(path)?;
+                }
+                _ if opened => {
+                    let path = scanner.scan_key()?;
+                    opened = scanner.scan_value(path)?;
+                }
+                _ => return None,
+            }
+        }
+        scanner.skip_whitespace();
+        (scanner.offset == text.len()).then_some(scanner.values)
+    }
+
+    /// Returns the next byte without consuming it.
+    fn peek(&self) -> Option<u8> {
+        self.text.as_bytes().get(self.offset).copied()
+    }
+
+    /// Skips past any whitespace.
+    fn skip_whitespace(&mut self) {
+        while matches!(self.peek(), Some(b' ' | b'\t' | b'\n' | b'\r')) {
+            self.offset += 1;
+        }
+    }
+
+    /// Skips past a string, returning its byte range.
+    fn skip_string(&mut self) -> Option<Range<usize>> {
+        let start = self.offset;
+        if self.peek()? != b'"' {
+            return None;
+        }
+        self.offset += 1;
+        loop {
+            match self.peek()? {
+                b'"' => break,
+                b'\\' => self.offset += 2,
+                _ => self.offset += 1,
+            }
+        }
+        self.offset += 1;
+        Some(start..self.offset)
+    }
+
+    /// Scans the value at the current offset, which has the given path.
+    /// Objects and arrays are only opened, returning true; their contents
+    /// are scanned by the caller.
+    fn scan_value(&mut self, path: Option<path::PathBuf>) -> Option<bool> {
+        self.skip_whitespace();
+        let start = self.offset;
+        match self.peek()? {
+            open @ (b'{' | b'[') => {
+                self.offset += 1;
+                self.open
+                    .push(Container::new(path, start, true, open == b'{'));
+                return Some(true);
+            }
+            b'"' => {
+                self.skip_string()?;
+            }
+            _ => {
+                while !matches!(
+                    self.peek(),
+                    None | Some(b' ' | b'\t' | b'\n' | b'\r' | b',' | b':' | b']' | b'}')
+                ) {
+                    self.offset += 1;
+                }
+                if self.offset == start {
+                    return None;
+                }
+            }
+        }
+        self.record(path, start);
+        Some(false)
+    }
+
+    /// Advances to the next value in the innermost container. For objects,
+    /// this scans the key of the member and the colon that follows it.
+    /// Returns the path of the value.
+    fn scan_key(&mut self) -> Option<Option<path::PathBuf>> {
+        if let Next::Index(_) = self.open.last()?.next {
+            return Some(self.open.last_mut()?.next_value(None));
+        }
+        self.skip_whitespace();
+        let key = serde_json::from_str(&self.text[self.skip_string()?]).ok()?;
+        self.open.last_mut()?.next_value(Some(key));
+        self.skip_whitespace();
+        if self.peek()? != b':' {
+            return None;
+        }
+        self.offset += 1;
+        Some(self.open.last_mut()?.next_value(None))
+    }
+
+    /// Closes the innermost container, of which the closing bracket or brace
+    /// was just scanned.
+    fn close(&mut self) {
+        if let Some(container) = self.open.pop() {
+            self.record(container.path, container.start);
+        }
+    }
+
+    /// Records the location of the value with the given path, which started
+    /// at the given offset and ends at the current offset.
+    fn record(&mut self, path: Option<path::PathBuf>, start: usize) {
+        if let Some(path) = path {
+            self.values.insert(path, start..self.offset);
+        }
+    }
+}
+
+/// Event from the libyaml event stream.
+enum YamlEvent {
+    /// A scalar with the given value.
+    Scalar(String),
+
+    /// An alias for an anchored node.
+    Alias,
+
+    /// The start of a mapping or sequence.
+    Start { mapping: bool, flow: bool },
+
+    /// The end of a mapping or sequence.
+    End,
+
+    /// The end of the document or the stream.
+    Finish,
+
+    /// Any other event.
+    Other,
+}
+
+/// Safe wrapper for a libyaml parser reading from a string.
+struct YamlParser<'a> {
+    /// The parser state. libyaml stores a pointer to the parser in the parser
+    /// itself, so it must not move once initialized.
+    parser: Box<MaybeUninit<unsafe_libyaml::yaml_parser_t>>,
+
+    /// The parser refers to the text it reads from.
+    text: PhantomData<&'a str>,
+}
+
+impl<'a> YamlParser<'a> {
+    /// Creates a parser for the given text.
+    fn new(text: &'a str) -> Option<Self> {
+        let mut parser = Box::new(MaybeUninit::uninit());
+        let ptr = MaybeUninit::as_mut_ptr(&mut *parser);
+        // SAFETY: the parser is heap-allocated and initialized before use,
+        // and the lifetime of the wrapper ensures the text outlives it.
+        unsafe {
+            if unsafe_libyaml::yaml_parser_initialize(ptr).fail {
+                return None;
+            }
+            unsafe_libyaml::yaml_parser_set_encoding(ptr, unsafe_libyaml::YAML_UTF8_ENCODING);
+            unsafe_libyaml::yaml_parser_set_input_string(ptr, text.as_ptr(), text.len() as u64);
+        }
+        Some(Self {
+            parser,
+            text: PhantomData,
+        })
+    }
+
+    /// Returns the next event along with the byte range of the text it was
+    /// parsed from, or None if the text is not valid YAML.
+    fn next_event(&mut self) -> Option<(YamlEvent, Range<usize>)> {
+        let parser = MaybeUninit::as_mut_ptr(&mut *self.parser);
+        let mut event = MaybeUninit::<unsafe_libyaml::yaml_event_t>::uninit();
+        // SAFETY: the parser was initialized in new(), and the event is only
+        // read after libyaml initialized it, and deleted afterwards.
+        unsafe {
+            let error = (&*parser).error;
+            if error != unsafe_libyaml::YAML_NO_ERROR
+                || unsafe_libyaml::yaml_parser_parse(parser, event.as_mut_ptr()).fail
+            {
+                return None;
+            }
+            let event = event.as_mut_ptr();
+            let kind = match (*event).type_ {
+                unsafe_libyaml::YAML_SCALAR_EVENT => {
+                    let scalar = &(*event).data.scalar;
+                    YamlEvent::Scalar(
+                        String::from_utf8_lossy(std::slice::from_raw_parts(
+                            scalar.value,
+                            scalar.length as usize,
+                        ))
+                        .into_owned(),
+                    )
+                }
+                unsafe_libyaml::YAML_ALIAS_EVENT => YamlEvent::Alias,
+                unsafe_libyaml::YAML_MAPPING_START_EVENT => YamlEvent::Start {
+                    mapping: true,
+                    flow: (*event).data.mapping_start.style
+                        == unsafe_libyaml::YAML_FLOW_MAPPING_STYLE,
+                },
+                unsafe_libyaml::YAML_SEQUENCE_START_EVENT => YamlEvent::Start {
+                    mapping: false,
+                    flow: (*event).data.sequence_start.style
+                        == unsafe_libyaml::YAML_FLOW_SEQUENCE_STYLE,
+                },
+                unsafe_libyaml::YAML_MAPPING_END_EVENT
+                | unsafe_libyaml::YAML_SEQUENCE_END_EVENT => YamlEvent::End,
+                unsafe_libyaml::YAML_DOCUMENT_END_EVENT | unsafe_libyaml::YAML_STREAM_END_EVENT => {
+                    YamlEvent::Finish
+                }
+                _ => YamlEvent::Other,
+            };
+            let range = (*event).start_mark.index as usize..(*event).end_mark.index as usize;
+            unsafe_libyaml::yaml_event_delete(event);
+            Some((kind, range))
+        }
+    }
+}
+
+impl Drop for YamlParser<'_> {
+    fn drop(&mut self) {
+        // SAFETY: the parser was initialized in new().
+        unsafe { unsafe_libyaml::yaml_parser_delete(MaybeUninit::as_mut_ptr(&mut *self.parser)) }
+    }
+}
+
+/// Scans the first document in the given YAML text, returning the byte
+/// ranges of all values keyed by their path. Locations are only recorded for
+/// the part of the text that precedes any syntax error.
+fn scan_yaml(text: &str, root: &path::PathBuf) -> HashMap<path::PathBuf, Range<usize>> {
+    let mut values = HashMap::new();
+    let Some(mut parser) = YamlParser::new(text) else {
+        return values;
+    };
+    let mut open: Vec<Container> = vec![];
+    while let Some((event, range)) = parser.next_event() {
+        match event {
+            YamlEvent::Scalar(_) | YamlEvent::Alias | YamlEvent::Start { .. } => {
+                let path = match open.last_mut() {
+                    Some(container) => container.next_value(match &event {
+                        YamlEvent::Scalar(key) => Some(key.clone()),
+                        _ => None,
+                    }),
+                    None => Some(root.clone()),
+                };
+                if let YamlEvent::Start { mapping, flow } = event {
+                    open.push(Container::new(path, range.start, flow, mapping));
+                } else {
+                    if let Some(container) = open.last_mut() {
+                        container.end = range.end;
+                    }
+                    if let Some(path) = path {
+                        values.insert(path, range);
+                    }
+                }
+            }
+            YamlEvent::End => {
+                let Some(container) = open.pop() else {
+                    break;
+                };
+                // The end of a block container is only known once the next
+                // token is scanned, so use the end of its last value instead.
+                let end = if container.flow {
+                    range.end
+                } else {
+                    container.end
+                };
+                if let Some(parent) = open.last_mut() {
+                    parent.end = end;
+                }
+                if let Some(path) = container.path {
+                    values.insert(path, container.start..end);
+                }
+            }
+            YamlEvent::Finish => break,
+            YamlEvent::Other => {}
+        }
+    }
+    values
+}
+
+/// Location information for a JSON or YAML file.
+#[derive(Clone, Debug)]
+pub struct SourceMap {
+    /// Name of the file, copied into the spans returned by lookups.
+    file: Option<String>,
+
+    /// The source text.
+    text: String,
+
+    /// Byte offsets at which each line starts.
+    line_starts: Vec<usize>,
+
+    /// Path of the node corresponding to the root value of the file.
+    root: path::PathBuf,
+
+    /// Byte ranges of the values in the file, keyed by their path. Empty if
+    /// the text could not be parsed.
+    values: HashMap<path::PathBuf, Range<usize>>,
+}
+
+impl SourceMap {
+    /// Creates a source map with the given values.
+    fn new(
+        text: &str,
+        file: Option<String>,
+        root: path::PathBuf,
+        values: HashMap<path::PathBuf, Range<usize>>,
+    ) -> Self {
+        let line_starts = std::iter::once(0)
+            .chain(text.match_indices('\n').map(|(index, _)| index + 1))
+            .collect();
+        Self {
+            file,
+            text: text.to_string(),
+            line_starts,
+            root,
+            values,
+        }
+    }
+
+    /// Records the location of all values in the given JSON text, the root
+    /// value of which corresponds to the node at the given path.
+    pub fn from_json(text: &str, file: Option<String>, root: path::PathBuf) -> Self {
+        let values = JsonScanner::scan(text, &root).unwrap_or_default();
+        Self::new(text, file, root, values)
+    }
+
+    /// Records the location of all values in the given YAML text, the root
+    /// value of which corresponds to the node at the given path.
+    pub fn from_yaml(text: &str, file: Option<String>, root: path::PathBuf) -> Self {
+        let values = scan_yaml(text, &root);
+        Self::new(text, file, root, values)
+    }
+
+    /// Returns the path of the node corresponding to the root value of the
+    /// file.
+    pub fn root(&self) -> &path::PathBuf {
+        &self.root
+    }
+
+    /// Returns the location of the value with the given path. If the value
+    /// can't be found, the location of its closest ancestor is returned
+    /// instead. Returns None if the path does not refer to a node within the
+    /// file.
+    pub fn lookup(&self, path: &path::PathBuf) -> Option<span::Span> {
+        if path.root != self.root.root || !path.elements.starts_with(&self.root.elements) {
+            return None;
+        }
+        let mut current = self.root.clone();
+        let mut range = self.values.get(&current)?;
+        'steps: for step in path.elements[self.root.elements.len()..]
+            .iter()
+            .flat_map(steps)
+        {
+            for element in step {
+                let candidate = child(&current, element);
+                if let Some(candidate_range) = self.values.get(&candidate) {
+                    current = candidate;
+                    range = candidate_range;
+                    continue 'steps;
+                }
+            }
+            break;
+        }
+        Some(self.span(range))
+    }
+
+    /// Converts a byte range into a span. The end of the span refers to the
+    /// last character of the range, ignoring trailing whitespace.
+    fn span(&self, range: &Range<usize>) -> span::Span {
+        let value = self.text[range.clone()].trim_end();
+        let last = value
+            .char_indices()
+            .next_back()
+            .map(|(offset, _)| offset)
+            .unwrap_or_default();
+        span::Span {
+            file: self.file.clone(),
+            start: self.position(range.start),
+            end: self.position(range.start + last),
+        }
+    }
+
+    /// Converts a byte offset into a line and column number.
+    fn position(&self, offset: usize) -> span::Position {
+        let line = self.line_starts.partition_point(|start| *start <= offset);
+        let line_start = self.line_starts[line - 1];
+        let column = self
+            .text
+            .get(line_start..offset)
+            .map(|x| x.chars().count())
+            .unwrap_or_default();
+        span::Position {
+            line,
+            column: column + 1,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn root() -> path::PathBuf {
+        path::PathBuf {
+            root: "plan",
+            elements: vec![],
+        }
+    }
+
+    fn at(elements: Vec<path::PathElement>) -> path::PathBuf {
+        path::PathBuf {
+            root: "plan",
+            elements,
+        }
+    }
+
+    fn position(span: Option<span::Span>) -> (usize, usize) {
+        let span = span.unwrap();
+        (span.start.line, span.start.column)
+    }
+
+    #[test]
+    fn json() {
+        let map = SourceMap::from_json(
+            "{\n  \"relations\": [\n    {},\n    { \"root\": { \"names\": [\"a\", \"b\"] } }\n  ]\n}",
+            None,
+            root(),
+        );
+        assert_eq!(position(map.lookup(&root())), (1, 1));
+        let span = map
+            .lookup(&at(vec![
+                path::PathElement::Repeated(String::from("relations"), 1),
+                path::PathElement::Variant(String::from("rel_type"), String::from("root")),
+                path::PathElement::Repeated(String::from("names"), 1),
+            ]))
+            .unwrap();
+        assert_eq!((span.start.line, span.start.column), (4, 32));
+        assert_eq!((span.end.line, span.end.column), (4, 34));
+
+        // Unknown children fall back to the closest ancestor.
+        assert_eq!(
+            position(map.lookup(&at(vec![
+                path::PathElement::Repeated(String::from("relations"), 0),
+                path::PathElement::Field(String::from("rel")),
+            ]))),
+            (3, 5)
+        );
+
+        // Field names match their JSON names, and escapes are decoded.
+        let map = SourceMap::from_json(
+            r#"{"extensionUrns": [{"extensionUrnAnchor": 1, "x": "\"]"}]}"#,
+            Some(String::from("plan.json")),
+            root(),
+        );
+        let span = map.lookup(&at(vec![
+            path::PathElement::Repeated(String::from("extension_urns"), 0),
+            path::PathElement::Field(String::from("extension_urn_anchor")),
+        ]));
+        assert_eq!(span.unwrap().to_string(), "plan.json:1:43");
+        let span = map.lookup(&at(vec![
+            path::PathElement::Repeated(String::from("extension_urns"), 0),
+            path::PathElement::Field(String::from("x")),
+        ]));
+        assert_eq!(span.unwrap().to_string(), "plan.json:1:51");
+
+        // Paths outside of the file have no location.
+        assert!(map
+            .lookup(&path::PathBuf {
+                root: "extension",
+                elements: vec![],
+            })
+            .is_none());
+
+        // Invalid JSON has no locations.
+        assert!(SourceMap::from_json("{", None, root())
+            .lookup(&root())
+            .is_none());
+        assert!(SourceMap::from_json("[1 2]", None, root())
+            .lookup(&root())
+            .is_none());
+    }
+
+    #[test]
+    fn yaml() {
+        let data = at(vec![
+            path::PathElement::Repeated(String::from("extension_urns"), 0),
+            path::PathElement::Field(String::from("data")),
+        ]);
+        let map = SourceMap::from_yaml(
+            "# comment\nurn: extension:test\ntypes:\n  - name: point\n    structure: {x: i32, y: [1, 2]}\n# trailing\n\nscalar_functions: []\n",
+            Some(String::from("test.yaml")),
+            data.clone(),
+        );
+        let lookup = |elements: Vec<path::PathElement>| {
+            let mut path = data.clone();
+            path.elements.extend(elements);
+            map.lookup(&path).unwrap()
+        };
+        assert_eq!(lookup(vec![]).to_string(), "test.yaml:2:1");
+        assert_eq!(
+            lookup(vec![path::PathElement::Field(String::from("urn"))]).to_string(),
+            "test.yaml:2:6"
+        );
+        let span = lookup(vec![
+            path::PathElement::Field(String::from("types")),
+            path::PathElement::Index(0),
+        ]);
+        assert_eq!((span.start.line, span.start.column), (4, 5));
+        assert_eq!((span.end.line, span.end.column), (5, 34));
+        let span = lookup(vec![
+            path::PathElement::Field(String::from("types")),
+            path::PathElement::Index(0),
+            path::PathElement::Field(String::from("structure")),
+            path::PathElement::Field(String::from("y")),
+            path::PathElement::Index(1),
+        ]);
+        assert_eq!((span.start.line, span.start.column), (5, 32));
+        assert_eq!(
+            lookup(vec![path::PathElement::Field(String::from(
+                "scalar_functions"
+            ))])
+            .to_string(),
+            "test.yaml:8:19"
+        );
+
+        // Unknown children fall back to the closest ancestor.
+        assert_eq!(
+            lookup(vec![
+                path::PathElement::Field(String::from("types")),
+                path::PathElement::Index(1),
+            ])
+            .to_string(),
+            "test.yaml:4:3"
+        );
+
+        // Paths outside of the file have no location.
+        assert!(map.lookup(&root()).is_none());
+    }
+}
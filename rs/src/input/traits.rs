@@ -169,6 +169,7 @@ mod tests {
                 summary: None,
                 node_type: tree::NodeType::ProtoMessage("substrait.Plan".to_string()),
                 data_type: None,
+                expression: None,
                 data: vec![],
             }
         );
@@ -182,6 +183,7 @@ mod tests {
                 summary: None,
                 node_type: tree::NodeType::ProtoMessage("substrait.Plan".to_string()),
                 data_type: None,
+                expression: None,
                 data: vec![],
             }
         );
@@ -198,6 +200,7 @@ mod tests {
                 summary: None,
                 node_type: tree::NodeType::ProtoMissingOneOf,
                 data_type: None,
+                expression: None,
                 data: vec![],
             }
         );
@@ -212,6 +215,7 @@ mod tests {
                 summary: None,
                 node_type: tree::NodeType::ProtoMessage("substrait.Rel".to_string()),
                 data_type: None,
+                expression: None,
                 data: vec![],
             }
         );
@@ -257,6 +261,7 @@ mod tests {
                     primitive_data::PrimitiveData::Enum("AGGREGATION_PHASE_UNSPECIFIED")
                 ),
                 data_type: None,
+                expression: None,
                 data: vec![],
             }
         );
@@ -271,6 +276,7 @@ mod tests {
                     primitive_data::PrimitiveData::Enum("AGGREGATION_PHASE_UNSPECIFIED")
                 ),
                 data_type: None,
+                expression: None,
                 data: vec![],
             }
         );
@@ -303,6 +309,7 @@ mod tests {
                     primitive_data::PrimitiveData::Unsigned(0)
                 ),
                 data_type: None,
+                expression: None,
                 data: vec![],
             }
         );
@@ -317,6 +324,7 @@ mod tests {
                     primitive_data::PrimitiveData::Unsigned(42)
                 ),
                 data_type: None,
+                expression: None,
                 data: vec![],
             }
         );
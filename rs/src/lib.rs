@@ -12,19 +12,24 @@
 //!     into the validator and resolved out of the box; to resolve custom
 //!     extension URNs, register a resolver with
 //!     [`Config::add_urn_resolver()`].
-//!  2) Parse the incoming `substrait.Plan` message using [`parse()`] or
-//!     [`validate()`]. This creates a [ParseResult], containing a
+//!  2) Parse the incoming `substrait.Plan` message using [`parse()`],
+//!     [`parse_json()`], [`parse_with_format()`], or [`validate()`]. This
+//!     creates a [ParseResult], containing a
 //!     [tree](output::tree) structure corresponding to the query plan that also
 //!     contains diagnostics and other annotations added by the validator.
+//!     Simple extension YAML files can also be validated on their own, using
+//!     [`validate_extension()`].
 //!  3) You can traverse the tree yourself using [ParseResult::root], or you can
 //!     use one of the methods associated with [ParseResult] to obtain the
 //!     validation results you need.
 //!
-//! Note that only the binary protobuf serialization format is supported at the
-//! input; the JSON format is *not* supported. This is a limitation of `prost`,
-//! the crate that was used for protobuf deserialization. If you're looking for
-//! a library (or CLI) that supports more human-friendly input, check out the
-//! Python bindings.
+//! Both the binary protobuf serialization format and the canonical protobuf
+//! JSON format are supported at the input; see [`InputFormat`]. If you're
+//! looking for a library (or CLI) that supports even more human-friendly
+//! input, such as YAML, check out the Python bindings.
+//! Diagnostics relating to a plan parsed from JSON, as well as YAML syntax
+//! errors in extension files, also carry the line and column they refer to;
+//! see [`Diagnostic::span`].
 #![cfg_attr(
     feature = "private_docs",
     allow(rustdoc::private_intra_doc_links),
@@ -40,23 +45,21 @@ resulting tree can then be [further converted](export) to a few export formats,
 or the validation [diagnostics](output::diagnostic) can simply be
 [extracted](ParseResult::iter_diagnostics()).
 
-This crate only supports the binary protobuf serialization format as input;
-that conversion is ultimately done [here](parse::traversal::parse_proto())
+Binary protobuf input is decoded [here](parse::traversal::parse_proto())
 using a combination of [prost] and some unfortunate magic in
-[substrait_validator_derive]. That is to say: it does *NOT* support JSON format
-or variations thereof. This is because support for protobuf JSON is flaky
-beyond the official bindings, likely in no small part due to all the case
-conversion magic and special cases crammed into that format. Since there are no
-official protobuf bindings for Rust, there is no way to do this from within the
-crate without reinventing the wheel as a square.
-
-Instead, the Python bindings, generated using
-[maturin](https://github.com/PyO3/maturin), include the user-facing logic for
-this. This is also the primary reason why the CLI is written in Python, rather
-than in Rust. When a format other than binary protobuf is passed to the Python
-package, it uses the official protobuf bindings for Python to (re)serialize to
-the binary format, before handing control to the Rust crate. For the return
-trip, the protobuf export format (using the message tree defined in the
+[substrait_validator_derive]. Canonical protobuf JSON input is first decoded
+into a [prost_reflect::DynamicMessage] using the descriptor pool embedded in the
+protobuf bindings, and then transcoded to the same message types (see
+[parse::parse_json()]). Decoding errors for JSON input are reported with the
+JSON path of the offending value, as tracked by [serde_path_to_error].
+
+The Python bindings, generated using
+[maturin](https://github.com/PyO3/maturin), additionally accept formats that
+are not protobuf formats at all, such as the JSON-as-YAML format used by the
+test suite. When such a format is passed to the Python package, it uses the
+official protobuf bindings for Python to (re)serialize to the binary format,
+before handing control to the Rust crate. For the return trip, the protobuf
+export format (using the message tree defined in the
 [substrait.validator](https://github.com/substrait-io/substrait-validator/blob\
 /main/proto/substrait/validator/validator.proto) protobuf namespace) is used to
 pass the parse result to Python.
@@ -71,8 +74,8 @@ C-like libraries.
 Currently, this crate has (almost) no test cases of its own. This is primarily
 to do with the fact that validating only part of a plan would require complex
 context setup and that, ideally, the (bits of) plan for the test cases are
-written in either JSON or a yet-more user-friendly variant thereof. For the
-reasons given above, this can't really be done from within Rust.
+written in either JSON or a yet-more user-friendly variant thereof. YAML, in
+particular, can't really be handled from within Rust.
 
 Instead, tests are run using the [test-runner crate](https://github.com/\
     substrait-io/substrait-validator/tree/main/tests) and its associated Python
@@ -102,7 +105,15 @@ validator at build time (see `build.rs`) and resolved automatically, so plans
 that only use the standard extensions can be validated offline with no
 configuration. Custom extensions are resolved through a user-supplied resolver
 registered via [`Config::add_urn_resolver()`], or remapped to a known URN with
-[`Config::override_urn()`].
+[`Config::override_urn()`]. Resolvers for the common cases of a directory tree
+of extension files and of URN-to-path templates are provided by the
+[resolver module](input::resolver).
+
+Resolving and parsing an extension file is relatively expensive. Applications
+that validate many plans can attach an [`ExtensionRegistry`] to the
+configuration with [`Config::set_extension_registry()`] to parse each file only
+once; the parse tree of a cached file (including its diagnostics) is grafted
+into the tree of each plan that uses it.
 
 ## Build process
 
@@ -176,7 +187,10 @@ use strum::IntoEnumIterator;
 
 // Aliases for common types used on the crate interface.
 pub use input::config::glob::Pattern;
+pub use input::config::CancellationToken;
 pub use input::config::Config;
+pub use input::registry::ExtensionRegistry;
+pub use input::Format as InputFormat;
 pub use output::comment::Comment;
 pub use output::diagnostic::Classification;
 pub use output::diagnostic::Diagnostic;
@@ -190,12 +204,33 @@ pub fn parse<B: prost::bytes::Buf + Clone>(buffer: B, config: &Config) -> ParseR
     parse::parse(buffer, config)
 }
 
+/// Parses and validates the given substrait [Plan] message, serialized using
+/// the canonical protobuf JSON format, and returns the parse tree and
+/// diagnostic results.
+pub fn parse_json(text: &str, config: &Config) -> ParseResult {
+    parse::parse_json(text, config)
+}
+
+/// Parses and validates the given substrait [Plan] message, serialized using
+/// the given format, and returns the parse tree and diagnostic results.
+pub fn parse_with_format(data: &[u8], format: InputFormat, config: &Config) -> ParseResult {
+    parse::parse_with_format(data, format, config)
+}
+
 /// Validates the given substrait [Plan] message and returns the parse tree  and
 /// diagnostic results.
 pub fn validate(plan: &Plan, config: &Config) -> ParseResult {
     parse::validate(plan, config)
 }
 
+/// Parses and validates the given simple extension YAML file on its own,
+/// rather than as part of a plan, and returns the parse tree and diagnostic
+/// results. Dependencies of the file are resolved using the configuration,
+/// just like for extensions referenced by plans.
+pub fn validate_extension(data: &[u8], config: &Config) -> ParseResult {
+    parse::parse_extension(data, config)
+}
+
 /// Returns an iterator that yields all known diagnostic classes.
 pub fn iter_diagnostics() -> impl Iterator<Item = Classification> {
     Classification::iter()
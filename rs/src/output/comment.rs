@@ -141,6 +141,16 @@ impl Comment {
     pub fn elements(&self) -> &[Element] {
         &self.elements
     }
+
+    /// Rebases the paths that this comment links to; see
+    /// [`PathBuf::rebase()`](path::PathBuf::rebase()).
+    pub fn rebase_links(&mut self, from: &path::PathBuf, to: &path::PathBuf) {
+        for element in self.elements.iter_mut() {
+            if let Element::Span(span) = element {
+                span.rebase_link(from, to);
+            }
+        }
+    }
 }
 
 impl std::fmt::Display for Comment {
@@ -265,6 +275,14 @@ impl Brief {
     pub fn spans(&self) -> &[Span] {
         &self.spans
     }
+
+    /// Rebases the paths that this brief links to; see
+    /// [`PathBuf::rebase()`](path::PathBuf::rebase()).
+    pub fn rebase_links(&mut self, from: &path::PathBuf, to: &path::PathBuf) {
+        for span in self.spans.iter_mut() {
+            span.rebase_link(from, to);
+        }
+    }
 }
 
 impl std::fmt::Display for Brief {
@@ -302,6 +320,16 @@ pub struct Span {
     pub link: Option<Link>,
 }
 
+impl Span {
+    /// Rebases the path that this span links to, if any; see
+    /// [`PathBuf::rebase()`](path::PathBuf::rebase()).
+    pub fn rebase_link(&mut self, from: &path::PathBuf, to: &path::PathBuf) {
+        if let Some(Link::Path(path)) = &mut self.link {
+            *path = path.rebase(from, to);
+        }
+    }
+}
+
 impl std::fmt::Display for Span {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(f, "{}", self.text)
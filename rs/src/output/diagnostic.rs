@@ -40,8 +40,12 @@
 //!    doesn't), or refer to a different location altogether (for instance to
 //!    point the user to the previous definition in a note following a
 //!    duplicate definition error).
+//!  - suppression: if the level was adjusted by a path-scoped override (see
+//!    [`Config::override_diagnostic_level_at()`](crate::Config::override_diagnostic_level_at())),
+//!    the pattern of the rule that matched and the reason given for it.
 
 use crate::output::path;
+use crate::output::span;
 use num_traits::cast::FromPrimitive;
 use std::sync::Arc;
 use strum::EnumProperty;
@@ -179,6 +183,9 @@ pub enum Classification {
     #[strum(props(HiddenDescription = "versioning"))]
     Versioning = 7,
 
+    #[strum(props(Description = "resource limit exceeded"))]
+    ResourceLimitExceeded = 9,
+
     #[strum(props(HiddenDescription = "experimental"))]
     Experimental = 999,
 
@@ -310,6 +317,15 @@ pub enum Classification {
     #[strum(props(Description = "unsupported type pattern or derivation construct"))]
     TypeDerivationNotSupported = 4014,
 
+    #[strum(props(Description = "implicit type promotion"))]
+    TypeImplicitPromotion = 4015,
+
+    #[strum(props(Description = "type without total order"))]
+    TypeNoTotalOrder = 4016,
+
+    #[strum(props(Description = "type without known equality"))]
+    TypeNotComparable = 4017,
+
     // Relation-related diagnostics (group 5).
     #[strum(props(HiddenDescription = "relation-related diagnostics"))]
     Relation = 5000,
@@ -323,6 +339,15 @@ pub enum Classification {
     #[strum(props(Description = "invalid relation"))]
     RelationInvalid = 5003,
 
+    #[strum(props(Description = "inconsistent aggregation phases"))]
+    RelationAggregationPhaseMismatch = 5004,
+
+    #[strum(props(Description = "duplicate grouping set"))]
+    RelationDuplicateGroupingSet = 5005,
+
+    #[strum(props(Description = "aggregate without grouping expressions or measures"))]
+    RelationEmptyAggregate = 5006,
+
     // Expression-related diagnostics (group 6).
     #[strum(props(HiddenDescription = "expression-related diagnostics"))]
     Expression = 6000,
@@ -339,6 +364,9 @@ pub enum Classification {
     #[strum(props(Description = "illegal subquery"))]
     ExpressionIllegalSubquery = 6004,
 
+    #[strum(props(Description = "unproven subquery cardinality"))]
+    ExpressionSubqueryCardinality = 6005,
+
     // Redundant declarations (group 7).
     #[strum(props(
         HiddenDescription = "diagnostics for pointing out parts of the plan that can be removed without changing its semantics"
@@ -368,6 +396,30 @@ pub enum Classification {
 
     #[strum(props(Description = "redundant enum variant"))]
     RedundantEnumVariant = 7008,
+
+    // Features not supported by the consumer profile (group 8).
+    #[strum(props(
+        HiddenDescription = "diagnostics for parts of the plan that the configured consumer does not support"
+    ))]
+    Unsupported = 8000,
+
+    #[strum(props(Description = "relation type not supported by consumer"))]
+    UnsupportedRelation = 8001,
+
+    #[strum(props(Description = "expression type not supported by consumer"))]
+    UnsupportedExpression = 8002,
+
+    #[strum(props(Description = "data type not supported by consumer"))]
+    UnsupportedType = 8003,
+
+    #[strum(props(Description = "data type parameter not supported by consumer"))]
+    UnsupportedTypeParameter = 8004,
+
+    #[strum(props(Description = "function not supported by consumer"))]
+    UnsupportedFunction = 8005,
+
+    #[strum(props(Description = "file format not supported by consumer"))]
+    UnsupportedFileFormat = 8006,
 }
 
 impl Classification {
@@ -523,7 +575,10 @@ macro_rules! cause {
 pub type Result<T> = std::result::Result<T, Cause>;
 
 /// Error level for a diagnostic message.
-#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(
+    Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, serde::Serialize, serde::Deserialize,
+)]
+#[serde(rename_all = "lowercase")]
 pub enum Level {
     /// Level used for diagnostics that don't point out anything wrong with
     /// the plan, and merely provide additional information.
@@ -532,6 +587,7 @@ pub enum Level {
     /// Level used for diagnostics that may or may not indicate that there
     /// is something wrong with the plan, i.e. the plan *could* be valid,
     /// but the validator isn't sure.
+    #[serde(alias = "warn")]
     Warning,
 
     /// Level used for diagnostics that indicate that there is definitely
@@ -550,6 +606,12 @@ pub struct RawDiagnostic {
 
     /// The path within the protobuf message where the diagnostic occurred.
     pub path: path::PathBuf,
+
+    /// The location of the diagnostic within the textual input, if known
+    /// up front. If this is None, it is derived from the path when the
+    /// diagnostic is pushed into the tree, if the path refers to something
+    /// that was parsed from text.
+    pub span: Option<span::Span>,
 }
 
 impl std::fmt::Display for RawDiagnostic {
@@ -557,6 +619,9 @@ impl std::fmt::Display for RawDiagnostic {
         write!(f, "{:?}", self.level)?;
         if !f.alternate() {
             write!(f, " at {}", self.path)?;
+            if let Some(span) = &self.span {
+                write!(f, " ({span})")?;
+            }
         }
         write!(f, ": {}", self.cause)
     }
@@ -576,6 +641,35 @@ pub struct Diagnostic {
 
     /// The path within the protobuf message where the diagnostic occurred.
     pub path: path::PathBuf,
+
+    /// The location of the diagnostic within the textual input, if the plan
+    /// was parsed from JSON or the diagnostic is a YAML syntax error.
+    pub span: Option<span::Span>,
+
+    /// The path-scoped level override that was applied to this diagnostic,
+    /// if any.
+    pub suppression: Option<Suppression>,
+}
+
+/// Record of a path-scoped level override that was applied to a diagnostic,
+/// such that it remains visible what was silenced (or escalated) and why.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Suppression {
+    /// The path pattern of the override rule that matched.
+    pub pattern: String,
+
+    /// The reason given for the override, if any.
+    pub reason: Option<String>,
+}
+
+impl std::fmt::Display for Suppression {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "overridden by rule for {}", self.pattern)?;
+        if let Some(reason) = &self.reason {
+            write!(f, ": {reason}")?;
+        }
+        Ok(())
+    }
 }
 
 impl std::fmt::Display for Diagnostic {
@@ -590,6 +684,9 @@ impl std::fmt::Display for Diagnostic {
         }
         if !f.alternate() {
             write!(f, " at {}", self.path)?;
+            if let Some(span) = &self.span {
+                write!(f, " ({span})")?;
+            }
         }
         write!(f, ": {}", self.cause)
     }
@@ -603,6 +700,8 @@ impl RawDiagnostic {
             original_level: self.level,
             adjusted_level,
             path: self.path,
+            span: self.span,
+            suppression: None,
         }
     }
 }
@@ -616,7 +715,8 @@ macro_rules! diag {
         crate::output::diagnostic::RawDiagnostic {
             cause: $cause,
             level: crate::output::diagnostic::Level::$level,
-            path: $path
+            path: $path,
+            span: None,
         }
     };
 }
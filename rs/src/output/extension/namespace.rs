@@ -215,7 +215,11 @@ impl<T> Definition<T> {
     {
         let reference = reference.into();
         let name = reference.name.name().unwrap_or("!").to_string();
+        // Unlike for the placeholder returned by ResolutionResult::new(), the
+        // namespace is known here, so the result is only incomplete if the
+        // name could refer to something in an unresolved nested namespace.
         let mut result = ResolutionResult::new(reference);
+        result.visible_incomplete = false;
         self.resolve_internal(&mut result, true, None, &name, true);
         result
     }
@@ -229,6 +233,7 @@ impl<T> Definition<T> {
         let reference = reference.into();
         let name = reference.name.name().unwrap_or("!").to_string();
         let mut result = ResolutionResult::new(reference);
+        result.visible_incomplete = false;
         self.resolve_internal(&mut result, false, None, &name, true);
         result
     }
@@ -140,7 +140,7 @@ pub struct EnumerationArgumentSlot {
 }
 
 /// Definition of a function option name.
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
 pub struct OptionName {
     /// A human-readable name for this option.
     pub name: String,
@@ -13,5 +13,6 @@ pub mod extension;
 pub mod parse_result;
 pub mod path;
 pub mod primitive_data;
+pub mod span;
 pub mod tree;
 pub mod type_system;
@@ -50,6 +50,10 @@ impl From<Validity> for diagnostic::Level {
 pub struct ParseResult {
     /// The root node of the tree.
     pub root: tree::Node,
+
+    /// The name of the root node, i.e. "plan" for plans and "extension" for
+    /// standalone extension files.
+    pub root_name: &'static str,
 }
 
 impl ParseResult {
@@ -79,6 +83,6 @@ impl ParseResult {
         out: &mut T,
         format: export::Format,
     ) -> std::io::Result<()> {
-        export::export(out, format, "plan", self)
+        export::export(out, format, self.root_name, self)
     }
 }
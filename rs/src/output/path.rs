@@ -11,7 +11,7 @@
 use crate::util;
 
 /// Element of a path to some field of a protobuf message and/or YAML file.
-#[derive(Clone, Debug, PartialEq, Eq)]
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
 pub enum PathElement {
     /// Refers to an optional protobuf field with the given name within the
     /// message, or a YAML map entry with the given key.
@@ -63,7 +63,7 @@ impl PathElement {
 }
 
 /// Refers to a location within a protobuf message.
-#[derive(Clone, Debug, PartialEq, Eq)]
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
 pub struct PathBuf {
     pub root: &'static str,
     pub elements: Vec<PathElement>,
@@ -79,6 +79,27 @@ impl std::fmt::Display for PathBuf {
     }
 }
 
+impl PathBuf {
+    /// Returns the path this path would have if the subtree rooted at `from`
+    /// were moved to `to`. Paths outside of that subtree are returned
+    /// unchanged.
+    pub fn rebase(&self, from: &PathBuf, to: &PathBuf) -> PathBuf {
+        if self.root == from.root && self.elements.starts_with(&from.elements) {
+            PathBuf {
+                root: to.root,
+                elements: to
+                    .elements
+                    .iter()
+                    .chain(self.elements[from.elements.len()..].iter())
+                    .cloned()
+                    .collect(),
+            }
+        } else {
+            self.clone()
+        }
+    }
+}
+
 /// Used to track a location within a protobuf message. The owned version
 /// is PathBuf.
 #[derive(Clone, Debug, PartialEq)]
@@ -198,4 +219,15 @@ mod tests {
         assert_eq!(e.to_string(), "a.\"4\".\"8\"[15].\"16\"<\"23\">[42]");
         assert_eq!(buf.to_string(), "a.\"4\".\"8\"[15].\"16\"<\"23\">[42]");
     }
+
+    #[test]
+    fn rebase() {
+        let from = Path::Root("a").with_field("b").to_path_buf();
+        let to = Path::Root("x").with_repeated("y", 1).to_path_buf();
+        let inside = Path::Root("a").with_field("b").with_index(3).to_path_buf();
+        let outside = Path::Root("a").with_field("c").to_path_buf();
+        assert_eq!(inside.rebase(&from, &to).to_string(), "x.y[1][3]");
+        assert_eq!(from.rebase(&from, &to).to_string(), "x.y[1]");
+        assert_eq!(outside.rebase(&from, &to), outside);
+    }
 }
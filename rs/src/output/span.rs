@@ -0,0 +1,62 @@
+// SPDX-License-Identifier: Apache-2.0
+
+//! Module for representing locations within textual input.
+//!
+//! Diagnostics normally refer to a node of the parse tree through a
+//! [`path::PathBuf`](crate::output::path::PathBuf). When the plan was parsed
+//! from protobuf JSON, or when an extension YAML file fails to parse, a
+//! [`Span`] can additionally be attached, such that editors and CI systems can
+//! point at the exact line and column.
+
+/// A position within a text file. Both the line and column number are
+/// one-based, and the column number counts Unicode characters.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Position {
+    /// One-based line number.
+    pub line: usize,
+
+    /// One-based column number.
+    pub column: usize,
+}
+
+impl std::fmt::Display for Position {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}:{}", self.line, self.column)
+    }
+}
+
+/// A range of text within a file, from start up to and including end.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Span {
+    /// The name of the file the span refers to. For extensions, this is the
+    /// URN of the extension. None is used for the input plan itself, as the
+    /// validator does not know where it came from.
+    pub file: Option<String>,
+
+    /// Position of the first character of the span.
+    pub start: Position,
+
+    /// Position of the last character of the span.
+    pub end: Position,
+}
+
+impl Span {
+    /// Creates a span that covers a single position.
+    pub fn at(file: Option<String>, line: usize, column: usize) -> Self {
+        let position = Position { line, column };
+        Self {
+            file,
+            start: position,
+            end: position,
+        }
+    }
+}
+
+impl std::fmt::Display for Span {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if let Some(file) = &self.file {
+            write!(f, "{file}:")?;
+        }
+        write!(f, "{}", self.start)
+    }
+}
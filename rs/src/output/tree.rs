@@ -95,6 +95,12 @@ pub struct Node {
     /// data.
     pub data_type: Option<data::Type>,
 
+    /// For the toplevel expressions of a relation, i.e. those that are not
+    /// part of another expression, a textual representation of the
+    /// expression. Unlike the brief description, this is also set when
+    /// documentation is disabled.
+    pub expression: Option<String>,
+
     /// The information gathered about the message.
     ///
     /// This normally includes all child nodes for this message, possibly
@@ -114,6 +120,7 @@ impl From<NodeType> for Node {
             summary: None,
             node_type,
             data_type: None,
+            expression: None,
             data: vec![],
         }
     }
@@ -172,6 +179,30 @@ impl Node {
     pub fn data_type(&self) -> data::Type {
         self.data_type.clone().unwrap_or_default()
     }
+
+    /// Rebases all paths stored in this subtree, i.e. those of diagnostics,
+    /// links, and node references; see
+    /// [`PathBuf::rebase()`](path::PathBuf::rebase()). This is used to graft
+    /// a subtree that was parsed elsewhere into a tree.
+    pub fn rebase_paths(&mut self, from: &path::PathBuf, to: &path::PathBuf) {
+        if let Some(brief) = &mut self.brief {
+            brief.rebase_links(from, to);
+        }
+        if let Some(summary) = &mut self.summary {
+            summary.rebase_links(from, to);
+        }
+        if let NodeType::NodeReference(_, reference) = &mut self.node_type {
+            reference.path = reference.path.rebase(from, to);
+        }
+        for data in self.data.iter_mut() {
+            match data {
+                NodeData::Child(child) => Arc::make_mut(&mut child.node).rebase_paths(from, to),
+                NodeData::Diagnostic(diag) => diag.path = diag.path.rebase(from, to),
+                NodeData::DataType(_) => {}
+                NodeData::Comment(comment) => comment.rebase_links(from, to),
+            }
+        }
+    }
 }
 
 /// The original data type that the node represents, to (in theory) allow the
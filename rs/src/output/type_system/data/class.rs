@@ -110,6 +110,46 @@ impl Class {
             (a, b) => a == b,
         }
     }
+
+    /// Returns the short name used for this class in the argument signature
+    /// of compound function names, or None if this class is unresolved.
+    pub fn signature_name(&self) -> Option<String> {
+        Some(match self {
+            Class::Simple(simple) => match simple {
+                Simple::Boolean => "bool",
+                Simple::I8 => "i8",
+                Simple::I16 => "i16",
+                Simple::I32 => "i32",
+                Simple::I64 => "i64",
+                Simple::Fp32 => "fp32",
+                Simple::Fp64 => "fp64",
+                Simple::String => "str",
+                Simple::Binary => "vbin",
+                Simple::Timestamp => "ts",
+                Simple::TimestampTz => "tstz",
+                Simple::Date => "date",
+                Simple::Time => "time",
+                Simple::IntervalYear => "iyear",
+                Simple::IntervalDay => "iday",
+                Simple::Uuid => "uuid",
+            }
+            .to_string(),
+            Class::Compound(compound) => match compound {
+                Compound::FixedChar => "fchar",
+                Compound::VarChar => "vchar",
+                Compound::FixedBinary => "fbin",
+                Compound::Decimal => "dec",
+                Compound::Struct | Compound::NamedStruct => "struct",
+                Compound::List => "list",
+                Compound::Map => "map",
+            }
+            .to_string(),
+            Class::UserDefined(user_defined) => {
+                format!("u!{}", user_defined.name.name().unwrap_or("?"))
+            }
+            Class::Unresolved => return None,
+        })
+    }
 }
 
 /// Enumeration of simple types defined by Substrait.
@@ -201,6 +201,124 @@ impl Definition {
             })
     }
 
+    /// Returns whether values of this type have a total order, i.e. whether
+    /// sorting them in ascending or descending order is well-defined. Maps
+    /// have no defined order, and Substrait has no way to define one for
+    /// user-defined types. Unresolved types are assumed to be orderable to
+    /// avoid noise.
+    pub fn has_total_order(&self) -> bool {
+        match &self.class {
+            data::Class::Compound(data::class::Compound::Map) | data::Class::UserDefined(_) => {
+                false
+            }
+            _ => self.parameter_types().all(|x| x.has_total_order()),
+        }
+    }
+
+    /// Returns whether values of this type are known to be comparable for
+    /// equality, as needed for DISTINCT aggregation. Substrait has no way to
+    /// define equality for user-defined types. Unresolved types are assumed
+    /// to be comparable to avoid noise.
+    pub fn is_comparable(&self) -> bool {
+        match &self.class {
+            data::Class::UserDefined(_) => false,
+            _ => self.parameter_types().all(|x| x.is_comparable()),
+        }
+    }
+
+    /// Returns an iterator over the parameters of this type that are data
+    /// types, such as the field types of a struct.
+    fn parameter_types(&self) -> impl Iterator<Item = Type> + '_ {
+        self.parameters
+            .iter()
+            .filter_map(|x| x.value.as_ref().and_then(|x| x.get_data_type()))
+    }
+
+    /// Returns the type that both this type and the given type can be
+    /// promoted to without loss of information, if the types differ but such
+    /// a promotion exists. Nullability is ignored for the purpose of the
+    /// comparison; the returned type is nullable if either type is. The
+    /// following promotions are defined:
+    ///
+    ///  - integers are widened to the widest of the two (i8 -> i16 -> i32
+    ///    -> i64);
+    ///  - decimals are unified to the smallest decimal type that can
+    ///    represent the integral and fractional digits of both, provided that
+    ///    this fits within the maximum precision;
+    ///  - FIXEDCHAR and VARCHAR are promoted to a VARCHAR of the largest of
+    ///    the two lengths, and any of these is promoted to string when
+    ///    combined with a string.
+    ///
+    /// Types with user-defined variations are never promoted.
+    pub fn common_promotion(&self, other: &Definition) -> Option<Type> {
+        use data::class::Compound;
+        use data::class::Simple;
+
+        if !matches!(self.variation, data::Variation::SystemPreferred)
+            || !matches!(other.variation, data::Variation::SystemPreferred)
+        {
+            return None;
+        }
+        let nullable = self.nullable || other.nullable;
+        let new = |class, parameters| {
+            data::new_type(
+                class,
+                nullable,
+                data::Variation::SystemPreferred,
+                parameters,
+            )
+            .ok()
+        };
+
+        let integer_rank = |class: &data::Class| match class {
+            data::Class::Simple(Simple::I8) => Some(0),
+            data::Class::Simple(Simple::I16) => Some(1),
+            data::Class::Simple(Simple::I32) => Some(2),
+            data::Class::Simple(Simple::I64) => Some(3),
+            _ => None,
+        };
+        if let (Some(a), Some(b)) = (integer_rank(&self.class), integer_rank(&other.class)) {
+            return match a.cmp(&b) {
+                std::cmp::Ordering::Less => new(other.class.clone(), vec![]),
+                std::cmp::Ordering::Equal => None,
+                std::cmp::Ordering::Greater => new(self.class.clone(), vec![]),
+            };
+        }
+
+        let decimal = data::Class::Compound(Compound::Decimal);
+        if self.class == decimal && other.class == decimal {
+            let (p1, s1) = (self.integer_parameter(0)?, self.integer_parameter(1)?);
+            let (p2, s2) = (other.integer_parameter(0)?, other.integer_parameter(1)?);
+            if (p1, s1) == (p2, s2) {
+                return None;
+            }
+            let scale = s1.max(s2);
+            let precision = (p1 - s1).max(p2 - s2) + scale;
+            return new(decimal, vec![precision.into(), scale.into()]);
+        }
+
+        let string_length = |def: &Definition| match def.class {
+            data::Class::Simple(Simple::String) => Some(None),
+            data::Class::Compound(Compound::FixedChar)
+            | data::Class::Compound(Compound::VarChar) => def.integer_parameter(0).map(Some),
+            _ => None,
+        };
+        if let (Some(a), Some(b)) = (string_length(self), string_length(other)) {
+            if self.class == other.class && a == b {
+                return None;
+            }
+            return match (a, b) {
+                (Some(a), Some(b)) => new(
+                    data::Class::Compound(Compound::VarChar),
+                    vec![a.max(b).into()],
+                ),
+                _ => new(data::Class::Simple(Simple::String), vec![]),
+            };
+        }
+
+        None
+    }
+
     /// Returns whether this is a STRUCT or NSTRUCT type.
     pub fn is_struct(&self) -> bool {
         matches!(
@@ -480,10 +480,12 @@ impl Binding {
         value: &meta::Value,
     ) -> diagnostic::Result<bool> {
         // If nullability is specified, the value must be a data type and its
-        // nullability must match the pattern.
+        // nullability must match the pattern. The pattern is matched within
+        // the same context, such that it can itself be a binding; this is
+        // used to handle MIRROR nullability.
         if let Some(nullability) = &self.nullability {
             if let meta::Value::DataType(dt) = value {
-                if !nullability.match_pattern(&dt.nullable().into())? {
+                if !nullability.match_pattern_with_context(context, &dt.nullable().into())? {
                     return Ok(false);
                 }
             } else {
@@ -541,9 +543,13 @@ impl Binding {
         if let Some(nullability) = &self.nullability {
             // Yield the current value of the binding, augmented with the
             // nullability field.
-            if let Some(current) = context.bindings.get(&self.name.to_ascii_lowercase()) {
+            if let Some(current) = context
+                .bindings
+                .get(&self.name.to_ascii_lowercase())
+                .cloned()
+            {
                 if let meta::Value::DataType(current) = current {
-                    let nullability = nullability.evaluate()?;
+                    let nullability = nullability.evaluate_with_context(context)?;
                     if let meta::Value::Boolean(nullability) = nullability {
                         Ok(current.override_nullable(nullability).into())
                     } else {
@@ -7,16 +7,21 @@
 //! information.
 
 use crate::input::config;
+use crate::input::proto::substrait;
+use crate::input::source_map;
 use crate::output::comment;
 use crate::output::diagnostic;
 use crate::output::extension;
 use crate::output::path;
+use crate::output::primitive_data;
+use crate::output::span;
 use crate::output::tree;
 use crate::output::type_system::data;
 use std::collections::HashMap;
 use std::collections::HashSet;
 use std::fmt::Debug;
 use std::hash::Hash;
+use std::sync::Arc;
 
 /// Parse/validation context and output node, passed to parser functions along
 /// with a reference to the to-be-parsed input node.
@@ -108,11 +113,16 @@ impl<'a> Context<'a> {
         brief: Option<B>,
     ) {
         self.output.class = class;
-        self.output.brief = brief.map(|c| c.into());
+        if self.documentation_enabled() {
+            self.output.brief = brief.map(|c| c.into());
+        }
     }
 
     /// Appends to the summary of this node.
     pub fn push_summary<C: Into<comment::Comment>>(&mut self, comment: C) {
+        if !self.documentation_enabled() {
+            return;
+        }
         if let Some(summary) = self.output.summary.as_mut() {
             summary.extend(comment.into())
         } else {
@@ -131,29 +141,16 @@ impl<'a> Context<'a> {
     /// Pushes a diagnostic into the node. This also evaluates its adjusted
     /// error level.
     pub fn push_diagnostic(&mut self, diag: diagnostic::RawDiagnostic) {
-        // Get the configured level limits for this diagnostic. First try the
-        // classification of the diagnostic itself, then its group, and then
-        // finally Unclassified. If no entries exist, simply yield
-        // (Info, Error), which is no-op.
-        let (min, max) = self
-            .config
-            .diagnostic_level_overrides
-            .get(&diag.cause.classification)
-            .or_else(|| {
-                self.config
-                    .diagnostic_level_overrides
-                    .get(&diag.cause.classification.group())
-            })
-            .or_else(|| {
-                self.config
-                    .diagnostic_level_overrides
-                    .get(&diagnostic::Classification::Unclassified)
-            })
-            .unwrap_or(&(diagnostic::Level::Info, diagnostic::Level::Error));
-
-        // Adjust the level.
-        let adjusted_level = diag.level.clamp(*min, *max);
-        let adjusted = diag.adjust_level(adjusted_level);
+        // Adjust the level according to the configuration.
+        let level = diag.level;
+        let mut adjusted = diag.adjust_level(level);
+        self.config.adjust_diagnostic(&mut adjusted);
+
+        // Look up the location of the diagnostic in the textual input, if the
+        // path refers to something that was parsed from text.
+        if adjusted.span.is_none() {
+            adjusted.span = self.state.source_span(&adjusted.path);
+        }
 
         // Actually push the data item.
         self.output.data.push(tree::NodeData::Diagnostic(adjusted));
@@ -161,7 +158,17 @@ impl<'a> Context<'a> {
 
     /// Pushes a comment into the node.
     pub fn push_comment<C: Into<comment::Comment>>(&mut self, comment: C) {
-        self.push(tree::NodeData::Comment(comment.into()))
+        if self.documentation_enabled() {
+            self.push(tree::NodeData::Comment(comment.into()))
+        }
+    }
+
+    /// Returns whether comments, briefs, and summaries should be generated,
+    /// i.e. whether the configuration doesn't ask for diagnostics only.
+    /// Callers can use this to avoid formatting documentation that would be
+    /// discarded anyway.
+    pub fn documentation_enabled(&self) -> bool {
+        !self.config.diagnostics_only
     }
 
     /// Sets the data type "returned" by this node. Specifically:
@@ -235,11 +242,16 @@ impl<'a> Context<'a> {
         // in.
         self.state.schema_stack.push(None);
 
+        // Expressions within the relation tree are not part of any
+        // expression outside of it.
+        let expression_depth = std::mem::take(&mut self.state.expression_depth);
+
         // Ensure that return statements can't break out of the context
         // early by wrapping the block in a closure first.
         let result = f(self);
 
-        // Pop the schema again.
+        // Restore the expression depth and pop the schema again.
+        self.state.expression_depth = expression_depth;
         self.state
             .schema_stack
             .pop()
@@ -248,11 +260,37 @@ impl<'a> Context<'a> {
         result
     }
 
+    /// Parses an expression using the given function, keeping track of
+    /// whether expressions are nested. Returns the result of the function,
+    /// along with whether the expression is a toplevel expression of its
+    /// relation, i.e. one that is not part of another expression.
+    pub fn enter_expression<R, F: FnOnce(&mut Context) -> R>(&mut self, f: F) -> (R, bool) {
+        let toplevel = self.state.expression_depth == 0;
+        self.state.expression_depth += 1;
+        let result = f(self);
+        self.state.expression_depth -= 1;
+        (result, toplevel)
+    }
+
+    /// Sets the textual representation of the expression represented by the
+    /// current node. This should only be used for toplevel expressions.
+    pub fn set_expression<S: Into<String>>(&mut self, expression: S) {
+        self.output.expression = Some(expression.into());
+    }
+
     /// Returns all data that has thus far been pushed into the current node.
     pub fn node_data(&self) -> &[tree::NodeData] {
         &self.output.data
     }
 
+    /// Removes the children that were not recognized by the validator from
+    /// the current node.
+    pub fn remove_unrecognized_children(&mut self) {
+        self.output
+            .data
+            .retain(|x| !matches!(x, tree::NodeData::Child(child) if !child.recognized));
+    }
+
     /// Returns the URN -> simple extension module map. This includes
     /// transitive dependencies that don't have an anchor.
     pub fn extension_modules(
@@ -278,6 +316,98 @@ impl<'a> Context<'a> {
             .define(anchor, urn, self.breadcrumb.path.to_path_buf())
     }
 
+    /// Returns the Substrait version declared by the plan, if known.
+    pub fn plan_version(&self) -> Option<&semver::Version> {
+        self.state.plan_version.as_ref()
+    }
+
+    /// Sets the Substrait version declared by the plan.
+    pub fn set_plan_version(&mut self, version: semver::Version) {
+        self.state.plan_version = Some(version);
+    }
+
+    /// Accounts for the given node being added as a child of the current
+    /// node, and checks the resource limits in the configuration. Returns
+    /// false if a limit has been exceeded or validation was cancelled, in
+    /// which case the child should not be parsed. A diagnostic is emitted
+    /// the first time this happens; all subsequent checks fail silently.
+    pub fn check_budget(&mut self, child: &tree::Node) -> bool {
+        let config = self.config;
+        let budget = &mut self.state.budget;
+        if budget.exceeded {
+            return false;
+        }
+        let start = *budget.start.get_or_insert_with(std::time::Instant::now);
+        budget.node_count += 1;
+        if let tree::NodeType::ProtoPrimitive(_, data) | tree::NodeType::YamlPrimitive(data) =
+            &child.node_type
+        {
+            match data {
+                primitive_data::PrimitiveData::String(x) => budget.literal_bytes += x.len(),
+                primitive_data::PrimitiveData::Bytes(x) => budget.literal_bytes += x.len(),
+                _ => {}
+            }
+        }
+
+        let message = if config
+            .cancellation_token
+            .as_ref()
+            .is_some_and(|x| x.is_cancelled())
+        {
+            String::from("validation was cancelled")
+        } else if let Some(max) = config.max_tree_depth.filter(|x| budget.depth >= *x) {
+            format!("parse tree depth exceeds the limit of {max}")
+        } else if let Some(max) = config.max_node_count.filter(|x| budget.node_count > *x) {
+            format!("parse tree node count exceeds the limit of {max}")
+        } else if let Some(max) = config
+            .max_literal_bytes
+            .filter(|x| budget.literal_bytes > *x)
+        {
+            format!("size of string and binary data exceeds the limit of {max} bytes")
+        } else if let Some(max) = config.time_limit.filter(|x| start.elapsed() > *x) {
+            format!("validation time exceeds the limit of {max:?}")
+        } else {
+            return true;
+        };
+        budget.exceeded = true;
+        diagnostic!(
+            self,
+            Error,
+            ResourceLimitExceeded,
+            "{message}; the remainder of the input was not validated"
+        );
+        false
+    }
+
+    /// Returns the resources used so far.
+    pub fn budget(&mut self) -> &mut Budget {
+        &mut self.state.budget
+    }
+
+    /// Returns the legacy extension declarations that were decoded alongside
+    /// the plan, if the plan contained any.
+    pub fn legacy_extensions(&self) -> Option<&Arc<substrait::validator::LegacyPlan>> {
+        self.state.legacy_extensions.as_ref()
+    }
+
+    /// Returns the resolver for legacy URI anchors and references.
+    pub fn extension_uris(&mut self) -> &mut Resolver<u32, extension::simple::module::Reference> {
+        &mut self.state.extension_uris
+    }
+
+    /// Registers a legacy extension URI definition. Shorthand for
+    /// extension_uris().define(), using the current path as the registration
+    /// path.
+    pub fn define_extension_uri(
+        &mut self,
+        anchor: u32,
+        module: extension::simple::module::Reference,
+    ) -> Result<(), (extension::simple::module::Reference, path::PathBuf)> {
+        self.state
+            .extension_uris
+            .define(anchor, module, self.breadcrumb.path.to_path_buf())
+    }
+
     /// Returns the resolver for function anchors and references.
     pub fn functions(
         &mut self,
@@ -425,6 +555,47 @@ impl<'a> Context<'a> {
     pub fn urn_stack(&mut self) -> &mut Vec<String> {
         &mut self.state.urn_stack
     }
+
+    /// Registers location information for a part of the tree that is parsed
+    /// from a text format. Diagnostics pushed for nodes within it from then
+    /// on refer to their location in the text.
+    pub fn add_source(&mut self, source: Arc<source_map::SourceMap>) {
+        self.state.sources.push(source);
+    }
+
+    /// Records that URN resolution was cut short because of a cyclic
+    /// dependency or the configured recursion limit.
+    pub fn set_urn_resolution_truncated(&mut self) {
+        self.state.urn_resolution_truncated = true;
+    }
+
+    /// Grafts the data of a node that was parsed in a different context onto
+    /// the current node, replacing its node type. This is used to replay
+    /// extension files cached in an extension registry. Paths are rebased
+    /// from root to the path of the current node, and diagnostic levels are
+    /// re-evaluated against the current configuration.
+    pub fn replay(&mut self, node: &tree::Node, root: &path::PathBuf) {
+        let path = self.path_buf();
+        let mut node = node.clone();
+        node.rebase_paths(root, &path);
+        adjust_diagnostic_levels(&mut node, self.config);
+        self.output.node_type = node.node_type;
+        self.output.data.extend(node.data);
+    }
+}
+
+/// Re-evaluates the adjusted level of all diagnostics in the given subtree
+/// against the given configuration.
+fn adjust_diagnostic_levels(node: &mut tree::Node, config: &config::Config) {
+    for data in node.data.iter_mut() {
+        match data {
+            tree::NodeData::Child(child) => {
+                adjust_diagnostic_levels(Arc::make_mut(&mut child.node), config)
+            }
+            tree::NodeData::Diagnostic(diag) => config.adjust_diagnostic(diag),
+            tree::NodeData::DataType(_) | tree::NodeData::Comment(_) => {}
+        }
+    }
 }
 
 #[derive(Clone, Debug)]
@@ -505,6 +676,9 @@ where
 /// Global state information tracked by the validation logic.
 #[derive(Default)]
 pub struct State {
+    /// The Substrait version declared by the plan, if it is known and valid.
+    pub plan_version: Option<semver::Version>,
+
     /// List of all resolved simple extension modules by URN, including
     /// transitive dependencies.
     pub extension_modules: HashMap<String, extension::simple::module::Reference>,
@@ -512,6 +686,13 @@ pub struct State {
     /// URN anchor resolver.
     pub extension_urns: Resolver<u32, extension::simple::module::Reference>,
 
+    /// Legacy extension declarations decoded alongside the plan, if the plan
+    /// still uses URIs to refer to extensions.
+    pub legacy_extensions: Option<Arc<substrait::validator::LegacyPlan>>,
+
+    /// Legacy URI anchor resolver.
+    pub extension_uris: Resolver<u32, extension::simple::module::Reference>,
+
     /// YAML-defined function anchor resolver.
     pub functions: Resolver<u32, extension::simple::function::ResolutionResult>,
 
@@ -536,6 +717,54 @@ pub struct State {
 
     /// Stack for URNs being parsed. Used to detect recursion and limit depth.
     pub urn_stack: Vec<String>,
+
+    /// Set when URN resolution was cut short because of a cyclic dependency
+    /// or the configured recursion limit. The parse tree of the extension
+    /// being parsed then depends on where it was referenced from, so it must
+    /// not be cached in an extension registry.
+    pub urn_resolution_truncated: bool,
+
+    /// Location information for the parts of the tree that were parsed from
+    /// a text format.
+    pub sources: Vec<Arc<source_map::SourceMap>>,
+
+    /// Resources used so far, for enforcing the limits in the configuration.
+    pub budget: Budget,
+
+    /// The number of expressions that the node currently being parsed is
+    /// part of, counting from the root of the relation tree it belongs to.
+    pub expression_depth: usize,
+}
+
+impl State {
+    /// Returns the location of the node with the given path in the textual
+    /// input, if it was parsed from text.
+    pub fn source_span(&self, path: &path::PathBuf) -> Option<span::Span> {
+        self.sources
+            .iter()
+            .filter_map(|source| Some((source.root().elements.len(), source.lookup(path)?)))
+            .max_by_key(|(depth, _)| *depth)
+            .map(|(_, span)| span)
+    }
+}
+
+/// Resource usage tracking for the limits in the configuration.
+#[derive(Default)]
+pub struct Budget {
+    /// The time at which traversal started.
+    pub start: Option<std::time::Instant>,
+
+    /// Depth of the node currently being parsed, the root being at depth 0.
+    pub depth: usize,
+
+    /// Number of nodes created so far.
+    pub node_count: usize,
+
+    /// Total size of the string and binary data encountered so far.
+    pub literal_bytes: usize,
+
+    /// Set when a limit was exceeded or validation was cancelled.
+    pub exceeded: bool,
 }
 
 /// Breadcrumbs structure. Each breadcrumb is associated with a node, and
@@ -580,3 +809,139 @@ impl Breadcrumb<'_> {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::output::parse_result;
+
+    /// Returns the classifications of the diagnostics in the given result.
+    fn classifications(result: &parse_result::ParseResult) -> Vec<diagnostic::Classification> {
+        result
+            .iter_diagnostics()
+            .map(|x| x.cause.classification)
+            .collect()
+    }
+
+    #[test]
+    fn resource_limits() {
+        let json = r#"{
+            "version": { "minorNumber": 87, "producer": "json-test" },
+            "relations": [{ "rel": { "filter": { "input": { "filter": {} } } } }]
+        }"#;
+        let exceeded = |config: &config::Config| {
+            classifications(&crate::parse_json(json, config))
+                .iter()
+                .filter(|x| **x == diagnostic::Classification::ResourceLimitExceeded)
+                .count()
+        };
+
+        let mut config = config::Config::default();
+        assert_eq!(exceeded(&config), 0);
+        config.set_max_tree_depth(Some(4));
+        assert_eq!(exceeded(&config), 1);
+
+        let mut config = config::Config::default();
+        config.set_max_node_count(Some(5));
+        assert_eq!(exceeded(&config), 1);
+
+        let mut config = config::Config::default();
+        config.set_max_literal_bytes(Some(4));
+        assert_eq!(exceeded(&config), 1);
+
+        let mut config = config::Config::default();
+        let token = config::CancellationToken::new();
+        config.set_cancellation_token(token.clone());
+        assert_eq!(exceeded(&config), 0);
+        token.cancel();
+        assert_eq!(exceeded(&config), 1);
+
+        // Limits can also be exceeded by the URN or URI of an extension
+        // declaration, in which case the declaration remains unresolved.
+        let mut config = config::Config::default();
+        config.set_max_literal_bytes(Some(30));
+        for declaration in [
+            r#""extensionUrns": [{
+                "extensionUrnAnchor": 1,
+                "urn": "extension:io.substrait:functions_arithmetic"
+            }]"#,
+            r#""extensionUris": [{
+                "extensionUriAnchor": 1,
+                "uri": "/functions_arithmetic.yaml"
+            }]"#,
+        ] {
+            let json = format!(
+                r#"{{
+                    "version": {{ "minorNumber": 87, "producer": "json-test" }},
+                    {declaration}
+                }}"#
+            );
+            let classes = classifications(&crate::parse_json(&json, &config));
+            assert_eq!(
+                classes
+                    .iter()
+                    .filter(|x| **x == diagnostic::Classification::ResourceLimitExceeded)
+                    .count(),
+                1
+            );
+        }
+    }
+
+    #[test]
+    fn diagnostics_only() {
+        let json = r#"{
+            "version": { "minorNumber": 87, "producer": "json-test" },
+            "relations": [{ "root": {
+                "input": { "read": {
+                    "baseSchema": {
+                        "names": ["a"],
+                        "struct": {
+                            "types": [{ "i32": { "nullability": "NULLABILITY_REQUIRED" } }],
+                            "nullability": "NULLABILITY_REQUIRED"
+                        }
+                    },
+                    "namedTable": { "names": ["t"] }
+                } },
+                "names": ["a"]
+            } }]
+        }"#;
+        let full = crate::parse_json(json, &config::Config::default());
+        let mut config = config::Config::default();
+        config.diagnostics_only();
+        let light = crate::parse_json(json, &config);
+
+        // The diagnostics and data types must be the same, but none of the
+        // documentation should be generated.
+        let diagnostics = |result: &parse_result::ParseResult| {
+            result
+                .iter_diagnostics()
+                .map(|x| format!("{x:#}"))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(diagnostics(&full), diagnostics(&light));
+        let data_types = |result: &parse_result::ParseResult| {
+            result
+                .root
+                .iter_flattened_node_data()
+                .filter_map(|x| match x {
+                    tree::NodeData::DataType(x) => Some(x.to_string()),
+                    _ => None,
+                })
+                .collect::<Vec<_>>()
+        };
+        assert!(!data_types(&full).is_empty());
+        assert_eq!(data_types(&full), data_types(&light));
+        assert!(full
+            .root
+            .iter_flattened_nodes()
+            .any(|x| x.brief.is_some() || x.summary.is_some()));
+        assert!(!light
+            .root
+            .iter_flattened_nodes()
+            .any(|x| x.brief.is_some() || x.summary.is_some()));
+        assert!(!light
+            .root
+            .iter_flattened_node_data()
+            .any(|x| matches!(x, tree::NodeData::Comment(_))));
+    }
+}
@@ -11,11 +11,13 @@ use crate::parse::expressions::literals;
 use crate::parse::types;
 use std::sync::Arc;
 
-// FIXME: what promotions are allowed and when? I saw Isthmus output an
-// if/else with branches differing in nullability, and that makes sense to me
-// as something to support. But on the other hand, explicit type casts for
-// everything might be nicer for a machine format. Either way, I'm not sure
-// the specification has anything to say about this?
+// The specification doesn't say which promotions are allowed between the
+// branches of a conditional. Differing nullability is accepted silently
+// (Isthmus outputs this, and it makes sense to support). Other differences are
+// checked against the promotion lattice of types::promote_and_assert_equal(),
+// which accepts integer widening, decimal unification, and string type
+// unification with a warning that describes the cast that would make the
+// types agree. The options of an or-list must match the value exactly.
 
 /// Parse an if-then expression. Returns a description of said expression.
 pub fn parse_if_then(
@@ -441,15 +441,34 @@ pub fn parse_aggregate_function(
     // verification during the binding!
     proto_repeated_field!(x, y, sorts, sorts::parse_sort_field);
     proto_enum_field!(x, y, phase, substrait::AggregationPhase);
-    proto_enum_field!(
+    let invocation = proto_enum_field!(
         x,
         y,
         invocation,
         substrait::aggregate_function::AggregationInvocation
-    );
+    )
+    .1;
 
     // Try to bind the function.
     let arguments = handle_legacy_arguments(y, arguments, legacy_arguments);
+
+    // Distinct aggregation requires that argument values can be compared
+    // for equality.
+    if invocation == Some(substrait::aggregate_function::AggregationInvocation::Distinct) {
+        for argument in arguments.iter() {
+            if let FunctionArgument::Value(data_type, _) = argument {
+                if !data_type.is_comparable() {
+                    diagnostic!(
+                        y,
+                        Warning,
+                        TypeNotComparable,
+                        "distinct aggregation requires values to be compared \
+                        for equality, but equality is not defined for {data_type}"
+                    );
+                }
+            }
+        }
+    }
     let context = FunctionContext {
         function_type: FunctionType::Aggregate,
         arguments,
@@ -8,6 +8,7 @@ use crate::output::extension;
 use crate::output::type_system::data;
 use crate::parse::context;
 use crate::parse::extensions;
+use crate::parse::profile;
 use crate::parse::types;
 use crate::util;
 use crate::util::string::Describe;
@@ -1302,6 +1303,13 @@ pub fn parse_literal(
     .1
     .unwrap_or_default();
 
+    // Check whether the consumer supports the data type. If the type is
+    // specified using an embedded type message, this was already done while
+    // parsing it.
+    if attributes_here {
+        profile::check_type(y, literal.data_type());
+    }
+
     // Describe node.
     y.set_data_type(literal.data_type().clone());
     describe!(y, Expression, "{}", literal);
@@ -10,9 +10,11 @@ pub mod references;
 pub mod subqueries;
 
 use crate::input::proto::substrait;
+use crate::input::traits::ProtoOneOf;
 use crate::output::diagnostic;
 use crate::output::type_system::data;
 use crate::parse::context;
+use crate::parse::profile;
 use crate::util;
 use crate::util::string::Describe;
 
@@ -159,6 +161,7 @@ fn parse_expression_type(
     y: &mut context::Context,
     enum_allowed: bool,
 ) -> diagnostic::Result<ExpressionOrEnum> {
+    profile::check_expression(y, x.proto_oneof_variant());
     Ok(match x {
         substrait::expression::RexType::Literal(x) => {
             literals::parse_literal(x, y).map(Expression::from)?.into()
@@ -247,12 +250,17 @@ fn parse_expression_internal(
     enum_allowed: bool,
 ) -> diagnostic::Result<ExpressionOrEnum> {
     // Parse the expression.
-    let (n, e) = proto_required_field!(x, y, rex_type, parse_expression_type, enum_allowed);
+    let ((n, e), toplevel) = y.enter_expression(|y| {
+        proto_required_field!(x, y, rex_type, parse_expression_type, enum_allowed)
+    });
     let expression = e.unwrap_or_default();
     let data_type = n.data_type();
 
     // Describe node.
     y.set_data_type(data_type);
+    if toplevel {
+        y.set_expression(expression.to_string());
+    }
     describe!(y, Expression, "{}", expression);
     summary!(y, "Expression: {:#}", expression);
     Ok(expression)
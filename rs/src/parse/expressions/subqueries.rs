@@ -11,6 +11,72 @@ use crate::parse::relations;
 use crate::parse::types;
 use std::sync::Arc;
 
+/// Returns whether the given integer literal expression is known to evaluate
+/// to zero or one.
+fn is_literal_zero_or_one(x: &substrait::Expression) -> bool {
+    use substrait::expression::literal::LiteralType;
+    use substrait::expression::RexType;
+    match &x.rex_type {
+        Some(RexType::Literal(literal)) => match literal.literal_type {
+            Some(LiteralType::I8(x)) | Some(LiteralType::I16(x)) | Some(LiteralType::I32(x)) => {
+                (0..=1).contains(&x)
+            }
+            Some(LiteralType::I64(x)) => (0..=1).contains(&x),
+            _ => false,
+        },
+        _ => false,
+    }
+}
+
+/// Statically determines whether the given relation is guaranteed to yield
+/// at most one row. This is a conservative analysis: false means that the
+/// relation may or may not yield more than one row.
+#[allow(deprecated)]
+fn yields_at_most_one_row(x: &substrait::Rel) -> bool {
+    use substrait::rel::RelType;
+    let input = |x: &Option<Box<substrait::Rel>>| x.as_deref().is_some_and(yields_at_most_one_row);
+    match &x.rel_type {
+        // An aggregate yields one row per grouping set if it has no grouping
+        // expressions, so at most one row if there is at most one grouping
+        // set.
+        Some(RelType::Aggregate(x)) => {
+            x.groupings.len() <= 1
+                && x.groupings.iter().all(|grouping| {
+                    grouping.grouping_expressions.is_empty()
+                        && grouping.expression_references.is_empty()
+                })
+        }
+
+        // A fetch relation limits its input to the given number of rows.
+        Some(RelType::Fetch(x)) => {
+            use substrait::fetch_rel::CountMode;
+            match &x.count_mode {
+                Some(CountMode::Count(count)) if (0..=1).contains(count) => true,
+                Some(CountMode::CountExpr(count)) if is_literal_zero_or_one(count) => true,
+                _ => input(&x.input),
+            }
+        }
+
+        // A virtual table yields the rows it contains.
+        Some(RelType::Read(x)) => match &x.read_type {
+            Some(substrait::read_rel::ReadType::VirtualTable(table)) => {
+                table.values.len() + table.expressions.len() <= 1
+            }
+            _ => false,
+        },
+
+        // These relations never yield more rows than their input.
+        Some(RelType::Filter(x)) => input(&x.input),
+        Some(RelType::Sort(x)) => input(&x.input),
+        Some(RelType::Project(x)) => input(&x.input),
+
+        // A cross product of two single-row inputs yields a single row.
+        Some(RelType::Cross(x)) => input(&x.left) && input(&x.right),
+
+        _ => false,
+    }
+}
+
 /// Parse a scalar subquery.
 fn parse_scalar(
     x: &substrait::expression::subquery::Scalar,
@@ -39,16 +105,36 @@ fn parse_scalar(
         Arc::default()
     };
 
-    // FIXME: what is the behavior when the query doesn't yield one row? Should
-    // the returned data type be made nullable?
+    // Scalar subqueries that don't yield any rows return null, so the return
+    // type is always nullable. Subqueries that yield more than one row are
+    // invalid at runtime; we can't check that in general, but we can point
+    // out when we can't prove that it won't happen.
+    let return_type = if return_type.is_unresolved() {
+        return_type
+    } else {
+        return_type.make_nullable()
+    };
+    if let Some(input) = &x.input {
+        if !yields_at_most_one_row(input) {
+            diagnostic!(
+                y,
+                Info,
+                ExpressionSubqueryCardinality,
+                "could not prove that the subquery returns at most one row; \
+                consider using an aggregate relation without groupings or a \
+                fetch relation with a count of one"
+            );
+        }
+    }
 
     // Describe node.
     y.set_data_type(return_type);
     summary!(
         y,
         "Executes the contained subquery for each row. The query is expected \
-        to return a single row and column, the value of which is returned by \
-        the expression."
+        to return at most one row and exactly one column. The value of this \
+        column is returned by the expression, or null if the subquery does \
+        not return any rows."
     );
     let expression = expressions::Expression::BigFunction(String::from("scalar_subquery"));
     describe!(y, Expression, "{}", expression);
@@ -137,6 +137,22 @@ impl<'a> AnalysisContext<'a> {
         object
     }
 
+    /// Resolve the name of a user-defined type class, as used with the `u!`
+    /// prefix. Unlike for resolve_pattern(), the name must refer to a type
+    /// class, so a diagnostic is emitted if it cannot be resolved.
+    fn resolve_user_defined_class(&mut self, name: &str, y: &mut context::Context) -> data::Class {
+        if let PatternObject::TypeClass(class) = self.resolve_pattern(std::iter::once(name), y) {
+            return class;
+        }
+        if let Some(scope) = self.scope {
+            scope
+                .resolve_type_class_from_ref(name.into())
+                .filter_all_items()
+                .expect_one(y, |_, _| false, |_, _| false);
+        }
+        data::Class::Unresolved
+    }
+
     /// Resolve a type variation identifier path.
     pub fn resolve_type_variation<S, I>(
         &mut self,
@@ -791,13 +807,7 @@ fn analyze_parameterized_type(
             let nullable = c.QMark().is_some();
             let parameters = expr_parameters(c.expr_all(), y, z);
             let class = match c.Identifier() {
-                Some(id) => {
-                    let name = id.symbol.text.to_string();
-                    match z.resolve_pattern(std::iter::once(name.as_str()), y) {
-                        PatternObject::TypeClass(class) => class,
-                        _ => data::Class::Unresolved,
-                    }
-                }
+                Some(id) => z.resolve_user_defined_class(&id.symbol.text.to_string(), y),
                 None => data::Class::Unresolved,
             };
             (
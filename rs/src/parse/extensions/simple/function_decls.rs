@@ -5,25 +5,505 @@
 
 use crate::input::yaml;
 use crate::output::diagnostic::Result;
+use crate::output::extension::simple::function;
+use crate::output::type_system::meta;
 use crate::parse::context;
 use crate::parse::extensions::simple::builder;
+use crate::parse::extensions::simple::derivations;
+use crate::util;
+use std::collections::HashMap;
+use std::sync::Arc;
 
-/// Parse a scalar function declaration.
-pub fn parse_scalar_function(
-    _x: &yaml::Value,
+/// Name of the inconsistent binding used to capture the nullability of the
+/// arguments of functions with MIRROR nullability handling. The exclamation
+/// mark ensures that it cannot conflict with bindings named in the YAML file.
+const MIRROR_BINDING: &str = "!nullable";
+
+/// Parse a type pattern given as a string. The object syntax for named
+/// structs is not supported yet.
+fn parse_pattern(
+    x: &yaml::Value,
+    y: &mut context::Context,
+    z: &mut derivations::AnalysisContext,
+) -> Result<meta::pattern::Value> {
+    match x {
+        yaml::Value::String(x) => derivations::parse_pattern(x, y, z),
+        yaml::Value::Object(_) => {
+            diagnostic!(
+                y,
+                Warning,
+                NotYetImplemented,
+                "named struct syntactic sugar for type patterns"
+            );
+            Ok(meta::pattern::Value::Unresolved)
+        }
+        _ => Err(cause!(YamlInvalidType, "string or object expected")),
+    }
+}
+
+/// Parse a type derivation program given as a string. The object syntax for
+/// named structs is not supported yet.
+fn parse_program(
+    x: &yaml::Value,
+    y: &mut context::Context,
+    z: &mut derivations::AnalysisContext,
+) -> Result<meta::Program> {
+    match x {
+        yaml::Value::String(x) => derivations::parse_program(x, y, z),
+        yaml::Value::Object(_) => {
+            diagnostic!(
+                y,
+                Warning,
+                NotYetImplemented,
+                "named struct syntactic sugar for type derivations"
+            );
+            Ok(meta::Program::default())
+        }
+        _ => Err(cause!(YamlInvalidType, "string or object expected")),
+    }
+}
+
+/// Parse an enumeration option or function option value.
+fn parse_option_value(x: &str, y: &mut context::Context) -> Result<String> {
+    if !util::string::is_identifier(x) {
+        diagnostic!(
+            y,
+            Error,
+            IllegalValue,
+            "option {x:?} is not a valid identifier"
+        );
+    }
+    Ok(x.to_string())
+}
+
+/// Parse a function argument slot declaration.
+fn parse_argument(
+    x: &yaml::Value,
+    y: &mut context::Context,
+    z: &mut derivations::AnalysisContext,
+) -> Result<function::ArgumentSlot> {
+    let object = x
+        .as_object()
+        .ok_or_else(|| cause!(YamlInvalidType, "object expected"))?;
+    let name = yaml_field!(x, y, "name", yaml_prim!(str))?.1;
+    let description = yaml_field!(x, y, "description", yaml_prim!(str))?.1;
+    let argument_type = if object.contains_key("value") {
+        let pattern = yaml_required_field!(x, y, "value", parse_pattern, z)?
+            .1
+            .unwrap_or_default();
+        let constant = yaml_field!(x, y, "constant", yaml_prim!(bool))?
+            .1
+            .unwrap_or_default();
+        function::ArgumentSlotType::Value(function::ValueArgumentSlot { pattern, constant })
+    } else if object.contains_key("type") {
+        let pattern = yaml_required_field!(
+            x,
+            y,
+            "type",
+            yaml_prim!(str, |x, y| derivations::parse_pattern(x, y, z))
+        )?
+        .1
+        .unwrap_or_default();
+        function::ArgumentSlotType::Type(function::TypeArgumentSlot { pattern })
+    } else if object.contains_key("options") {
+        let options =
+            yaml_required_repeated_field!(x, y, "options", yaml_prim!(str, parse_option_value))?
+                .1
+                .into_iter()
+                .flatten()
+                .collect();
+        function::ArgumentSlotType::Enumeration(function::EnumerationArgumentSlot {
+            options,
+            required: true,
+        })
+    } else {
+        return Err(cause!(
+            YamlMissingKey,
+            "one of value, type, or options must be specified"
+        ));
+    };
+    Ok(function::ArgumentSlot {
+        name,
+        description,
+        argument_type,
+    })
+}
+
+/// Parse the option declarations of a function implementation.
+fn parse_options(
+    x: &yaml::Value,
+    y: &mut context::Context,
+) -> Result<HashMap<function::OptionName, function::OptionValues>> {
+    if let yaml::Value::Object(object) = x {
+        let mut names: Vec<&String> = object.keys().collect();
+        names.sort();
+        let mut options = HashMap::new();
+        for name in names {
+            let (_, values) = yaml_field!(x, y, name.as_str(), |x, y| {
+                yaml_field!(x, y, "description", yaml_prim!(str))?;
+                Ok(yaml_required_repeated_field!(
+                    x,
+                    y,
+                    "values",
+                    yaml_prim!(str, parse_option_value),
+                    0
+                )?
+                .1
+                .into_iter()
+                .flatten()
+                .collect::<Vec<_>>())
+            })?;
+            options.insert(
+                function::OptionName { name: name.clone() },
+                function::OptionValues {
+                    values: values.unwrap_or_default(),
+                },
+            );
+        }
+        Ok(options)
+    } else {
+        Err(cause!(YamlInvalidType, "object expected"))
+    }
+}
+
+/// Parse the parameter consistency of a variadic function.
+fn parse_parameter_consistency(
+    x: &str,
+    _y: &mut context::Context,
+) -> Result<function::ParameterConsistency> {
+    match x {
+        "CONSISTENT" => Ok(function::ParameterConsistency::Consistent),
+        "INCONSISTENT" => Ok(function::ParameterConsistency::Inconsistent),
+        _ => Err(cause!(
+            IllegalValue,
+            "expected CONSISTENT or INCONSISTENT, but found {x}"
+        )),
+    }
+}
+
+/// Parse the variadic behavior of a function implementation.
+fn parse_variadic_behavior(
+    x: &yaml::Value,
+    y: &mut context::Context,
+) -> Result<function::VariadicBehavior> {
+    let min = yaml_field!(x, y, "min", yaml_prim!(u64))?
+        .1
+        .unwrap_or_default() as usize;
+    let max = yaml_field!(x, y, "max", yaml_prim!(u64))?
+        .1
+        .map(|max| max as usize)
+        .unwrap_or(usize::MAX);
+    if min > max {
+        diagnostic!(
+            y,
+            Error,
+            IllegalValue,
+            "minimum number of arguments {min} is greater than maximum {max}"
+        );
+    }
+    let parameter_consistency = yaml_field!(
+        x,
+        y,
+        "parameterConsistency",
+        yaml_prim!(str, parse_parameter_consistency)
+    )?
+    .1
+    .unwrap_or(function::ParameterConsistency::Consistent);
+    Ok(function::VariadicBehavior {
+        parameter_consistency,
+        min,
+        max,
+    })
+}
+
+/// Parse the nullability handling of a function implementation.
+fn parse_nullability_handling(
+    x: &str,
+    _y: &mut context::Context,
+) -> Result<function::NullabilityHandling> {
+    match x {
+        "MIRROR" => Ok(function::NullabilityHandling::Mirror),
+        "DECLARED_OUTPUT" => Ok(function::NullabilityHandling::DeclaredOutput),
+        "DISCRETE" => Ok(function::NullabilityHandling::Discrete),
+        _ => Err(cause!(
+            IllegalValue,
+            "expected MIRROR, DECLARED_OUTPUT, or DISCRETE, but found {x}"
+        )),
+    }
+}
+
+/// Parse the decomposability of an aggregate function implementation.
+fn parse_decomposable(x: &str, _y: &mut context::Context) -> Result<()> {
+    match x {
+        "NONE" | "ONE" | "MANY" => Ok(()),
+        _ => Err(cause!(
+            IllegalValue,
+            "expected NONE, ONE, or MANY, but found {x}"
+        )),
+    }
+}
+
+/// Parse the implementation map of a function implementation.
+fn parse_implementations(
+    x: &yaml::Value,
     _y: &mut context::Context,
-    _z: &mut builder::Builder,
+) -> Result<HashMap<String, yaml::Value>> {
+    if let yaml::Value::Object(object) = x {
+        Ok(object
+            .iter()
+            .map(|(key, value)| (key.clone(), value.clone()))
+            .collect())
+    } else {
+        Err(cause!(YamlInvalidType, "object expected"))
+    }
+}
+
+/// Converts all bindings in the given pattern to inconsistent bindings, as
+/// specified by the INCONSISTENT parameter consistency.
+fn make_bindings_inconsistent(pattern: &mut meta::pattern::Value) {
+    match pattern {
+        meta::pattern::Value::Binding(binding) => binding.inconsistent = true,
+        meta::pattern::Value::DataType(data_type) => {
+            for parameter in data_type.parameters.iter_mut().flatten() {
+                if let Some(value) = &mut parameter.value {
+                    make_bindings_inconsistent(value);
+                }
+            }
+        }
+        _ => (),
+    }
+}
+
+/// Overrides the top-level nullability of the given pattern with the given
+/// nullability pattern.
+fn override_nullability(
+    pattern: &mut meta::pattern::Value,
+    nullability: &Arc<meta::pattern::Value>,
+) {
+    match pattern {
+        meta::pattern::Value::Binding(binding) => {
+            binding.nullability = Some(nullability.clone());
+        }
+        meta::pattern::Value::DataType(data_type) => {
+            data_type.nullable = nullability.clone();
+        }
+        _ => (),
+    }
+}
+
+/// Returns the name used for an argument slot in the compound name of a
+/// function.
+fn signature_name(slot: &function::ArgumentSlot) -> String {
+    let pattern = match &slot.argument_type {
+        function::ArgumentSlotType::Value(slot) => &slot.pattern,
+        function::ArgumentSlotType::Type(slot) => &slot.pattern,
+        function::ArgumentSlotType::Enumeration(_) => return String::from("req"),
+    };
+    match pattern {
+        meta::pattern::Value::DataType(data_type) => data_type
+            .class
+            .as_ref()
+            .and_then(|class| class.signature_name())
+            .unwrap_or_else(|| String::from("any")),
+        meta::pattern::Value::Binding(binding) => {
+            let name = binding.name.to_ascii_lowercase();
+            if name.starts_with("any") {
+                name
+            } else {
+                String::from("any")
+            }
+        }
+        _ => String::from("any"),
+    }
+}
+
+/// Parse a function implementation, and register it in the builder under
+/// both its simple and compound name.
+fn parse_implementation(
+    x: &yaml::Value,
+    y: &mut context::Context,
+    z: &mut builder::Builder,
+    common: &Arc<function::Common>,
 ) -> Result<()> {
-    // TODO
+    crate::parse::traversal::push_yaml_field(x, y, "deprecated", true, |_, _| Ok(()))?;
+    yaml_field!(x, y, "description", yaml_prim!(str))?;
+
+    // Parse the argument patterns and type derivations. These must all be
+    // analyzed using the same analysis context, such that bindings are shared.
+    let (mut arguments, mut return_type) = {
+        let mut analysis_context = derivations::AnalysisContext::new(Some(&*z));
+        let arguments =
+            yaml_repeated_field!(x, y, "args", parse_argument, 0, &mut analysis_context)?
+                .1
+                .into_iter()
+                .map(|argument| {
+                    argument.unwrap_or(function::ArgumentSlot {
+                        name: None,
+                        description: None,
+                        argument_type: function::ArgumentSlotType::Value(
+                            function::ValueArgumentSlot {
+                                pattern: meta::pattern::Value::Unresolved,
+                                constant: false,
+                            },
+                        ),
+                    })
+                })
+                .collect::<Vec<_>>();
+        if common.function_type == function::Type::Aggregate {
+            yaml_field!(x, y, "intermediate", parse_program, &mut analysis_context)?;
+        }
+        let return_type =
+            yaml_required_field!(x, y, "return", parse_program, &mut analysis_context)?
+                .1
+                .unwrap_or_default();
+        (arguments, return_type)
+    };
+
+    // Parse the remaining properties.
+    let options = yaml_field!(x, y, "options", parse_options)?
+        .1
+        .unwrap_or_default();
+    let variadic = yaml_field!(x, y, "variadic", parse_variadic_behavior)?.1;
+    if variadic.is_some() && arguments.is_empty() {
+        diagnostic!(
+            y,
+            Error,
+            IllegalValue,
+            "variadic functions must have at least one argument"
+        );
+    }
+    let variadic = variadic.unwrap_or(function::VariadicBehavior {
+        parameter_consistency: function::ParameterConsistency::Consistent,
+        min: 1,
+        max: 1,
+    });
+    let session_dependent = yaml_field!(x, y, "sessionDependent", yaml_prim!(bool))?
+        .1
+        .unwrap_or(false);
+    let deterministic = yaml_field!(x, y, "deterministic", yaml_prim!(bool))?
+        .1
+        .unwrap_or(true);
+    let nullability_handling = yaml_field!(
+        x,
+        y,
+        "nullability",
+        yaml_prim!(str, parse_nullability_handling)
+    )?
+    .1
+    .unwrap_or(function::NullabilityHandling::Mirror);
+    let implementations = yaml_field!(x, y, "implementation", parse_implementations)?
+        .1
+        .unwrap_or_default();
+    if common.function_type == function::Type::Aggregate {
+        yaml_field!(x, y, "decomposable", yaml_prim!(str, parse_decomposable))?;
+        yaml_field!(x, y, "maxset", yaml_prim!(f64))?;
+        yaml_field!(x, y, "ordered", yaml_prim!(bool))?;
+    }
+
+    // Capture the semantics of the parameter consistency and nullability
+    // handling in the patterns.
+    if variadic.parameter_consistency == function::ParameterConsistency::Inconsistent {
+        if let Some(slot) = arguments.last_mut() {
+            if let function::ArgumentSlotType::Value(slot) = &mut slot.argument_type {
+                make_bindings_inconsistent(&mut slot.pattern);
+            }
+        }
+    }
+    let argument_nullability = match nullability_handling {
+        function::NullabilityHandling::Mirror => {
+            let mirror = Arc::new(meta::pattern::Value::Binding(meta::pattern::Binding {
+                name: String::from(MIRROR_BINDING),
+                inconsistent: true,
+                nullability: None,
+            }));
+            override_nullability(&mut return_type.expression, &mirror);
+            Some(mirror)
+        }
+        function::NullabilityHandling::DeclaredOutput => Some(Arc::new(meta::pattern::Value::Any)),
+        function::NullabilityHandling::Discrete => None,
+    };
+    if let Some(nullability) = argument_nullability {
+        for slot in arguments.iter_mut() {
+            if let function::ArgumentSlotType::Value(slot) = &mut slot.argument_type {
+                override_nullability(&mut slot.pattern, &nullability);
+            }
+        }
+    }
+
+    // Derive the compound name, and register the implementation.
+    let compound_name = format!(
+        "{}:{}",
+        common.name,
+        arguments
+            .iter()
+            .map(signature_name)
+            .collect::<Vec<_>>()
+            .join("_")
+    );
+    if let Err(e) = z
+        .function_impls
+        .resolve_local(compound_name.as_str())
+        .expect_not_yet_defined()
+    {
+        diagnostic!(y, Error, e);
+        return Ok(());
+    }
+    let definition = Arc::new(function::Definition {
+        extension_id: z.extension_id,
+        common: common.clone(),
+        compound_name: compound_name.clone(),
+        arguments,
+        options,
+        variadic,
+        session_dependent,
+        deterministic,
+        nullability_handling,
+        return_type,
+        implementations,
+    });
+    z.function_impls
+        .define_item(&common.name, definition.clone(), true);
+    z.function_impls
+        .define_item(compound_name, definition, true);
     Ok(())
 }
 
+/// Parse a function declaration of the given type.
+fn parse_function(
+    x: &yaml::Value,
+    y: &mut context::Context,
+    z: &mut builder::Builder,
+    function_type: function::Type,
+) -> Result<()> {
+    let name = yaml_required_field!(x, y, "name", yaml_prim!(str))?
+        .1
+        .unwrap_or_default();
+    let description = yaml_field!(x, y, "description", yaml_prim!(str))?.1;
+    crate::parse::traversal::push_yaml_field(x, y, "deprecated", true, |_, _| Ok(()))?;
+    crate::parse::traversal::push_yaml_field(x, y, "metadata", true, |_, _| Ok(()))?;
+    let common = Arc::new(function::Common {
+        name,
+        description,
+        function_type,
+    });
+    yaml_required_repeated_field!(x, y, "impls", parse_implementation, 1, z, &common)?;
+    Ok(())
+}
+
+/// Parse a scalar function declaration.
+pub fn parse_scalar_function(
+    x: &yaml::Value,
+    y: &mut context::Context,
+    z: &mut builder::Builder,
+) -> Result<()> {
+    parse_function(x, y, z, function::Type::Scalar)
+}
+
 /// Parse an aggregate function declaration.
 pub fn parse_aggregate_function(
-    _x: &yaml::Value,
-    _y: &mut context::Context,
-    _z: &mut builder::Builder,
+    x: &yaml::Value,
+    y: &mut context::Context,
+    z: &mut builder::Builder,
 ) -> Result<()> {
-    // TODO
-    Ok(())
+    parse_function(x, y, z, function::Type::Aggregate)
 }
@@ -3,12 +3,16 @@
 //! Module providing parse/validation functions for advanced extensions, i.e.
 //! those based around YAML files.
 
+use crate::input::legacy_uris;
 use crate::input::proto::substrait;
 use crate::output::diagnostic::Result;
 use crate::output::extension;
 use crate::output::extension::simple::module::Scope;
 use crate::output::type_system::data;
 use crate::parse::context;
+use crate::parse::profile;
+use crate::parse::traversal;
+use std::sync::Arc;
 
 mod builder;
 mod derivations;
@@ -18,6 +22,8 @@ mod type_decls;
 mod type_variation_decls;
 mod yaml;
 
+pub use yaml::parse_file;
+
 /// Parse a user-defined name. Note that names are matched case-insensitively
 /// because we return the name as lowercase.
 #[allow(clippy::ptr_arg)]
@@ -54,7 +60,7 @@ fn parse_simple_extension_yaml_urn_mapping(
     let anchor = proto_primitive_field!(x, y, extension_urn_anchor, parse_anchor).1;
     let yaml_data = proto_primitive_field!(x, y, urn, yaml::parse_urn)
         .1
-        .unwrap();
+        .unwrap_or_else(unresolved_module);
 
     // If the specified anchor is valid, insert a mapping for it.
     if let Some(anchor) = anchor {
@@ -73,6 +79,134 @@ fn parse_simple_extension_yaml_urn_mapping(
     Ok(())
 }
 
+/// Parse a legacy extension URI. URIs are mapped to the URN of the extension
+/// they refer to using the configuration, after which the URN is resolved as
+/// usual.
+#[allow(clippy::ptr_arg)]
+fn parse_uri(x: &String, y: &mut context::Context) -> Result<extension::simple::module::Reference> {
+    diagnostic!(
+        y,
+        Warning,
+        Deprecation,
+        "extension URIs are deprecated; extensions should be referred to \
+        by URN instead"
+    );
+    let config = y.config;
+    let urn = config
+        .uri_urn_mappings
+        .get(x)
+        .map(String::as_str)
+        .or_else(|| legacy_uris::lookup(x));
+    if let Some(urn) = urn {
+        comment!(y, "Mapped to URN {urn}.");
+        yaml::parse_urn(urn, y)
+    } else {
+        diagnostic!(
+            y,
+            Warning,
+            YamlResolutionFailed,
+            "extension URI {x} is not mapped to a URN"
+        );
+        Ok(unresolved_module())
+    }
+}
+
+/// Returns a reference to an extension that could not be resolved, used in
+/// place of the result of parsing a URN or URI when that failed or was
+/// skipped.
+fn unresolved_module() -> extension::simple::module::Reference {
+    Arc::new(extension::reference::Data {
+        name: Default::default(),
+        urn: Default::default(),
+        definition: None,
+    })
+}
+
+/// Parse a legacy mapping from a URI anchor to a YAML extension.
+fn parse_simple_extension_yaml_uri_mapping(
+    x: &substrait::validator::LegacyExtensionUri,
+    y: &mut context::Context,
+) -> Result<()> {
+    // Parse the fields.
+    let anchor = proto_primitive_field!(x, y, extension_uri_anchor, parse_anchor).1;
+    let yaml_data = proto_primitive_field!(x, y, uri, parse_uri)
+        .1
+        .unwrap_or_else(unresolved_module);
+
+    // If the specified anchor is valid, insert a mapping for it.
+    if let Some(anchor) = anchor {
+        if let Err((prev_data, prev_path)) = y.define_extension_uri(anchor, yaml_data) {
+            diagnostic!(
+                y,
+                Error,
+                IllegalValue,
+                "anchor {anchor} is already in use for extension {}",
+                prev_data.urn
+            );
+            link!(y, prev_path, "Previous definition was here.");
+        }
+    }
+
+    Ok(())
+}
+
+/// Parse a legacy URI reference and resolve it.
+fn parse_uri_reference(
+    x: &u32,
+    y: &mut context::Context,
+) -> Result<extension::simple::module::Reference> {
+    match y.extension_uris().resolve(x).cloned() {
+        Some((yaml_data, path)) => {
+            describe!(y, Misc, "{}", yaml_data.urn);
+            link!(y, path, "URI anchor is defined here");
+            Ok(yaml_data)
+        }
+        None => {
+            describe!(y, Misc, "Unresolved URI");
+            Err(cause!(LinkMissingAnchor, "URI anchor {x} does not exist"))
+        }
+    }
+}
+
+/// Parse the reference to the extension that a declaration refers to. The
+/// URN reference takes precedence; if it is not set, the legacy URI
+/// reference decoded alongside the plan is used instead, if any.
+fn parse_module_reference(
+    urn_reference: &u32,
+    legacy_uri_reference: Option<u32>,
+    y: &mut context::Context,
+) -> Option<extension::simple::module::Reference> {
+    match legacy_uri_reference {
+        Some(uri_reference) if *urn_reference == 0 => {
+            traversal::push_proto_required_field(
+                y,
+                &Some(urn_reference),
+                "extension_urn_reference",
+                false,
+                |_, _| Ok(()),
+            );
+            traversal::push_proto_required_field(
+                y,
+                &Some(&uri_reference),
+                "extension_uri_reference",
+                false,
+                parse_uri_reference,
+            )
+            .1
+        }
+        _ => {
+            traversal::push_proto_required_field(
+                y,
+                &Some(urn_reference),
+                "extension_urn_reference",
+                false,
+                parse_urn_reference,
+            )
+            .1
+        }
+    }
+}
+
 /// Parse an URN reference and resolve it.
 fn parse_urn_reference(
     x: &u32,
@@ -206,6 +340,7 @@ pub fn parse_function_reference(
         Some((function, path)) => {
             describe!(y, Misc, "{}", &function);
             link!(y, path, "Function anchor is defined here");
+            profile::check_function(y, &function.as_item());
             Ok(function)
         }
         None => {
@@ -222,12 +357,13 @@ pub fn parse_function_reference(
 fn parse_extension_mapping_data(
     x: &substrait::extensions::simple_extension_declaration::MappingType,
     y: &mut context::Context,
+    legacy_uri_reference: Option<u32>,
 ) -> Result<()> {
     match x {
         substrait::extensions::simple_extension_declaration::MappingType::ExtensionType(x) => {
 
             // Parse the fields.
-            let module_ref_opt = proto_primitive_field!(x, y, extension_urn_reference, parse_urn_reference).1;
+            let module_ref_opt = parse_module_reference(&x.extension_urn_reference, legacy_uri_reference, y);
             let anchor = proto_primitive_field!(x, y, type_anchor, parse_anchor).1;
             let name = proto_primitive_field!(x, y, name, parse_name).1;
 
@@ -271,7 +407,7 @@ fn parse_extension_mapping_data(
         substrait::extensions::simple_extension_declaration::MappingType::ExtensionTypeVariation(x) => {
 
             // Parse the fields.
-            let module_ref_opt = proto_primitive_field!(x, y, extension_urn_reference, parse_urn_reference).1;
+            let module_ref_opt = parse_module_reference(&x.extension_urn_reference, legacy_uri_reference, y);
             let anchor = proto_primitive_field!(x, y, type_variation_anchor, parse_anchor).1;
             let name = proto_primitive_field!(x, y, name, parse_name).1;
 
@@ -318,7 +454,7 @@ fn parse_extension_mapping_data(
         substrait::extensions::simple_extension_declaration::MappingType::ExtensionFunction(x) => {
 
             // Parse the fields.
-            let module_ref_opt = proto_primitive_field!(x, y, extension_urn_reference, parse_urn_reference).1;
+            let module_ref_opt = parse_module_reference(&x.extension_urn_reference, legacy_uri_reference, y);
             let anchor = proto_primitive_field!(x, y, function_anchor, parse_anchor).1;
             let name = proto_primitive_field!(x, y, name).1;
 
@@ -386,12 +522,36 @@ fn parse_extension_mapping_data(
     Ok(())
 }
 
+/// Returns the legacy URI reference of the given legacy extension
+/// declaration, if it is set.
+pub fn legacy_uri_reference(x: &substrait::validator::LegacyExtensionDeclaration) -> Option<u32> {
+    use substrait::validator::legacy_extension_declaration::MappingType;
+    x.mapping_type
+        .as_ref()
+        .map(|x| match x {
+            MappingType::ExtensionType(x)
+            | MappingType::ExtensionTypeVariation(x)
+            | MappingType::ExtensionFunction(x) => x.extension_uri_reference,
+        })
+        .filter(|x| *x != 0)
+}
+
 /// Parse a mapping from a function/type/variation anchor to an extension.
+/// legacy is the corresponding legacy declaration decoded alongside the plan,
+/// if any.
 fn parse_extension_mapping(
     x: &substrait::extensions::SimpleExtensionDeclaration,
     y: &mut context::Context,
+    legacy: Option<&substrait::validator::LegacyExtensionDeclaration>,
 ) -> Result<()> {
-    proto_required_field!(x, y, mapping_type, parse_extension_mapping_data);
+    let legacy_uri_reference = legacy.and_then(legacy_uri_reference);
+    proto_required_field!(
+        x,
+        y,
+        mapping_type,
+        parse_extension_mapping_data,
+        legacy_uri_reference
+    );
     Ok(())
 }
 
@@ -403,7 +563,28 @@ pub fn parse_plan(x: &substrait::Plan, y: &mut context::Context) {
         extension_urns,
         parse_simple_extension_yaml_urn_mapping
     );
-    proto_repeated_field!(x, y, extensions, parse_extension_mapping);
+
+    // Plans from older producers may still refer to extensions by URI. These
+    // fields no longer exist in the plan message, so they are decoded
+    // separately.
+    let legacy = y.legacy_extensions().cloned();
+    if let Some(legacy) = &legacy {
+        traversal::push_proto_repeated_field(
+            y,
+            &legacy.extension_uris,
+            "extension_uris",
+            false,
+            parse_simple_extension_yaml_uri_mapping,
+            |_, _, _, _, _| (),
+        );
+    }
+
+    let mut legacy_declarations = legacy.iter().flat_map(|x| x.extensions.iter());
+    proto_repeated_field!(x, y, extensions, |x, y| parse_extension_mapping(
+        x,
+        y,
+        legacy_declarations.next()
+    ));
 }
 
 /// Generate Info diagnostics for any extension definitions that weren't used.
@@ -451,3 +632,118 @@ pub fn check_unused_definitions(y: &mut context::Context) {
         link!(y, path, "Declaration was here.");
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::input::config;
+    use crate::input::proto;
+    use crate::output::diagnostic;
+    use crate::output::parse_result;
+    use prost::Message;
+
+    /// Returns the classifications of the diagnostics in the given result.
+    fn classifications(result: &parse_result::ParseResult) -> Vec<diagnostic::Classification> {
+        result
+            .iter_diagnostics()
+            .map(|x| x.cause.classification)
+            .collect()
+    }
+
+    #[test]
+    fn legacy_extension_uris_json() {
+        let config = config::Config::default();
+        let json = r#"{
+            "version": { "minorNumber": 87, "producer": "json-test" },
+            "extensionUris": [{
+                "extensionUriAnchor": 1,
+                "uri": "https://github.com/substrait-io/substrait/blob/main/extensions/functions_arithmetic.yaml"
+            }],
+            "extensions": [{
+                "extensionFunction": {
+                    "extensionUriReference": 1,
+                    "functionAnchor": 1,
+                    "name": "add"
+                }
+            }],
+            "relations": []
+        }"#;
+        let result = crate::parse_json(json, &config);
+        let classes = classifications(&result);
+        assert!(classes.contains(&diagnostic::Classification::Deprecation));
+        assert!(!classes.contains(&diagnostic::Classification::ProtoParseFailed));
+        assert!(!classes.contains(&diagnostic::Classification::LinkMissingAnchor));
+        assert!(!classes.contains(&diagnostic::Classification::YamlResolutionFailed));
+
+        // References to URI anchors that don't exist must still be caught,
+        // and URIs without a mapping must be reported.
+        let json = json
+            .replace(
+                r#""extensionUriReference": 1"#,
+                r#""extensionUriReference": 2"#,
+            )
+            .replace("functions_arithmetic.yaml", "unknown_functions.yaml");
+        let classes = classifications(&crate::parse_json(&json, &config));
+        assert!(classes.contains(&diagnostic::Classification::LinkMissingAnchor));
+        assert!(classes.contains(&diagnostic::Classification::YamlResolutionFailed));
+    }
+
+    #[test]
+    fn legacy_extension_uris_binary() {
+        let mut config = config::Config::default();
+        config.map_uri_to_urn(
+            "/custom.yaml",
+            "extension:io.substrait:functions_arithmetic",
+        );
+
+        // Plans from older producers can't be constructed using the current
+        // bindings, so the legacy fields are encoded separately and merged
+        // into the declaration and the plan by concatenation.
+        let mut declaration = proto::substrait::validator::LegacyExtensionDeclaration {
+            mapping_type: Some(
+                proto::substrait::validator::legacy_extension_declaration::MappingType::ExtensionFunction(
+                    proto::substrait::validator::legacy_extension_declaration::Mapping {
+                        extension_uri_reference: 1,
+                    },
+                ),
+            ),
+        }
+        .encode_to_vec();
+        proto::substrait::extensions::SimpleExtensionDeclaration {
+            mapping_type: Some(
+                proto::substrait::extensions::simple_extension_declaration::MappingType::ExtensionFunction(
+                    proto::substrait::extensions::simple_extension_declaration::ExtensionFunction {
+                        function_anchor: 1,
+                        name: String::from("add"),
+                        ..Default::default()
+                    },
+                ),
+            ),
+        }
+        .encode(&mut declaration)
+        .unwrap();
+        let mut binary = proto::substrait::Plan {
+            version: Some(proto::substrait::Version {
+                minor_number: 87,
+                producer: String::from("binary-test"),
+                ..Default::default()
+            }),
+            ..Default::default()
+        }
+        .encode_to_vec();
+        proto::substrait::validator::LegacyPlan {
+            extension_uris: vec![proto::substrait::validator::LegacyExtensionUri {
+                extension_uri_anchor: 1,
+                uri: String::from("/custom.yaml"),
+            }],
+            ..Default::default()
+        }
+        .encode(&mut binary)
+        .unwrap();
+        prost::encoding::bytes::encode(2, &declaration, &mut binary);
+
+        let classes = classifications(&crate::parse(binary.as_slice(), &config));
+        assert!(classes.contains(&diagnostic::Classification::Deprecation));
+        assert!(!classes.contains(&diagnostic::Classification::LinkMissingAnchor));
+        assert!(!classes.contains(&diagnostic::Classification::YamlResolutionFailed));
+    }
+}
@@ -5,15 +5,244 @@
 
 use crate::input::yaml;
 use crate::output::diagnostic::Result;
+use crate::output::extension;
+use crate::output::type_system::data;
+use crate::output::type_system::meta;
 use crate::parse::context;
 use crate::parse::extensions::simple::builder;
+use crate::parse::extensions::simple::derivations;
+use crate::util;
+use std::sync::Arc;
+
+/// Parse a structure field type, returning the simple type class it refers
+/// to, if any.
+fn parse_structure_field_type(
+    x: &str,
+    y: &mut context::Context,
+    z: &mut derivations::AnalysisContext,
+) -> Result<Option<data::class::Simple>> {
+    let data_type = derivations::parse_type(x, y, z)?;
+    match data_type.class() {
+        data::Class::Simple(simple) => Ok(Some(*simple)),
+        data::Class::Unresolved => Ok(None),
+        class => {
+            diagnostic!(
+                y,
+                Info,
+                NotYetImplemented,
+                "structure fields of type class {class} are not supported; \
+                only simple type classes are"
+            );
+            Ok(None)
+        }
+    }
+}
+
+/// Parse the structure of a type, which may either be a string describing
+/// the type or an object describing a (non-nullable) named struct.
+fn parse_structure(
+    x: &yaml::Value,
+    y: &mut context::Context,
+    z: &mut derivations::AnalysisContext,
+) -> Result<Vec<(String, data::class::Simple)>> {
+    match x {
+        yaml::Value::String(x) => {
+            // Only named structures can be represented in the type class
+            // definition, so this is only validated.
+            derivations::parse_type(x, y, z)?;
+            Ok(vec![])
+        }
+        yaml::Value::Object(object) => {
+            let mut names: Vec<&String> = object.keys().collect();
+            names.sort();
+            let mut structure = vec![];
+            for name in names {
+                let (_, simple) = yaml_field!(
+                    x,
+                    y,
+                    name.as_str(),
+                    yaml_prim!(str, |x, y| parse_structure_field_type(x, y, z))
+                )?;
+                if let Some(simple) = simple.flatten() {
+                    structure.push((name.clone(), simple));
+                }
+            }
+            Ok(structure)
+        }
+        _ => Err(cause!(YamlInvalidType, "string or object expected")),
+    }
+}
+
+/// Parse the metatype of a type parameter into the pattern that parameters
+/// bound to the slot must match.
+fn parse_parameter_type(x: &str, _y: &mut context::Context) -> Result<meta::pattern::Value> {
+    match x {
+        "dataType" => Ok(meta::pattern::Value::exactly_type(meta::Type::DataType)),
+        "boolean" => Ok(meta::pattern::Value::Boolean(None)),
+        "integer" => Ok(meta::pattern::Value::Integer(i64::MIN, i64::MAX)),
+        "enumeration" => Ok(meta::pattern::Value::Enum(None)),
+        "string" => Ok(meta::pattern::Value::String(None)),
+        _ => Err(cause!(
+            IllegalValue,
+            "unknown parameter type {x}; expected dataType, boolean, integer, \
+            enumeration, or string"
+        )),
+    }
+}
+
+/// Parse an enumeration option for an enumeration parameter.
+fn parse_enum_option(x: &str, y: &mut context::Context) -> Result<String> {
+    if !util::string::is_identifier(x) {
+        diagnostic!(
+            y,
+            Error,
+            IllegalValue,
+            "enumeration option {x:?} is not a valid identifier"
+        );
+    }
+    Ok(x.to_string())
+}
+
+/// Parse a type parameter slot declaration.
+fn parse_parameter(
+    x: &yaml::Value,
+    y: &mut context::Context,
+) -> Result<extension::simple::type_class::ParameterSlot> {
+    let name = yaml_field!(x, y, "name", yaml_prim!(str))?
+        .1
+        .unwrap_or_default();
+    let description = yaml_field!(x, y, "description", yaml_prim!(str))?
+        .1
+        .unwrap_or_default();
+    let pattern = yaml_required_field!(x, y, "type", yaml_prim!(str, parse_parameter_type))?
+        .1
+        .unwrap_or_default();
+    let min = yaml_field!(x, y, "min", yaml_prim!(i64))?.1;
+    let max = yaml_field!(x, y, "max", yaml_prim!(i64))?.1;
+    let options = yaml_repeated_field!(x, y, "options", yaml_prim!(str, parse_enum_option), 1)?
+        .1
+        .into_iter()
+        .flatten()
+        .collect::<Vec<_>>();
+    let optional = yaml_field!(x, y, "optional", yaml_prim!(bool))?
+        .1
+        .unwrap_or_default();
+
+    // Refine the pattern using the bounds/options, if applicable.
+    let pattern = match pattern {
+        meta::pattern::Value::Integer(_, _) => {
+            let min = min.unwrap_or(i64::MIN);
+            let max = max.unwrap_or(i64::MAX);
+            if min > max {
+                diagnostic!(
+                    y,
+                    Error,
+                    IllegalValue,
+                    "minimum value {min} is greater than maximum value {max}"
+                );
+            }
+            meta::pattern::Value::Integer(min, max)
+        }
+        meta::pattern::Value::Enum(_) => {
+            if options.is_empty() {
+                diagnostic!(
+                    y,
+                    Error,
+                    YamlMissingKey,
+                    "options must be specified for enumeration parameters"
+                );
+                meta::pattern::Value::Enum(None)
+            } else {
+                meta::pattern::Value::Enum(Some(options))
+            }
+        }
+        pattern => {
+            if min.is_some() || max.is_some() {
+                diagnostic!(
+                    y,
+                    Warning,
+                    RedundantField,
+                    "min and max are only used for integer parameters"
+                );
+            }
+            if !options.is_empty() {
+                diagnostic!(
+                    y,
+                    Warning,
+                    RedundantField,
+                    "options are only used for enumeration parameters"
+                );
+            }
+            pattern
+        }
+    };
+
+    Ok(extension::simple::type_class::ParameterSlot {
+        name,
+        description,
+        pattern,
+        optional,
+    })
+}
 
 /// Parse a type declaration.
 pub fn parse_type(
-    _x: &yaml::Value,
-    _y: &mut context::Context,
-    _z: &mut builder::Builder,
+    x: &yaml::Value,
+    y: &mut context::Context,
+    z: &mut builder::Builder,
 ) -> Result<()> {
-    // TODO
+    // Parse name and human-readable information.
+    let name = yaml_required_field!(x, y, "name", yaml_prim!(str))?
+        .1
+        .unwrap_or_default();
+    let description = yaml_field!(x, y, "description", yaml_prim!(str))?
+        .1
+        .unwrap_or_default();
+    crate::parse::traversal::push_yaml_field(x, y, "deprecated", true, |_, _| Ok(()))?;
+    crate::parse::traversal::push_yaml_field(x, y, "metadata", true, |_, _| Ok(()))?;
+
+    // Parse the structure and parameters of the type.
+    let structure = {
+        let mut analysis_context = derivations::AnalysisContext::new(Some(&*z));
+        yaml_field!(x, y, "structure", parse_structure, &mut analysis_context)?
+            .1
+            .unwrap_or_default()
+    };
+    let parameter_slots = yaml_repeated_field!(x, y, "parameters", parse_parameter)?
+        .1
+        .into_iter()
+        .flatten()
+        .collect::<Vec<_>>();
+    let parameters_variadic = yaml_field!(x, y, "variadic", yaml_prim!(bool))?
+        .1
+        .unwrap_or_default();
+    if parameters_variadic && parameter_slots.is_empty() {
+        diagnostic!(
+            y,
+            Error,
+            IllegalValue,
+            "variadic types must have at least one parameter"
+        );
+    }
+
+    // Register the type class.
+    if !name.is_empty() {
+        if let Err(e) = z
+            .type_classes
+            .resolve_local(name.as_str())
+            .expect_not_yet_defined()
+        {
+            diagnostic!(y, Error, e);
+        } else {
+            let definition = extension::simple::type_class::Definition {
+                extension_id: z.extension_id,
+                description,
+                structure,
+                parameter_slots,
+                parameters_variadic,
+            };
+            z.type_classes.define_item(name, Arc::new(definition), true);
+        }
+    }
     Ok(())
 }
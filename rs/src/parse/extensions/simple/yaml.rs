@@ -3,10 +3,12 @@
 //! Module providing parse/validation functions for parsing YAML extension
 //! files.
 
+use crate::input::registry;
 use crate::input::yaml;
 use crate::output::diagnostic::Result;
 use crate::output::extension;
 use crate::output::path;
+use crate::output::tree;
 use crate::parse::context;
 use crate::parse::extensions::simple::builder;
 use crate::parse::extensions::simple::function_decls;
@@ -58,6 +60,19 @@ fn parse_dependencies(
             let (_, reference) =
                 yaml_field!(x, y, alias.as_str(), yaml_prim!(str, parse_dependency))?;
             if let Some(reference) = reference {
+                // Make the type classes and type variations of the dependency
+                // available using the alias as namespace prefix.
+                let definition = reference.definition.as_ref();
+                builder.type_classes.define_nested(
+                    alias,
+                    definition.map(|x| x.type_classes.clone()),
+                    false,
+                );
+                builder.type_variations.define_nested(
+                    alias,
+                    definition.map(|x| x.type_variations.clone()),
+                    false,
+                );
                 builder.dependencies.insert(alias.clone(), reference);
             }
         }
@@ -116,6 +131,115 @@ fn parse_root(
     Ok(builder.into())
 }
 
+/// The schema for YAML extension files, loaded when first used.
+static SCHEMA: once_cell::sync::Lazy<jsonschema::Validator> = once_cell::sync::Lazy::new(|| {
+    jsonschema::Validator::new(
+        &yaml::yaml_to_json(
+            serde_yaml::from_str::<serde_yaml::Value>(
+                substrait_extensions::text::SIMPLE_EXTENSIONS_SCHEMA,
+            )
+            .unwrap(),
+            &path::Path::default(),
+        )
+        .unwrap(),
+    )
+    .unwrap()
+});
+
+/// Resolves the extension file identified by the given URN and parses it.
+fn parse_definition(
+    urn: &str,
+    y: &mut context::Context,
+) -> Option<Arc<extension::simple::module::Definition>> {
+    traversal::parse_urn(
+        urn,
+        y,
+        |x, y| traversal::read_yaml(x, y, Some(&SCHEMA), urn),
+        |x, y| parse_root(x, y, urn),
+    )
+    .1
+    .map(Arc::new)
+}
+
+/// Parses an extension file that is validated on its own rather than as part
+/// of a plan. The file is treated as if it were resolved from the URN it
+/// declares. name is the name of the file that locations of diagnostics
+/// within the file are reported for.
+pub fn parse_file(
+    data: &[u8],
+    y: &mut context::Context,
+    name: &str,
+) -> Option<Arc<extension::simple::module::Definition>> {
+    traversal::parse_file(
+        Box::new(data.to_vec()),
+        y,
+        |x, y| traversal::read_yaml(x, y, Some(&SCHEMA), name),
+        |x| {
+            x.get("urn")
+                .and_then(yaml::Value::as_str)
+                .unwrap_or_default()
+                .to_string()
+        },
+        parse_root,
+    )
+    .1
+    .map(Arc::new)
+}
+
+/// Same as parse_definition(), but using the given extension registry. If the
+/// registry holds an entry for the URN that is applicable in the current
+/// context, its parse tree is replayed into the current node instead.
+/// Otherwise, the file is parsed in isolation from the rest of the plan, such
+/// that the result can be added to the registry.
+fn parse_definition_with_registry(
+    urn: &str,
+    y: &mut context::Context,
+    registry: &registry::ExtensionRegistry,
+) -> Option<Arc<extension::simple::module::Definition>> {
+    let root = path::PathBuf {
+        root: registry::ROOT,
+        elements: vec![],
+    };
+
+    // Use the cached entry, if any.
+    let max_depth = y.config.max_urn_resolution_depth;
+    if let Some(entry) = registry.get(urn) {
+        if entry.is_applicable(y.urn_stack(), max_depth) {
+            y.replay(&entry.node, &root);
+            return Some(entry.definition.clone());
+        }
+    }
+
+    // Parse the file using a fresh state, such that the resulting tree does
+    // not depend on (or link to) anything else in the plan. Only the URN
+    // recursion stack is carried over, to detect cycles and apply the
+    // recursion limit, as well as the resources used so far, to apply the
+    // resource limits.
+    let mut state = context::State {
+        urn_stack: y.urn_stack().clone(),
+        budget: std::mem::take(y.budget()),
+        ..Default::default()
+    };
+    let mut node = tree::Node::from(y.node_type().clone());
+    let definition = parse_definition(
+        urn,
+        &mut context::Context::new(registry::ROOT, &mut node, &mut state, y.config),
+    );
+    y.replay(&node, &root);
+    let exceeded = state.budget.exceeded;
+    *y.budget() = std::mem::take(&mut state.budget);
+
+    // Add the result to the registry, unless it was affected by the URN
+    // recursion stack or cut short by a resource limit.
+    if state.urn_resolution_truncated {
+        y.set_urn_resolution_truncated();
+    } else if let Some(definition) = definition.as_ref().filter(|_| !exceeded) {
+        registry.insert(urn, registry::Entry::new(definition.clone(), node));
+    }
+
+    definition
+}
+
 fn make_module_reference(
     urn: &str,
     definition: Option<Arc<extension::simple::module::Definition>>,
@@ -151,32 +275,14 @@ pub fn parse_urn<S: AsRef<str>>(
         }
         make_module_reference(urn, module.definition.clone(), y)
     } else {
-        // Load the schema for YAML extension files when this function is first
-        // called.
-        static SCHEMA: once_cell::sync::Lazy<jsonschema::Validator> =
-            once_cell::sync::Lazy::new(|| {
-                jsonschema::Validator::new(
-                    &yaml::yaml_to_json(
-                        serde_yaml::from_str::<serde_yaml::Value>(
-                            substrait_extensions::text::SIMPLE_EXTENSIONS_SCHEMA,
-                        )
-                        .unwrap(),
-                        &path::Path::default(),
-                    )
-                    .unwrap(),
-                )
-                .unwrap()
-            });
-
-        // Parse the file.
-        let definition = traversal::parse_urn(
-            urn,
-            y,
-            |x, y| traversal::read_yaml(x, y, Some(&SCHEMA)),
-            |x, y| parse_root(x, y, urn),
-        )
-        .1
-        .map(Arc::new);
+        // Parse the file, going through the extension registry if one is
+        // configured.
+        let config = y.config;
+        let definition = if let Some(registry) = &config.extension_registry {
+            parse_definition_with_registry(urn, y, registry)
+        } else {
+            parse_definition(urn, y)
+        };
 
         // Create reference and insert into extension module list.
         let module = make_module_reference(urn, definition, y);
@@ -187,3 +293,98 @@ pub fn parse_urn<S: AsRef<str>>(
 
     Ok(module)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::input::config;
+    use crate::output::diagnostic;
+
+    #[test]
+    fn standalone_extension() {
+        let config = config::Config::default();
+        let yaml = "
+urn: extension:com.example:geometry
+types:
+  - name: point
+    structure:
+      latitude: i32
+      longitude: i32
+scalar_functions:
+  - name: distance
+    impls:
+      - args:
+          - value: u!point
+          - value: u!point
+        return: fp64
+";
+        let result = crate::validate_extension(yaml.as_bytes(), &config);
+        assert_eq!(result.root_name, "extension");
+        assert_eq!(
+            result.root.node_type,
+            tree::NodeType::ResolvedUrn(String::from("extension:com.example:geometry"))
+        );
+        assert!(!result
+            .iter_diagnostics()
+            .any(|x| x.adjusted_level == diagnostic::Level::Error));
+
+        // References to types that don't exist are caught, and reported at
+        // their location in the file.
+        let result = crate::validate_extension(
+            yaml.replace("u!point\n        return", "u!nowhere\n        return")
+                .as_bytes(),
+            &config,
+        );
+        let diag = result
+            .iter_diagnostics()
+            .find(|x| x.cause.classification == diagnostic::Classification::LinkUnresolvedName)
+            .unwrap();
+        assert_eq!(diag.span.as_ref().unwrap().to_string(), "extension:13:20");
+
+        // So are malformed type derivations.
+        let result = crate::validate_extension(
+            yaml.replace("return: fp64", "return: decimal<P1")
+                .as_bytes(),
+            &config,
+        );
+        assert!(result
+            .iter_diagnostics()
+            .any(|x| x.cause.classification == diagnostic::Classification::TypeParseError));
+
+        // And implementations with the same compound name.
+        let duplicate = "
+      - args:
+          - value: u!point
+          - value: u!point
+        return: i32
+";
+        let result = crate::validate_extension((yaml.to_string() + duplicate).as_bytes(), &config);
+        assert!(
+            result
+                .iter_diagnostics()
+                .any(|x| x.cause.classification
+                    == diagnostic::Classification::LinkDuplicateDefinition)
+        );
+
+        // Dependencies are checked.
+        let result = crate::validate_extension(
+            yaml.replace("types:", "dependencies:\n  geo: not-a-urn\ntypes:")
+                .as_bytes(),
+            &config,
+        );
+        assert!(result
+            .iter_diagnostics()
+            .any(|x| x.cause.classification == diagnostic::Classification::IllegalUrn));
+
+        // So are files that don't match the schema, again at the location of
+        // the offending value.
+        let result = crate::validate_extension(b"urn: 1", &config);
+        let diag = result
+            .iter_diagnostics()
+            .find(|x| {
+                x.cause.classification == diagnostic::Classification::YamlSchemaValidationFailed
+            })
+            .unwrap();
+        assert_eq!(diag.span.as_ref().unwrap().to_string(), "extension:1:6");
+    }
+}
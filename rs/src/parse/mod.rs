@@ -186,18 +186,26 @@ pub mod context;
 mod expressions;
 mod extensions;
 mod plan;
+mod profile;
 mod relations;
 mod sorts;
 mod types;
+mod versioning;
 
+use crate::input;
 use crate::input::config;
 use crate::input::proto;
+use crate::input::source_map;
 use crate::input::traits::InputNode;
 use crate::output::diagnostic;
 use crate::output::parse_result;
 use crate::output::path;
+use crate::output::span;
+use crate::output::tree;
 
 use prost::Message;
+use prost_reflect::ReflectMessage;
+use std::sync::Arc;
 
 /// Represents the state of attempting to decode a buffer as a plan, or failing
 /// that, as a plan version.
@@ -230,6 +238,28 @@ fn parse_proto<B: prost::bytes::Buf + Clone>(buffer: B) -> ParsedProtoResult {
     }
 }
 
+/// Returns whether the given legacy plan fields contain any legacy extension
+/// declarations.
+fn has_legacy_extensions(legacy: &proto::substrait::validator::LegacyPlan) -> bool {
+    !legacy.extension_uris.is_empty()
+        || legacy
+            .extensions
+            .iter()
+            .any(|x| extensions::simple::legacy_uri_reference(x).is_some())
+}
+
+/// Decodes the legacy extension declarations that older producers may still
+/// put in a serialized plan, which prost would otherwise silently drop.
+/// Returns None if the plan contains none.
+fn decode_legacy_extensions<B: prost::bytes::Buf>(
+    buffer: B,
+) -> Option<Arc<proto::substrait::validator::LegacyPlan>> {
+    proto::substrait::validator::LegacyPlan::decode(buffer)
+        .ok()
+        .filter(has_legacy_extensions)
+        .map(Arc::new)
+}
+
 /// Parses the given [`proto::substrait::Plan`] message, validates it, and
 /// returns the parse tree with diagnostic results.
 pub fn parse<B: prost::bytes::Buf + Clone>(
@@ -237,6 +267,7 @@ pub fn parse<B: prost::bytes::Buf + Clone>(
     config: &config::Config,
 ) -> parse_result::ParseResult {
     let mut state = context::State::default();
+    state.legacy_extensions = decode_legacy_extensions(buffer.clone());
 
     let (err1, err2) = match parse_proto(buffer) {
         ParsedProtoResult::Parsed(ref plan) => {
@@ -266,9 +297,36 @@ pub fn parse<B: prost::bytes::Buf + Clone>(
         ParsedProtoResult::Failed(err1, err2) => (err1, err2),
     };
 
-    // --------------------------------------------------------------------------------
-    // The parser failed to decode the buffer as either a plan or plan version, so now
-    // we create a minimal parse tree with the error diagnostics from the parser.
+    // The parser failed to decode the buffer as either a plan or plan version.
+    parse_failed(
+        config,
+        vec![
+            (
+                cause!(
+                    ProtoParseFailed,
+                    "failed to parse as substrait.Plan: {err1}"
+                ),
+                None,
+            ),
+            (
+                cause!(
+                    ProtoParseFailed,
+                    "failed to parse as substrait.PlanVersion: {err2}"
+                ),
+                None,
+            ),
+        ],
+    )
+}
+
+/// Creates a minimal parse tree with just the given decode errors and their
+/// locations in the input (if known), for when the input could not be decoded
+/// at all.
+fn parse_failed(
+    config: &config::Config,
+    causes: Vec<(diagnostic::Cause, Option<span::Span>)>,
+) -> parse_result::ParseResult {
+    let mut state = context::State::default();
 
     // Create a minimal root node with just the decode error
     // diagnostic.
@@ -278,33 +336,167 @@ pub fn parse<B: prost::bytes::Buf + Clone>(
     let mut context = context::Context::new("plan", &mut root, &mut state, config);
     plan::mark_experimental(&mut context);
 
-    // Push the earlier diagnostic.
-    context.push_diagnostic(diagnostic::RawDiagnostic {
-        cause: cause!(
-            ProtoParseFailed,
-            "failed to parse as substrait.Plan: {err1}"
-        ),
-        level: diagnostic::Level::Error,
-        path: path::PathBuf {
-            root: "plan",
-            elements: vec![],
-        },
-    });
-
-    // Push the PlanVersion diagnostic.
-    context.push_diagnostic(diagnostic::RawDiagnostic {
-        cause: cause!(
-            ProtoParseFailed,
-            "failed to parse as substrait.PlanVersion: {err2}"
-        ),
-        level: diagnostic::Level::Error,
-        path: path::PathBuf {
-            root: "plan",
-            elements: vec![],
+    // Push the decode errors.
+    for (cause, span) in causes {
+        context.push_diagnostic(diagnostic::RawDiagnostic {
+            cause,
+            level: diagnostic::Level::Error,
+            path: path::PathBuf {
+                root: "plan",
+                elements: vec![],
+            },
+            span,
+        });
+    }
+
+    parse_result::ParseResult {
+        root,
+        root_name: "plan",
+    }
+}
+
+/// Decodes the canonical protobuf JSON serialization of a
+/// [`proto::substrait::Plan`] message, using the descriptor pool that comes
+/// with the protobuf bindings. Fields that don't exist in the protobuf
+/// description are an error, unless the configuration says to ignore unknown
+/// fields. On failure, the location of the error is returned along with the
+/// error, if known.
+fn parse_json_proto(
+    text: &str,
+    config: &config::Config,
+) -> Result<proto::substrait::Plan, (diagnostic::Cause, Option<span::Span>)> {
+    let descriptor = proto::substrait::Plan::default().descriptor();
+    let mut deserializer = serde_json::Deserializer::from_str(text);
+    let mut track = serde_path_to_error::Track::new();
+    let message = prost_reflect::DynamicMessage::deserialize_with_options(
+        descriptor,
+        serde_path_to_error::Deserializer::new(&mut deserializer, &mut track),
+        &prost_reflect::DeserializeOptions::new()
+            .deny_unknown_fields(!config.ignore_unknown_fields),
+    )
+    .and_then(|message| deserializer.end().map(|_| message))
+    .map_err(|e| {
+        (
+            cause!(
+                ProtoParseFailed,
+                "failed to parse JSON as substrait.Plan at {}: {e}",
+                track.path()
+            ),
+            Some(span::Span::at(None, e.line(), e.column().max(1))),
+        )
+    })?;
+    message
+        .transcode_to::<proto::substrait::Plan>()
+        .map_err(|e| (ecause!(ProtoParseFailed, e), None))
+}
+
+/// Separates the legacy extension declarations from the canonical protobuf
+/// JSON serialization of a plan, as [`proto::substrait::Plan`] no longer has
+/// fields for them. Returns None if the plan contains none; otherwise returns
+/// the declarations along with the JSON text without them.
+fn split_legacy_json(text: &str) -> Option<(Arc<proto::substrait::validator::LegacyPlan>, String)> {
+    const URI_FIELDS: &[&str] = &["extensionUris", "extension_uris"];
+    const REFERENCE_FIELDS: &[&str] = &["extensionUriReference", "extension_uri_reference"];
+    if !URI_FIELDS
+        .iter()
+        .chain(REFERENCE_FIELDS)
+        .any(|x| text.contains(x))
+    {
+        return None;
+    }
+
+    // Decode the legacy fields, ignoring everything else.
+    let mut value: serde_json::Value = serde_json::from_str(text).ok()?;
+    let legacy = prost_reflect::DynamicMessage::deserialize_with_options(
+        proto::substrait::validator::LegacyPlan::default().descriptor(),
+        &value,
+        &prost_reflect::DeserializeOptions::new().deny_unknown_fields(false),
+    )
+    .ok()?
+    .transcode_to::<proto::substrait::validator::LegacyPlan>()
+    .ok()
+    .filter(has_legacy_extensions)?;
+
+    // Remove the legacy fields from the JSON text.
+    let object = value.as_object_mut()?;
+    for field in URI_FIELDS {
+        object.remove(*field);
+    }
+    if let Some(serde_json::Value::Array(declarations)) = object.get_mut("extensions") {
+        for mapping in declarations
+            .iter_mut()
+            .filter_map(serde_json::Value::as_object_mut)
+            .flat_map(|x| x.values_mut())
+            .filter_map(serde_json::Value::as_object_mut)
+        {
+            for field in REFERENCE_FIELDS {
+                mapping.remove(*field);
+            }
+        }
+    }
+
+    Some((Arc::new(legacy), value.to_string()))
+}
+
+/// Parses the given canonical protobuf JSON serialization of a
+/// [`proto::substrait::Plan`] message, validates it, and returns the parse
+/// tree with diagnostic results.
+pub fn parse_json(text: &str, config: &config::Config) -> parse_result::ParseResult {
+    // If the legacy fields had to be removed from the text, locations in the
+    // text that remains are meaningless to the user.
+    let legacy = split_legacy_json(text);
+    let plan = match &legacy {
+        Some((_, stripped)) => {
+            parse_json_proto(stripped, config).map_err(|(cause, _)| (cause, None))
+        }
+        None => parse_json_proto(text, config),
+    };
+    match plan {
+        Ok(plan) => {
+            let mut state = context::State::default();
+            state.legacy_extensions = legacy.map(|(legacy, _)| legacy);
+            let root = path::PathBuf {
+                root: "plan",
+                elements: vec![],
+            };
+            let source = source_map::SourceMap::from_json(text, None, root);
+            state.sources.push(Arc::new(source));
+            validate_with_state(&plan, &mut state, config)
+        }
+        Err(error) => parse_failed(config, vec![error]),
+    }
+}
+
+/// Parses the given serialized [`proto::substrait::Plan`] message in the
+/// given format, validates it, and returns the parse tree with diagnostic
+/// results.
+pub fn parse_with_format(
+    data: &[u8],
+    format: input::Format,
+    config: &config::Config,
+) -> parse_result::ParseResult {
+    match format {
+        input::Format::Proto => parse(data, config),
+        input::Format::Json => match std::str::from_utf8(data) {
+            Ok(text) => parse_json(text, config),
+            Err(e) => parse_failed(config, vec![(ecause!(ProtoParseFailed, e), None)]),
         },
-    });
+    }
+}
 
-    parse_result::ParseResult { root }
+/// Parses the given simple extension YAML file on its own, validates it, and
+/// returns the parse tree with diagnostic results. Dependencies of the file
+/// are resolved as usual.
+pub fn parse_extension(data: &[u8], config: &config::Config) -> parse_result::ParseResult {
+    let mut state = context::State::default();
+    let mut root = tree::Node::from(tree::NodeType::YamlMap);
+    let mut context = context::Context::new("extension", &mut root, &mut state, config);
+    plan::mark_experimental(&mut context);
+    extensions::simple::parse_file(data, &mut context, "extension");
+    parse_result::ParseResult {
+        root,
+        root_name: "extension",
+    }
 }
 
 /// Validate the given [`proto::substrait::Plan`] message, returning the parse
@@ -313,12 +505,93 @@ pub fn validate(
     plan: &proto::substrait::Plan,
     config: &config::Config,
 ) -> parse_result::ParseResult {
-    let mut state = context::State::default();
-    traversal::validate::<proto::substrait::Plan, _>(
-        plan,
-        "plan",
-        plan::parse_plan,
-        &mut state,
-        config,
-    )
+    validate_with_state(plan, &mut context::State::default(), config)
+}
+
+/// Validate the given [`proto::substrait::Plan`] message using the given
+/// initial parser state.
+fn validate_with_state(
+    plan: &proto::substrait::Plan,
+    state: &mut context::State,
+    config: &config::Config,
+) -> parse_result::ParseResult {
+    traversal::validate::<proto::substrait::Plan, _>(plan, "plan", plan::parse_plan, state, config)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn json_input() {
+        let config = config::Config::default();
+        let json = r#"{
+            "version": { "minorNumber": 87, "producer": "json-test" },
+            "relations": []
+        }"#;
+        let binary = proto::substrait::Plan {
+            version: Some(proto::substrait::Version {
+                minor_number: 87,
+                producer: String::from("json-test"),
+                ..Default::default()
+            }),
+            ..Default::default()
+        }
+        .encode_to_vec();
+
+        // JSON input should yield the same diagnostics as the equivalent
+        // binary input, except that they also have a location.
+        let from_json = parse_json(json, &config);
+        let from_binary = parse(binary.as_slice(), &config);
+        assert_eq!(
+            from_json
+                .iter_diagnostics()
+                .map(|x| format!("{x:#}"))
+                .collect::<Vec<_>>(),
+            from_binary
+                .iter_diagnostics()
+                .map(|x| format!("{x:#}"))
+                .collect::<Vec<_>>()
+        );
+        assert!(!from_json
+            .iter_diagnostics()
+            .any(|x| x.cause.classification == diagnostic::Classification::ProtoParseFailed));
+    }
+
+    #[test]
+    fn json_input_error_path() {
+        let config = config::Config::default();
+        let json = r#"{ "relations": [ { "root": { "nmes": ["a"] } } ] }"#;
+        let result = parse_with_format(json.as_bytes(), input::Format::Json, &config);
+        let diagnostics = result
+            .iter_diagnostics()
+            .filter(|x| x.adjusted_level == diagnostic::Level::Error)
+            .collect::<Vec<_>>();
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(
+            diagnostics[0].cause.classification,
+            diagnostic::Classification::ProtoParseFailed
+        );
+        assert_eq!(diagnostics[0].span.as_ref().map(|x| x.start.line), Some(1));
+        let message = diagnostics[0].cause.to_string();
+        assert!(message.contains("relations[0].root"), "{message}");
+    }
+
+    #[test]
+    fn json_input_unknown_fields() {
+        let json = r#"{
+            "version": { "minorNumber": 87, "producer": "json-test" },
+            "relations": [],
+            "unknownField": 1
+        }"#;
+        let failed = |config: &config::Config| {
+            parse_json(json, config)
+                .iter_diagnostics()
+                .any(|x| x.cause.classification == diagnostic::Classification::ProtoParseFailed)
+        };
+        let mut config = config::Config::default();
+        assert!(failed(&config));
+        config.ignore_unknown_fields();
+        assert!(!failed(&config));
+    }
 }
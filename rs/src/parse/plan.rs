@@ -146,6 +146,9 @@ fn parse_version(x: &substrait::Version, y: &mut context::Context) -> diagnostic
             crate::substrait_version()
         );
     };
+    if version != semver::Version::new(0, 0, 0) {
+        y.set_plan_version(version);
+    }
 
     // Check hash.
     proto_primitive_field!(x, y, git_hash, parse_git_hash);
@@ -0,0 +1,211 @@
+// SPDX-License-Identifier: Apache-2.0
+
+//! Module for checking the plan against the consumer capability profile, if
+//! one is configured.
+
+use crate::input::profile;
+use crate::output::extension;
+use crate::output::type_system::data;
+use crate::parse::context;
+
+/// Checks whether the relation type with the given name is supported.
+pub fn check_relation(y: &mut context::Context, relation: &str) {
+    let config = y.config;
+    if let Some(profile) = &config.consumer_profile {
+        if !profile.supports_relation(relation) {
+            diagnostic!(
+                y,
+                Error,
+                UnsupportedRelation,
+                "{} does not support {relation} relations",
+                profile.consumer_name()
+            );
+        }
+    }
+}
+
+/// Checks whether the expression type with the given name is supported.
+pub fn check_expression(y: &mut context::Context, expression: &str) {
+    let config = y.config;
+    if let Some(profile) = &config.consumer_profile {
+        if !profile.supports_expression(expression) {
+            diagnostic!(
+                y,
+                Error,
+                UnsupportedExpression,
+                "{} does not support {expression} expressions",
+                profile.consumer_name()
+            );
+        }
+    }
+}
+
+/// Checks whether the file format with the given name is supported.
+pub fn check_file_format(y: &mut context::Context, format: &str) {
+    let config = y.config;
+    if let Some(profile) = &config.consumer_profile {
+        if !profile.supports_file_format(format) {
+            diagnostic!(
+                y,
+                Error,
+                UnsupportedFileFormat,
+                "{} does not support the {format} file format",
+                profile.consumer_name()
+            );
+        }
+    }
+}
+
+/// Checks whether the given function is supported.
+pub fn check_function(y: &mut context::Context, function: &extension::simple::function::Reference) {
+    let Some(name) = function.name.name() else {
+        return;
+    };
+    let config = y.config;
+    if let Some(profile) = &config.consumer_profile {
+        if !profile.supports_function(function.urn.name(), name) {
+            diagnostic!(
+                y,
+                Error,
+                UnsupportedFunction,
+                "{} does not support function {name}",
+                profile.consumer_name()
+            );
+        }
+    }
+}
+
+/// Checks whether the given data type is supported.
+pub fn check_type(y: &mut context::Context, data_type: &data::Type) {
+    let config = y.config;
+    if let Some(profile) = &config.consumer_profile {
+        match profile.check_type(data_type) {
+            Ok(()) => {}
+            Err(profile::TypeSupportError::Class(class)) => diagnostic!(
+                y,
+                Error,
+                UnsupportedType,
+                "{} does not support {class} data types",
+                profile.consumer_name()
+            ),
+            Err(profile::TypeSupportError::Parameter {
+                class,
+                index,
+                value,
+                range,
+            }) => diagnostic!(
+                y,
+                Error,
+                UnsupportedTypeParameter,
+                "{} only supports {range} for parameter {index} of {class} data types, \
+                found {value}",
+                profile.consumer_name()
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::input::config;
+    use crate::output::diagnostic;
+
+    #[test]
+    fn consumer_profile() {
+        let json = r#"{
+            "version": { "minorNumber": 87, "producer": "json-test" },
+            "extensionUrns": [{
+                "extensionUrnAnchor": 1,
+                "urn": "extension:io.substrait:functions_arithmetic"
+            }],
+            "extensions": [{
+                "extensionFunction": {
+                    "extensionUrnReference": 1,
+                    "functionAnchor": 1,
+                    "name": "add:i32_i32"
+                }
+            }],
+            "relations": [{ "rel": { "project": {
+                "input": { "read": {
+                    "baseSchema": {
+                        "names": ["a", "b"],
+                        "struct": {
+                            "types": [
+                                { "i32": { "nullability": "NULLABILITY_REQUIRED" } },
+                                { "decimal": {
+                                    "precision": 38,
+                                    "scale": 2,
+                                    "nullability": "NULLABILITY_REQUIRED"
+                                } }
+                            ],
+                            "nullability": "NULLABILITY_REQUIRED"
+                        }
+                    },
+                    "namedTable": { "names": ["t"] }
+                } },
+                "expressions": [{ "scalarFunction": {
+                    "functionReference": 1,
+                    "arguments": [
+                        { "value": { "selection": {
+                            "rootReference": {},
+                            "directReference": { "structField": { "field": 0 } }
+                        } } },
+                        { "value": { "literal": { "i32": 1 } } }
+                    ],
+                    "outputType": { "i32": { "nullability": "NULLABILITY_REQUIRED" } }
+                } }]
+            } } }]
+        }"#;
+        let unsupported = |config: &config::Config| {
+            crate::parse_json(json, config)
+                .iter_diagnostics()
+                .map(|x| x.cause.classification)
+                .filter(|x| x.group() == diagnostic::Classification::Unsupported)
+                .collect::<Vec<_>>()
+        };
+
+        let mut config = config::Config::default();
+        assert!(unsupported(&config).is_empty());
+
+        // Everything the plan uses.
+        config.set_consumer_profile(
+            profile::ConsumerProfile::from_yaml(
+                "
+                relations: [read, project]
+                types:
+                  i32: {}
+                  decimal:
+                    parameters: [{max: 38}]
+                  struct: {}
+                functions: ['extension:io.substrait:functions_arithmetic::add:*']
+                ",
+            )
+            .unwrap(),
+        );
+        assert!(unsupported(&config).is_empty());
+
+        // A profile that lacks the project relation, limits the precision of
+        // decimals, and only supports subtraction.
+        config.set_consumer_profile(
+            profile::ConsumerProfile::from_yaml(
+                "
+                name: test-engine
+                relations: [read]
+                types:
+                  i32: {}
+                  decimal:
+                    parameters: [{max: 18}]
+                  struct: {}
+                functions: ['subtract:*']
+                ",
+            )
+            .unwrap(),
+        );
+        let classes = unsupported(&config);
+        assert!(classes.contains(&diagnostic::Classification::UnsupportedRelation));
+        assert!(classes.contains(&diagnostic::Classification::UnsupportedTypeParameter));
+        assert!(classes.contains(&diagnostic::Classification::UnsupportedFunction));
+        assert!(!classes.contains(&diagnostic::Classification::UnsupportedType));
+    }
+}
@@ -45,10 +45,68 @@ struct Field {
     field_type: FieldType,
 }
 
-/// Parse a measure.
+/// Returns whether an aggregate function evaluated in the given phase
+/// consumes intermediate values (true) or raw input values (false), or None
+/// if the phase is not specified.
+fn consumes_intermediate(phase: substrait::AggregationPhase) -> Option<bool> {
+    match phase {
+        substrait::AggregationPhase::Unspecified => None,
+        substrait::AggregationPhase::InitialToIntermediate
+        | substrait::AggregationPhase::InitialToResult => Some(false),
+        substrait::AggregationPhase::IntermediateToIntermediate
+        | substrait::AggregationPhase::IntermediateToResult => Some(true),
+    }
+}
+
+/// Returns whether an aggregate function evaluated in the given phase
+/// yields intermediate values (true) or final results (false), or None if
+/// the phase is not specified.
+fn yields_intermediate(phase: substrait::AggregationPhase) -> Option<bool> {
+    match phase {
+        substrait::AggregationPhase::Unspecified => None,
+        substrait::AggregationPhase::InitialToIntermediate
+        | substrait::AggregationPhase::IntermediateToIntermediate => Some(true),
+        substrait::AggregationPhase::InitialToResult
+        | substrait::AggregationPhase::IntermediateToResult => Some(false),
+    }
+}
+
+/// Returns the aggregation phases of the measures of the given aggregate
+/// relation, ignoring measures for which no phase is specified.
+fn measure_phases(
+    x: &substrait::AggregateRel,
+) -> impl Iterator<Item = substrait::AggregationPhase> + '_ {
+    x.measures
+        .iter()
+        .filter_map(|x| x.measure.as_ref())
+        .map(|x| x.phase())
+        .filter(|x| *x != substrait::AggregationPhase::Unspecified)
+}
+
+/// Returns whether the given relation yields intermediate aggregate values,
+/// looking through exchanges. Aggregate relations yield them depending on
+/// the phases of their measures, and read relations never do. Returns None
+/// if this is unknown, for instance because the relation may pass through
+/// values from its input.
+fn input_yields_intermediate(x: &substrait::Rel) -> Option<bool> {
+    match &x.rel_type {
+        Some(substrait::rel::RelType::Aggregate(x)) => {
+            measure_phases(x).find_map(yields_intermediate)
+        }
+        Some(substrait::rel::RelType::Exchange(x)) => {
+            x.input.as_deref().and_then(input_yields_intermediate)
+        }
+        Some(substrait::rel::RelType::Read(_)) => Some(false),
+        _ => None,
+    }
+}
+
+/// Parse a measure. input_intermediate specifies whether the input relation
+/// is known to yield intermediate values, see input_yields_intermediate().
 fn parse_measure(
     x: &substrait::aggregate_rel::Measure,
     y: &mut context::Context,
+    input_intermediate: Option<bool>,
 ) -> diagnostic::Result<expressions::Expression> {
     // Parse the aggregate function.
     let (n, e) = proto_required_field!(x, y, measure, functions::parse_aggregate_function);
@@ -56,6 +114,29 @@ fn parse_measure(
     let expression = e.unwrap_or_default();
     y.set_data_type(data_type.clone());
 
+    // Check that the phase of the aggregate function matches its input.
+    let consumes = x
+        .measure
+        .as_ref()
+        .and_then(|x| consumes_intermediate(x.phase()));
+    match (consumes, input_intermediate) {
+        (Some(true), Some(true)) | (Some(false), Some(false)) | (_, None) | (None, _) => {}
+        (Some(true), Some(false)) => diagnostic!(
+            y,
+            Warning,
+            RelationAggregationPhaseMismatch,
+            "this measure consumes intermediate values, but the input does \
+            not yield intermediate values"
+        ),
+        (Some(false), Some(true)) => diagnostic!(
+            y,
+            Warning,
+            RelationAggregationPhaseMismatch,
+            "this measure consumes raw input values, but the input is an \
+            aggregate relation that yields intermediate values"
+        ),
+    }
+
     // Parse the filter and describe the node.
     if x.filter.is_some() {
         let (n, e) = proto_required_field!(x, y, filter, expressions::parse_predicate);
@@ -100,10 +181,23 @@ pub fn parse_aggregate_rel(
     // Set schema context for the grouping and measure expressions.
     y.set_schema(in_type);
 
+    // Parse the grouping expressions that grouping sets can refer to. Each of
+    // these yields a field, in the order in which they are specified.
+    let mut fields = vec![];
+    let (nodes, expressions) =
+        proto_repeated_field!(x, y, grouping_expressions, expressions::parse_expression);
+    for (node, expression) in nodes.iter().zip(expressions) {
+        fields.push(Field {
+            expression: expression.unwrap_or_default(),
+            data_type: node.data_type(),
+            field_type: FieldType::NullableGroupedField,
+        });
+    }
+    let mut referenced = vec![false; fields.len()];
+
     // Parse grouping sets.
     let mut grouping_set_expressions: Vec<substrait::Expression> = vec![];
-    let mut fields = vec![];
-    let mut sets = vec![];
+    let mut sets: Vec<Vec<usize>> = vec![];
     proto_repeated_field!(x, y, groupings, |x, y| {
         sets.push(vec![]);
         proto_repeated_field!(x, y, grouping_expressions, |x, y| {
@@ -113,9 +207,8 @@ pub fn parse_aggregate_rel(
             // field list. Return the index in the field list.
             let index = grouping_set_expressions
                 .iter()
-                .enumerate()
-                .find(|(_, e)| e == &x)
-                .map(|(i, _)| i)
+                .position(|e| e == x)
+                .map(|i| i + referenced.len())
                 .unwrap_or_else(|| {
                     // Create new field.
                     grouping_set_expressions.push(x.clone());
@@ -133,7 +226,40 @@ pub fn parse_aggregate_rel(
 
             result
         });
-        match x.grouping_expressions.len() {
+        proto_repeated_field!(x, y, expression_references, |x, y| {
+            let index = *x as usize;
+            if index >= referenced.len() {
+                return Err(cause!(
+                    IllegalValue,
+                    "grouping expression index {index} is out of range; there \
+                    are {} grouping expressions",
+                    referenced.len()
+                ));
+            }
+            referenced[index] = true;
+            describe!(y, Misc, "Grouping expression {}", fields[index].expression);
+            sets.last_mut().unwrap().push(index);
+            Ok(())
+        });
+
+        // Check for duplicate grouping sets. These are legal, but yield
+        // duplicate rows, which is probably not intended.
+        let (set, previous_sets) = sets.split_last().unwrap();
+        let set = set.iter().collect::<HashSet<_>>();
+        if let Some(index) = previous_sets
+            .iter()
+            .position(|x| x.iter().collect::<HashSet<_>>() == set)
+        {
+            diagnostic!(
+                y,
+                Warning,
+                RelationDuplicateGroupingSet,
+                "this grouping set is equivalent to grouping set {index}, so \
+                the rows for it will be returned twice"
+            );
+        }
+
+        match set.len() {
             0 => summary!(y, "A grouping set that aggregates all rows."),
             1 => summary!(
                 y,
@@ -148,6 +274,14 @@ pub fn parse_aggregate_rel(
         }
         Ok(())
     });
+    for (index, _) in referenced.iter().enumerate().filter(|(_, x)| !**x) {
+        diagnostic!(
+            y,
+            Warning,
+            RedundantField,
+            "grouping expression {index} is not referenced by any grouping set"
+        );
+    }
     drop(grouping_set_expressions);
     let sets = sets;
 
@@ -164,9 +298,28 @@ pub fn parse_aggregate_rel(
         }
     }
 
+    // Check that the measures agree on whether they yield intermediate
+    // values or final results.
+    let mut phases = measure_phases(x).filter_map(yields_intermediate);
+    if let Some(first) = phases.next() {
+        if phases.any(|x| x != first) {
+            diagnostic!(
+                y,
+                Warning,
+                RelationAggregationPhaseMismatch,
+                "some measures yield intermediate values while others yield \
+                final results"
+            );
+        }
+    }
+
     // Parse measures.
+    let input_intermediate = x.input.as_deref().and_then(input_yields_intermediate);
     proto_repeated_field!(x, y, measures, |x, y| {
-        let result = parse_measure(x, y);
+        let (result, toplevel) = y.enter_expression(|y| parse_measure(x, y, input_intermediate));
+        if let (Ok(expression), true) = (&result, toplevel) {
+            y.set_expression(expression.to_string());
+        }
         fields.push(Field {
             expression: result.as_ref().cloned().unwrap_or_default(),
             data_type: y.data_type(),
@@ -175,13 +328,15 @@ pub fn parse_aggregate_rel(
         result
     });
 
-    // The relation is invalid if no fields result from it.
+    // If there are neither grouping expressions nor measures, the relation
+    // yields rows without any fields, which is probably not intended.
     if fields.is_empty() {
         diagnostic!(
             y,
-            Error,
-            RelationInvalid,
-            "aggregate relations must have at least one grouping expression or measure"
+            Warning,
+            RelationEmptyAggregate,
+            "this aggregate relation has no grouping expressions or measures, \
+            so it yields rows without any fields"
         );
     }
 
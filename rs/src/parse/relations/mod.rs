@@ -19,6 +19,7 @@ use crate::input::proto::substrait;
 use crate::input::traits::ProtoOneOf;
 use crate::output::diagnostic;
 use crate::parse::context;
+use crate::parse::profile;
 
 /// Parse a relation type.
 fn parse_rel_type(x: &substrait::rel::RelType, y: &mut context::Context) -> diagnostic::Result<()> {
@@ -30,6 +31,9 @@ fn parse_rel_type(x: &substrait::rel::RelType, y: &mut context::Context) -> diag
     // as relations.
     describe!(y, Relation, "{} relation", x.proto_oneof_variant());
 
+    // Check whether the consumer supports this relation type.
+    profile::check_relation(y, x.proto_oneof_variant());
+
     // NOTE: if you're here because you added a relation type and now CI is
     // failing, you can just add "_ => Ok(())," to the end of this list. The
     // validator will then automatically throw a "not yet implemented" warning
@@ -10,6 +10,7 @@
 use std::sync::Arc;
 
 use crate::input::proto::substrait;
+use crate::input::traits::ProtoOneOf;
 use crate::output::diagnostic;
 use crate::output::type_system::data;
 use crate::parse::context;
@@ -17,6 +18,7 @@ use crate::parse::expressions;
 use crate::parse::expressions::literals;
 use crate::parse::expressions::references::mask;
 use crate::parse::extensions;
+use crate::parse::profile;
 use crate::parse::types;
 use crate::util;
 
@@ -28,6 +30,10 @@ struct SourceInfo {
 
     /// The schema of the data, if not context-sensitive.
     pub data_type: Option<data::Type>,
+
+    /// Whether (some of) the data is read from delimiter-separated text
+    /// files, in which case all fields must be parseable from strings.
+    pub text: bool,
 }
 
 /// Parse virtual table.
@@ -61,6 +67,7 @@ fn parse_virtual_table(
     Ok(SourceInfo {
         name: String::from("virtual table"),
         data_type: Some(data_type),
+        text: false,
     })
 }
 
@@ -126,39 +133,198 @@ fn parse_path_type(
     }
 }
 
-/// Parse file format, yielding a string description of the format if known.
+/// Information about the file format of a file entry.
+struct FileFormatInfo {
+    /// Short description of the file format, used in the summary of the
+    /// file entry.
+    pub name: String,
+
+    /// Whether the files are delimiter-separated text files, for which all
+    /// fields are parsed from strings.
+    pub text: bool,
+}
+
+/// Parse the options for delimiter-separated text files, yielding a string
+/// description of the configuration.
+fn parse_text_read_options(
+    x: &substrait::read_rel::local_files::file_or_files::DelimiterSeparatedTextReadOptions,
+    y: &mut context::Context,
+) -> diagnostic::Result<String> {
+    // Parse fields.
+    proto_primitive_field!(x, y, field_delimiter, |x, y| {
+        if x.is_empty() {
+            diagnostic!(
+                y,
+                Error,
+                IllegalValue,
+                "the field delimiter must not be empty"
+            );
+        } else if x.chars().count() > 1 {
+            diagnostic!(
+                y,
+                Error,
+                IllegalValue,
+                "the field delimiter must be a single character, but {} was specified",
+                util::string::as_quoted_string(x)
+            );
+        }
+        Ok(())
+    });
+    proto_primitive_field!(x, y, max_line_size, |x, y| {
+        if *x == 0 {
+            diagnostic!(
+                y,
+                Warning,
+                IllegalValue,
+                "a maximum line size of zero makes the behavior for any \
+                nonempty line undefined"
+            );
+        }
+        Ok(())
+    });
+    proto_primitive_field!(x, y, quote, |x, y| {
+        // The specification allows multiple characters here, but hardly any
+        // consumer supports that.
+        if x.chars().count() > 1 {
+            diagnostic!(
+                y,
+                Warning,
+                IllegalValue,
+                "multi-character quotes are not widely supported"
+            );
+        }
+        Ok(())
+    });
+    proto_primitive_field!(x, y, header_lines_to_skip);
+    proto_primitive_field!(x, y, escape, |x, y| {
+        if x.chars().count() > 1 {
+            diagnostic!(
+                y,
+                Error,
+                IllegalValue,
+                "the escape character must be a single character, but {} was specified",
+                util::string::as_quoted_string(x)
+            );
+        }
+        Ok(())
+    });
+    proto_field!(x, y, value_treated_as_null);
+
+    // Check for ambiguous combinations of special characters.
+    if !x.field_delimiter.is_empty() {
+        if x.field_delimiter == x.quote {
+            diagnostic!(
+                y,
+                Error,
+                IllegalValue,
+                "the field delimiter and quote character must differ"
+            );
+        }
+        if x.field_delimiter == x.escape {
+            diagnostic!(
+                y,
+                Error,
+                IllegalValue,
+                "the field delimiter and escape character must differ"
+            );
+        }
+    }
+    if !x.quote.is_empty() && x.quote == x.escape {
+        diagnostic!(
+            y,
+            Warning,
+            IllegalValue,
+            "the escape character is the same as the quote character, so \
+            quotes within strings can only be escaped by doubling them"
+        );
+    }
+
+    // Describe the node.
+    describe!(y, Misc, "Delimiter-separated text options");
+    let mut options = vec![format!(
+        "field delimiter {}",
+        util::string::as_quoted_string(&x.field_delimiter)
+    )];
+    if !x.quote.is_empty() {
+        options.push(format!(
+            "quote {}",
+            util::string::as_quoted_string(&x.quote)
+        ));
+    }
+    if !x.escape.is_empty() {
+        options.push(format!(
+            "escape {}",
+            util::string::as_quoted_string(&x.escape)
+        ));
+    }
+    if x.header_lines_to_skip > 0 {
+        options.push(format!(
+            "skipping {} header line(s)",
+            x.header_lines_to_skip
+        ));
+    }
+    if x.max_line_size > 0 {
+        options.push(format!("lines of at most {} bytes", x.max_line_size));
+    }
+    if let Some(null) = &x.value_treated_as_null {
+        options.push(format!(
+            "{} treated as null",
+            util::string::as_quoted_string(null)
+        ));
+    }
+    let options: String =
+        itertools::Itertools::intersperse(options.into_iter(), ", ".to_string()).collect();
+    summary!(y, "Fields are separated using {}.", options);
+    Ok(format!("delimiter-separated text ({options})"))
+}
+
+/// Parse file format, yielding a description of the format if known.
 fn parse_file_format(
     x: &substrait::read_rel::local_files::file_or_files::FileFormat,
     y: &mut context::Context,
-) -> diagnostic::Result<Option<String>> {
-    Ok(match x {
-        substrait::read_rel::local_files::file_or_files::FileFormat::Parquet(_) => {
-            Some(String::from("Parquet"))
+) -> diagnostic::Result<Option<FileFormatInfo>> {
+    use substrait::read_rel::local_files::file_or_files::FileFormat;
+    profile::check_file_format(y, x.proto_oneof_variant());
+    let (name, text) = match x {
+        FileFormat::Parquet(_) => {
+            describe!(y, Misc, "Parquet options");
+            ("Parquet", false)
         }
-        substrait::read_rel::local_files::file_or_files::FileFormat::Arrow(_) => {
-            Some(String::from("Arrow IPC"))
+        FileFormat::Arrow(_) => {
+            describe!(y, Misc, "Arrow IPC options");
+            ("Arrow IPC", false)
         }
-        substrait::read_rel::local_files::file_or_files::FileFormat::Orc(_) => {
-            Some(String::from("Orc"))
+        FileFormat::Orc(_) => {
+            describe!(y, Misc, "Orc options");
+            ("Orc", false)
         }
-        substrait::read_rel::local_files::file_or_files::FileFormat::Dwrf(_) => {
-            Some(String::from("Dwrf"))
+        FileFormat::Dwrf(_) => {
+            describe!(y, Misc, "Dwrf options");
+            ("Dwrf", false)
         }
-        substrait::read_rel::local_files::file_or_files::FileFormat::Extension(x) => {
+        FileFormat::Extension(x) => {
             extensions::advanced::parse_functional_any(x, y)?;
-            None
+            return Ok(None);
         }
-        substrait::read_rel::local_files::file_or_files::FileFormat::Text(_) => {
-            Some(String::from("Text"))
+        FileFormat::Text(x) => {
+            return Ok(Some(FileFormatInfo {
+                name: parse_text_read_options(x, y)?,
+                text: true,
+            }));
         }
-    })
+    };
+    Ok(Some(FileFormatInfo {
+        name: String::from(name),
+        text,
+    }))
 }
 
-/// Parse file entry.
+/// Parse file entry. Returns whether the entry refers to delimiter-separated
+/// text files.
 fn parse_file_or_files(
     x: &substrait::read_rel::local_files::FileOrFiles,
     y: &mut context::Context,
-) -> diagnostic::Result<()> {
+) -> diagnostic::Result<bool> {
     // Parse path.
     let multiple = proto_required_field!(x, y, path_type, parse_path_type)
         .1
@@ -208,8 +374,8 @@ fn parse_file_or_files(
         }
         summary!(y, "a single");
     }
-    if let Some(format) = format {
-        summary!(y, "{}", format);
+    if let Some(format) = &format {
+        summary!(y, "{}", format.name);
     }
     if multiple {
         summary!(y, "files");
@@ -217,7 +383,7 @@ fn parse_file_or_files(
         summary!(y, "file");
     }
 
-    Ok(())
+    Ok(format.map(|x| x.text).unwrap_or_default())
 }
 
 /// Parse local files.
@@ -226,7 +392,10 @@ fn parse_local_files(
     y: &mut context::Context,
 ) -> diagnostic::Result<SourceInfo> {
     // Parse fields.
-    proto_required_repeated_field!(x, y, items, parse_file_or_files, |_, _, _, _, _| (),);
+    let text = proto_required_repeated_field!(x, y, items, parse_file_or_files)
+        .1
+        .into_iter()
+        .any(|x| x.unwrap_or_default());
     proto_field!(
         x,
         y,
@@ -239,6 +408,7 @@ fn parse_local_files(
     Ok(SourceInfo {
         name: String::from("local files"),
         data_type: None,
+        text,
     })
 }
 
@@ -282,6 +452,7 @@ fn parse_named_table(
     Ok(SourceInfo {
         name,
         data_type: None,
+        text: false,
     })
 }
 
@@ -309,6 +480,7 @@ fn parse_extension_table(
             .map(|x| x.type_url.to_string())
             .unwrap_or_else(|| String::from("extension")),
         data_type: None,
+        text: false,
     })
 }
 
@@ -329,6 +501,40 @@ fn parse_read_type(
     }
 }
 
+/// Checks whether all fields of the given schema can be parsed from text.
+fn check_text_schema(y: &mut context::Context, schema: &data::Type) {
+    for (index, field) in schema.parameters().iter().enumerate() {
+        let Some(data_type) = field.value.as_ref().and_then(|x| x.get_data_type()) else {
+            continue;
+        };
+        let parseable = match data_type.class() {
+            data::Class::Simple(_) | data::Class::Unresolved => true,
+            data::Class::Compound(compound) => !matches!(
+                compound,
+                data::class::Compound::Struct
+                    | data::class::Compound::NamedStruct
+                    | data::class::Compound::List
+                    | data::class::Compound::Map
+            ),
+            data::Class::UserDefined(_) => false,
+        };
+        if !parseable {
+            diagnostic!(
+                y,
+                Warning,
+                TypeMismatch,
+                "field {} is of type {data_type}, which cannot be parsed from \
+                delimiter-separated text",
+                field
+                    .name
+                    .as_ref()
+                    .map(util::string::as_ident_or_string)
+                    .unwrap_or_else(|| index.to_string())
+            );
+        }
+    }
+}
+
 /// Parse read relation.
 pub fn parse_read_rel(x: &substrait::ReadRel, y: &mut context::Context) -> diagnostic::Result<()> {
     // Handle read type field.
@@ -337,6 +543,7 @@ pub fn parse_read_rel(x: &substrait::ReadRel, y: &mut context::Context) -> diagn
         .unwrap_or(SourceInfo {
             name: String::from("unknown source"),
             data_type: None,
+            text: false,
         });
 
     // Handle schema field.
@@ -365,6 +572,12 @@ pub fn parse_read_rel(x: &substrait::ReadRel, y: &mut context::Context) -> diagn
         );
     }
 
+    // Fields read from text files are parsed from strings, which only makes
+    // sense for types that have a string representation.
+    if source.text {
+        check_text_schema(y, &schema);
+    }
+
     // Set the schema to the merged data type.
     y.set_schema(schema.clone());
 
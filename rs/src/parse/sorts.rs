@@ -6,14 +6,43 @@ use crate::input::proto::substrait;
 use crate::input::traits::ProtoEnum;
 use crate::output::comment;
 use crate::output::diagnostic;
+use crate::output::extension;
 use crate::output::type_system::data;
+use crate::output::type_system::meta;
+use crate::output::type_system::meta::pattern::Pattern;
 use crate::parse::context;
 use crate::parse::expressions;
 use crate::parse::extensions;
 
-/// Parse a sort direction.
-fn parse_sort_direction(x: &i32, y: &mut context::Context) -> diagnostic::Result<&'static str> {
+/// Parse a sort direction, applicable to elements of the given data type.
+fn parse_sort_direction(
+    x: &i32,
+    y: &mut context::Context,
+    data_type: &data::Type,
+) -> diagnostic::Result<&'static str> {
     use substrait::sort_field::SortDirection;
+
+    // Ascending and descending sorts require a total order.
+    if matches!(
+        SortDirection::proto_enum_from_i32(*x),
+        Some(
+            SortDirection::AscNullsFirst
+                | SortDirection::AscNullsLast
+                | SortDirection::DescNullsFirst
+                | SortDirection::DescNullsLast
+        )
+    ) && !data_type.has_total_order()
+    {
+        diagnostic!(
+            y,
+            Warning,
+            TypeNoTotalOrder,
+            "{data_type} has no total order, so an ascending or descending \
+            sort is not well-defined; use a comparison function or cluster \
+            equal values instead"
+        );
+    }
+
     match SortDirection::proto_enum_from_i32(*x) {
         None => {
             diagnostic!(
@@ -56,6 +85,98 @@ fn parse_sort_direction(x: &i32, y: &mut context::Context) -> diagnostic::Result
     }
 }
 
+/// Binds a comparison function definition to two arguments of the given
+/// data type, returning the type it yields when successful, or a description
+/// of the mismatch otherwise.
+fn bind_comparison_function(
+    definition: &extension::simple::function::Definition,
+    data_type: &data::Type,
+) -> Result<data::Type, String> {
+    use extension::simple::function::ArgumentSlotType;
+
+    // Determine which argument slots the two arguments bind to.
+    let variadic = definition.variadic.min..=definition.variadic.max;
+    let slots = match definition.arguments.len() {
+        1 if variadic.contains(&2) => vec![&definition.arguments[0], &definition.arguments[0]],
+        2 if variadic.contains(&1) => definition.arguments.iter().collect(),
+        _ => {
+            return Err(format!(
+                "comparison functions must take two arguments, but {} does not",
+                definition.compound_name
+            ))
+        }
+    };
+
+    // Match the arguments against the patterns of the slots, and evaluate
+    // the return type in the resulting context.
+    let mut context = meta::Context::default();
+    let value = meta::Value::from(data_type.clone());
+    for (index, slot) in slots.into_iter().enumerate() {
+        let matched = match &slot.argument_type {
+            ArgumentSlotType::Value(slot) => slot
+                .pattern
+                .match_pattern_with_context(&mut context, &value)
+                .map_err(|e| e.to_string())?,
+            _ => false,
+        };
+        if !matched {
+            return Err(format!(
+                "argument {} of {} does not accept values of type {data_type}",
+                index + 1,
+                definition.compound_name
+            ));
+        }
+    }
+    definition
+        .return_type
+        .evaluate_type(&mut context)
+        .map_err(|e| e.to_string())
+}
+
+/// Checks the argument types encoded in the compound name of a comparison
+/// function against the type of the sort expression, for when the function
+/// definition is not available. The compound name is only a naming
+/// convention, so mismatches are reported as warnings.
+fn check_comparison_function_signature(
+    name: &str,
+    y: &mut context::Context,
+    data_type: &data::Type,
+) {
+    let Some((_, signature)) = name.split_once(':') else {
+        return;
+    };
+    let Some(expected) = data_type.class().signature_name() else {
+        return;
+    };
+    let arguments = if signature.is_empty() {
+        vec![]
+    } else {
+        signature.split('_').collect::<Vec<_>>()
+    };
+    if arguments.len() != 2 {
+        diagnostic!(
+            y,
+            Warning,
+            TypeMismatch,
+            "comparison functions must take two arguments, but {name} takes {}",
+            arguments.len()
+        );
+        return;
+    }
+    for (index, argument) in arguments.into_iter().enumerate() {
+        if !argument.starts_with("any") && !argument.eq_ignore_ascii_case(&expected) {
+            diagnostic!(
+                y,
+                Warning,
+                TypeMismatch,
+                "argument {} of {name} is of type {argument}, but the sort \
+                expression yields {data_type} ({expected})",
+                index + 1
+            );
+        }
+    }
+}
+
 /// Parse a function reference that should resolve to a comparison function
 /// (i.e. one usable for sorts) for the given type.
 fn parse_comparison_function_reference(
@@ -75,7 +196,22 @@ fn parse_comparison_function_reference(
         options: vec![],
         return_type: data::new_unresolved_type(),
     };
-    let binding = expressions::functions::FunctionBinding::new(Some(&functions), &context, y);
+    let mut binding = expressions::functions::FunctionBinding::new(Some(&functions), &context, y);
+
+    // The comparison function must take two arguments of the sort expression
+    // type. If the definition of the function is known, bind it to derive
+    // the return type; otherwise, the best we can do is check the argument
+    // types encoded in its compound name.
+    if !data_type.is_unresolved() {
+        if let Some(definition) = &binding.function.definition {
+            match bind_comparison_function(definition, data_type) {
+                Ok(return_type) => binding.return_type = return_type,
+                Err(message) => diagnostic!(y, Error, TypeMismatch, "{message}"),
+            }
+        } else if let Some(name) = binding.function.name.name() {
+            check_comparison_function_signature(name, y, data_type);
+        }
+    }
 
     // Describe how the function is to be interpreted.
     let comment = comment::Comment::new()
@@ -98,9 +234,7 @@ fn parse_comparison_function_reference(
             }
         }
         data::Class::Simple(data::class::Simple::I8)
-        | data::Class::Simple(data::class::Simple::I16)
-        | data::Class::Simple(data::class::Simple::I32)
-        | data::Class::Simple(data::class::Simple::I64) => {
+        | data::Class::Simple(data::class::Simple::I32) => {
             let comment = comment
                 .plain("interpreted as follows:")
                 .lo()
@@ -114,7 +248,7 @@ fn parse_comparison_function_reference(
             } else {
                 comment
                     .li()
-                    .plain("f(a, b) => null: a and b have no defined sort order.")
+                    .plain("f(a, b) => zero: a and b have no defined sort order.")
             }
         }
         _ => {
@@ -123,7 +257,7 @@ fn parse_comparison_function_reference(
                     y,
                     Error,
                     TypeMismatch,
-                    "comparison functions must yield booleans (a < b) or integers (a ?= b), but found {}",
+                    "comparison functions must yield booleans (a < b) or i8/i32 integers (a ?= b), but found {}",
                     binding.return_type
                 );
             }
@@ -150,7 +284,7 @@ fn parse_sort_kind(
     data_type: &data::Type,
 ) -> diagnostic::Result<&'static str> {
     match x {
-        substrait::sort_field::SortKind::Direction(x) => parse_sort_direction(x, y),
+        substrait::sort_field::SortKind::Direction(x) => parse_sort_direction(x, y, data_type),
         substrait::sort_field::SortKind::ComparisonFunctionReference(x) => {
             parse_comparison_function_reference(x, y, data_type)
         }
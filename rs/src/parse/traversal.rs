@@ -11,6 +11,7 @@
 #![allow(unused_macros)]
 
 use crate::input::config;
+use crate::input::source_map;
 use crate::input::traits::InputNode;
 use crate::input::traits::ProtoEnum;
 use crate::input::yaml;
@@ -18,8 +19,10 @@ use crate::output::diagnostic;
 use crate::output::parse_result;
 use crate::output::path;
 use crate::output::primitive_data;
+use crate::output::span;
 use crate::output::tree;
 use crate::parse::context;
+use crate::parse::versioning;
 use std::sync::Arc;
 
 //=============================================================================
@@ -76,13 +79,16 @@ pub fn push_diagnostic(
         cause,
         level,
         path: context.path_buf(),
+        span: None,
     });
 }
 
 /// Convenience/shorthand macro for pushing formatted comments to a node.
 macro_rules! comment {
     ($context:expr, $($fmts:expr),*) => {
-        $context.push_comment(format!($($fmts),*))
+        if $context.documentation_enabled() {
+            $context.push_comment(format!($($fmts),*))
+        }
     };
 }
 
@@ -90,14 +96,20 @@ macro_rules! comment {
 /// some path to a node.
 macro_rules! link {
     ($context:expr, $path:expr, $($fmts:expr),*) => {
-        $context.push_comment(crate::output::comment::Comment::new().link(format!($($fmts),*), $path))
+        if $context.documentation_enabled() {
+            $context.push_comment(crate::output::comment::Comment::new().link(format!($($fmts),*), $path))
+        }
     };
 }
 
 /// Convenience/shorthand macro for setting descriptive information for a node.
 macro_rules! describe {
     ($context:expr, $class:ident, $($fmts:expr),*) => {
-        $context.set_description(crate::output::tree::Class::$class, Some(format!($($fmts),*)))
+        if $context.documentation_enabled() {
+            $context.set_description(crate::output::tree::Class::$class, Some(format!($($fmts),*)))
+        } else {
+            $context.set_description(crate::output::tree::Class::$class, None::<String>)
+        }
     };
 }
 
@@ -105,7 +117,9 @@ macro_rules! describe {
 /// node.
 macro_rules! summary {
     ($context:expr, $($fmts:expr),*) => {
-        $context.push_summary(format!($($fmts),*))
+        if $context.documentation_enabled() {
+            $context.push_summary(format!($($fmts),*))
+        }
     };
 }
 
@@ -128,8 +142,41 @@ where
     // Create the node for the child.
     let mut field_output = child.data_to_node();
 
+    // Look up whether the child corresponds to a feature that is not
+    // available in all versions of Substrait.
+    let feature = if unknown_subtree {
+        None
+    } else {
+        versioning::lookup(context.node_type(), &path_element, &field_output.node_type)
+    };
+
+    // Stop descending into the input once a resource limit is exceeded or
+    // validation is cancelled, such that a partial result is produced.
+    if !context.check_budget(&field_output) {
+        return (Arc::new(field_output), None);
+    }
+
+    // Unrecognized subtrees are only reported in diagnostics-only mode, so
+    // there is no need to traverse them.
+    if unknown_subtree && !context.documentation_enabled() {
+        let field_output = Arc::new(field_output);
+        context.push(tree::NodeData::Child(tree::Child {
+            path_element,
+            node: field_output.clone(),
+            recognized: false,
+        }));
+        return (field_output, None);
+    }
+    let depth = context.budget().depth;
+
     // Create the context for calling the parse function for the child.
     let mut field_context = context.child(&mut field_output, path_element.clone());
+    field_context.budget().depth = depth + 1;
+
+    // Check the feature against the version declared by the plan.
+    if let Some(feature) = feature {
+        versioning::check(&mut field_context, feature);
+    }
 
     // Call the provided parser function.
     let result = parser(child, &mut field_context)
@@ -146,6 +193,7 @@ where
         &mut field_context,
         result.is_some() && !unknown_subtree,
     );
+    field_context.budget().depth = depth;
 
     // Push and return the completed node.
     let field_output = Arc::new(field_output);
@@ -186,6 +234,11 @@ fn handle_unknown_children<T: InputNode>(
             );
         }
     }
+
+    // The unrecognized children were only needed for the above.
+    if !context.documentation_enabled() {
+        context.remove_unrecognized_children();
+    }
 }
 
 /// Pushes a single unrecognized field as an unrecognized child node. Used by
@@ -756,7 +809,7 @@ where
     // parse function succeeded.
     handle_unknown_children(input, &mut context, true);
 
-    parse_result::ParseResult { root }
+    parse_result::ParseResult { root, root_name }
 }
 
 //=============================================================================
@@ -1149,6 +1202,7 @@ fn resolve_urn(urn: &str, context: &mut context::Context) -> Option<config::Bina
         )
         .collect::<String>();
         diagnostic!(context, Error, YamlCyclicDependency, "{cycle}");
+        context.set_urn_resolution_truncated();
         return None;
     }
 
@@ -1161,6 +1215,7 @@ fn resolve_urn(urn: &str, context: &mut context::Context) -> Option<config::Bina
                 YamlResolutionDisabled,
                 "configured recursion limit for URN resolution has been reached"
             );
+            context.set_urn_resolution_truncated();
             return None;
         }
     }
@@ -1263,45 +1318,133 @@ where
     if let Some(data) = resolve_urn(urn, context) {
         // Parse the flat file to a traversable tree.
         if let Some(root) = reader(data, context) {
-            // Update recursion stack.
-            context.urn_stack().push(urn.to_string());
-
-            // Defer to the provided parser to handle parsing the resolved
-            // data.
-            let (field_output, result) = push_child(
-                context,
-                &root,
-                path::PathElement::Field("data".to_string()),
-                false,
-                parser,
-            );
-
-            // Revert recursion stack update.
-            context.urn_stack().pop();
-
-            // Replace node type to make clear what the child node we just
-            // added signifies.
-            context.replace_node_type(tree::NodeType::ResolvedUrn(urn.to_string()));
-
             // Success, at least to the point that a tree was formed. The
             // tree itself might still be invalid.
-            return (Some(field_output), result);
+            return push_resolved(urn, &root, context, parser);
         }
     }
 
     (None, None)
 }
 
-/// Read function for YAML files, to be used with [parse_urn()].
+/// Same as parse_urn(), but for the contents of a file that was provided
+/// directly rather than resolved from a URN. The urn function is called on
+/// the root of the tree returned by the reader to determine the URN that the
+/// file declares, which is then treated as the URN the file was resolved
+/// from. The parser also receives this URN.
+pub fn parse_file<FP, FR, FU, TF, TR>(
+    data: config::BinaryData,
+    context: &mut context::Context,
+    reader: FR,
+    urn: FU,
+    parser: FP,
+) -> OptionalResult<TR>
+where
+    TF: InputNode,
+    FR: FnOnce(config::BinaryData, &mut context::Context) -> Option<TF>,
+    FU: FnOnce(&TF) -> String,
+    FP: FnOnce(&TF, &mut context::Context, &str) -> diagnostic::Result<TR>,
+{
+    if let Some(root) = reader(data, context) {
+        let urn = urn(&root);
+        push_resolved(&urn, &root, context, |x, y| parser(x, y, &urn))
+    } else {
+        (None, None)
+    }
+}
+
+/// Worker for parse_urn() and parse_file(): pushes the "data" child for the
+/// tree read from the file resolved for the given URN.
+fn push_resolved<FP, TF, TR>(
+    urn: &str,
+    root: &TF,
+    context: &mut context::Context,
+    parser: FP,
+) -> OptionalResult<TR>
+where
+    TF: InputNode,
+    FP: FnOnce(&TF, &mut context::Context) -> diagnostic::Result<TR>,
+{
+    // Update recursion stack.
+    context.urn_stack().push(urn.to_string());
+
+    // Defer to the provided parser to handle parsing the resolved data.
+    let (field_output, result) = push_child(
+        context,
+        root,
+        path::PathElement::Field("data".to_string()),
+        false,
+        parser,
+    );
+
+    // Revert recursion stack update.
+    context.urn_stack().pop();
+
+    // Replace node type to make clear what the child node we just added
+    // signifies.
+    context.replace_node_type(tree::NodeType::ResolvedUrn(urn.to_string()));
+
+    (Some(field_output), result)
+}
+
+/// Converts the location of a value that failed schema validation to the
+/// path of its node, given the path of the root node of the YAML data.
+/// Numeric location segments refer to either array elements or object
+/// members, depending on the value they select from.
+fn yaml_instance_path(
+    root: &path::PathBuf,
+    data: &yaml::Value,
+    location: &jsonschema::paths::Location,
+) -> path::PathBuf {
+    let mut path = root.clone();
+    let mut value = Some(data);
+    for segment in location {
+        let element = match (value, segment) {
+            (
+                Some(yaml::Value::Array(elements)),
+                jsonschema::paths::LocationSegment::Index(index),
+            ) => {
+                value = elements.get(index);
+                path::PathElement::Index(index)
+            }
+            (Some(yaml::Value::Object(members)), segment) => {
+                let key = segment.to_string();
+                value = members.get(&key);
+                path::PathElement::Field(key)
+            }
+            _ => break,
+        };
+        path.elements.push(element);
+    }
+    path
+}
+
+/// Read function for YAML files, to be used with [parse_urn()]. name is the
+/// name of the file that the location of diagnostics is reported for.
 pub fn read_yaml(
     binary_data: config::BinaryData,
     context: &mut context::Context,
     schema: Option<&jsonschema::Validator>,
+    name: &str,
 ) -> Option<yaml::Value> {
     // Parse as UTF-8.
-    let string_data = match std::str::from_utf8(binary_data.as_ref().as_ref()) {
+    let binary_data = binary_data.as_ref().as_ref();
+    let string_data = match std::str::from_utf8(binary_data) {
         Err(e) => {
-            ediagnostic!(context, Error, YamlParseFailed, e);
+            // Refer to the location of the first invalid byte.
+            let valid = std::str::from_utf8(&binary_data[..e.valid_up_to()]).unwrap_or_default();
+            let (line, column) = valid
+                .split('\n')
+                .enumerate()
+                .last()
+                .map(|(index, line)| (index + 1, line.chars().count() + 1))
+                .unwrap_or((1, 1));
+            context.push_diagnostic(diagnostic::RawDiagnostic {
+                cause: ecause!(YamlParseFailed, e),
+                level: diagnostic::Level::Error,
+                path: context.path_buf(),
+                span: Some(span::Span::at(Some(name.to_string()), line, column)),
+            });
             return None;
         }
         Ok(x) => x,
@@ -1310,14 +1453,35 @@ pub fn read_yaml(
     // Parse as YAML.
     let yaml_data = match serde_yaml::from_str::<serde_yaml::Value>(string_data) {
         Err(e) => {
-            ediagnostic!(context, Error, YamlParseFailed, e);
+            let span = e
+                .location()
+                .map(|x| span::Span::at(Some(name.to_string()), x.line(), x.column()));
+            context.push_diagnostic(diagnostic::RawDiagnostic {
+                cause: ecause!(YamlParseFailed, e),
+                level: diagnostic::Level::Error,
+                path: context.path_buf(),
+                span,
+            });
             return None;
         }
         Ok(x) => x,
     };
 
+    // Record the location of the values in the file. The root value is
+    // parsed as the "data" child of the current node (see push_resolved()),
+    // so diagnostics pushed for it and its descendants are located through
+    // their path from here on.
+    let root = context.path().with_field("data").to_path_buf();
+    let source = Arc::new(source_map::SourceMap::from_yaml(
+        string_data,
+        Some(name.to_string()),
+        root.clone(),
+    ));
+    context.add_source(source.clone());
+
     // Convert to JSON DOM.
-    let json_data = match yaml::yaml_to_json(yaml_data, context.path()) {
+    let json_data = yaml::yaml_to_json(yaml_data, &context.path().with_field("data"));
+    let json_data = match json_data {
         Err(e) => {
             diagnostic!(context, e);
             return None;
@@ -1325,12 +1489,19 @@ pub fn read_yaml(
         Ok(x) => x,
     };
 
-    // Validate with schema.
+    // Validate with schema. The diagnostics refer to the location of the
+    // value that failed validation.
     if let Some(schema) = schema {
         let errors: Vec<_> = schema.iter_errors(&json_data).collect();
         if !errors.is_empty() {
             for e in errors {
-                ediagnostic!(context, Error, YamlSchemaValidationFailed, e);
+                let span = source.lookup(&yaml_instance_path(&root, &json_data, e.instance_path()));
+                context.push_diagnostic(diagnostic::RawDiagnostic {
+                    cause: ecause!(YamlSchemaValidationFailed, e),
+                    level: diagnostic::Level::Error,
+                    path: context.path_buf(),
+                    span,
+                });
             }
             return None;
         }
@@ -12,6 +12,7 @@ use crate::output::type_system::data::class::ParameterInfo;
 use crate::output::type_system::meta;
 use crate::parse::context;
 use crate::parse::extensions;
+use crate::parse::profile;
 use crate::util;
 
 /// Parses a required nullability enum.
@@ -960,6 +961,9 @@ pub fn parse_type(x: &substrait::Type, y: &mut context::Context) -> diagnostic::
     // Describe the data type.
     describe_type(y, &data_type);
 
+    // Check whether the consumer supports the data type.
+    profile::check_type(y, &data_type);
+
     // Attach the type to the node.
     y.set_data_type(data_type);
 
@@ -1012,6 +1016,22 @@ fn assert_equal_internal(
     } else if base.is_unresolved() {
         other.clone()
     } else {
+        // If both types may be promoted, see if there is a promotion that
+        // makes them agree. Substrait doesn't define implicit casts, so the
+        // consumer may or may not accept this.
+        if promote_other && promote_base {
+            if let Some(promoted) = other.common_promotion(base) {
+                diagnostic!(
+                    context,
+                    Warning,
+                    TypeImplicitPromotion,
+                    "{message}: {other} vs. {base}{path}; an explicit cast to \
+                    {promoted} would make them agree"
+                );
+                return promoted;
+            }
+        }
+
         // Match base types.
         let base_types_match = match (other.class(), base.class()) {
             (
@@ -1222,7 +1242,10 @@ pub fn assert_equal<S: AsRef<str>>(
 }
 
 /// Like assert_equal, but will first promote either input to try to make them
-/// match.
+/// match. Differences in nullability are promoted silently; promotions
+/// through the lattice defined by [data::TypeDef::common_promotion()] (integer
+/// widening, decimal unification, and string type unification) yield the
+/// promoted type along with a warning that describes the promotion.
 pub fn promote_and_assert_equal<S: AsRef<str>>(
     context: &mut context::Context,
     other: &data::Type,
@@ -0,0 +1,242 @@
+// SPDX-License-Identifier: Apache-2.0
+
+//! Module for checking the plan against the Substrait version it declares.
+//!
+//! Plans are always parsed using the protobuf definitions of the Substrait
+//! version that the validator was built for, but a plan may declare an older
+//! version, for instance because it is intended for a consumer that has not
+//! been updated yet. This module records, for protobuf fields, oneof variants,
+//! and enum values that were not part of Substrait from the start, the version
+//! in which they were introduced, deprecated, or removed, and emits
+//! Versioning diagnostics when a plan uses something that its declared
+//! version does not (or no longer) supports.
+//!
+//! The table is not exhaustive: anything not listed is assumed to be
+//! available in all versions.
+
+use crate::output::path;
+use crate::output::primitive_data;
+use crate::output::tree;
+use crate::parse::context;
+use std::collections::HashMap;
+
+/// Availability of a protobuf field, oneof variant, or enum value across
+/// Substrait versions.
+pub struct Feature {
+    /// Full protobuf name of the message or enum that the feature belongs
+    /// to.
+    pub scope: &'static str,
+
+    /// Name of the field, oneof variant, or enum value.
+    pub name: &'static str,
+
+    /// The version in which the feature was introduced, if it was not part
+    /// of Substrait from the start.
+    pub introduced: Option<semver::Version>,
+
+    /// The version in which the feature was deprecated, if any.
+    pub deprecated: Option<semver::Version>,
+
+    /// The version in which the feature was removed, if any.
+    pub removed: Option<semver::Version>,
+}
+
+/// Shorthand for a 0.x.0 version.
+const fn v(minor: u64) -> Option<semver::Version> {
+    Some(semver::Version::new(0, minor, 0))
+}
+
+/// Shorthand for a feature that was introduced in the given version.
+const fn introduced(scope: &'static str, name: &'static str, minor: u64) -> Feature {
+    Feature {
+        scope,
+        name,
+        introduced: v(minor),
+        deprecated: None,
+        removed: None,
+    }
+}
+
+/// The known features.
+static FEATURES: &[Feature] = &[
+    // Relation types.
+    introduced("substrait.Rel", "hash_join", 22),
+    introduced("substrait.Rel", "merge_join", 22),
+    introduced("substrait.Rel", "exchange", 25),
+    introduced("substrait.Rel", "expand", 31),
+    introduced("substrait.Rel", "window", 41),
+    introduced("substrait.Rel", "nested_loop_join", 42),
+    introduced("substrait.Rel", "write", 44),
+    introduced("substrait.Rel", "ddl", 44),
+    introduced("substrait.Rel", "reference", 47),
+    introduced("substrait.Rel", "update", 63),
+    // Expression types.
+    introduced("substrait.Expression", "nested", 37),
+    introduced("substrait.Expression", "dynamic_parameter", 70),
+    introduced("substrait.Expression", "lambda", 83),
+    introduced("substrait.Expression", "lambda_invocation", 83),
+    introduced("substrait.Expression", "execution_context_variable", 86),
+    // Data types.
+    introduced("substrait.Type", "precision_timestamp", 39),
+    introduced("substrait.Type", "precision_timestamp_tz", 39),
+    introduced("substrait.Type", "interval_compound", 57),
+    introduced("substrait.Type", "precision_time", 69),
+    introduced("substrait.Type", "alias", 84),
+    introduced("substrait.Type", "func", 85),
+    // Join types.
+    introduced("substrait.JoinRel.JoinType", "JOIN_TYPE_LEFT_MARK", 53),
+    introduced("substrait.JoinRel.JoinType", "JOIN_TYPE_RIGHT_MARK", 53),
+    // Plan-level fields.
+    introduced("substrait.Plan", "parameter_bindings", 70),
+    introduced("substrait.Plan", "extension_urns", 75),
+    introduced("substrait.Plan", "type_aliases", 84),
+    introduced("substrait.Plan", "execution_behavior", 86),
+    // Legacy extension URIs (see LegacyPlan).
+    Feature {
+        scope: "substrait.Plan",
+        name: "extension_uris",
+        introduced: None,
+        deprecated: v(75),
+        removed: v(85),
+    },
+];
+
+/// Returns the feature that the child of a node of type parent_type at the
+/// given path element with node type child_type corresponds to, if it is
+/// listed.
+pub fn lookup(
+    parent_type: &tree::NodeType,
+    path_element: &path::PathElement,
+    child_type: &tree::NodeType,
+) -> Option<&'static Feature> {
+    static INDEX: once_cell::sync::Lazy<HashMap<&'static str, Vec<&'static Feature>>> =
+        once_cell::sync::Lazy::new(|| {
+            let mut index: HashMap<&'static str, Vec<&'static Feature>> = HashMap::new();
+            for feature in FEATURES {
+                index.entry(feature.scope).or_default().push(feature);
+            }
+            index
+        });
+    let find = |scope: &str, name: &str| {
+        INDEX
+            .get(scope)
+            .and_then(|x| x.iter().find(|x| x.name == name).copied())
+    };
+
+    // Enum values.
+    if let tree::NodeType::ProtoPrimitive(scope, primitive_data::PrimitiveData::Enum(name)) =
+        child_type
+    {
+        if let Some(feature) = find(scope, name) {
+            return Some(feature);
+        }
+    }
+
+    // Fields and oneof variants.
+    if let tree::NodeType::ProtoMessage(scope) = parent_type {
+        let name = match path_element {
+            path::PathElement::Field(name) | path::PathElement::Repeated(name, _) => name,
+            path::PathElement::Variant(_, name) => name,
+            path::PathElement::Index(_) => return None,
+        };
+        find(scope, name)
+    } else {
+        None
+    }
+}
+
+/// Checks whether the given feature, used by the node corresponding to the
+/// given context, is available in the version of Substrait that the plan
+/// declares.
+pub fn check(y: &mut context::Context, feature: &Feature) {
+    let Some(version) = y.plan_version().cloned() else {
+        return;
+    };
+    let name = format!("{}.{}", feature.scope, feature.name);
+    if let Some(removed) = feature.removed.as_ref().filter(|x| &version >= x) {
+        diagnostic!(
+            y,
+            Warning,
+            Versioning,
+            "{name} was removed in Substrait {removed}, but the plan \
+            declares version {version}"
+        );
+    } else if let Some(introduced) = feature.introduced.as_ref().filter(|x| &version < x) {
+        diagnostic!(
+            y,
+            Warning,
+            Versioning,
+            "{name} was introduced in Substrait {introduced}, but the plan \
+            declares version {version}"
+        );
+    } else if let Some(deprecated) = feature.deprecated.as_ref().filter(|x| &version >= x) {
+        diagnostic!(
+            y,
+            Info,
+            Versioning,
+            "{name} is deprecated as of Substrait {deprecated}, and the plan \
+            declares version {version}"
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::input::config;
+    use crate::output::diagnostic;
+
+    #[test]
+    fn lookup_features() {
+        let rel = tree::NodeType::ProtoMessage(String::from("substrait.Rel"));
+        let expand = tree::NodeType::ProtoMessage(String::from("substrait.ExpandRel"));
+        let feature = lookup(
+            &rel,
+            &path::PathElement::Variant(String::from("rel_type"), String::from("expand")),
+            &expand,
+        )
+        .unwrap();
+        assert_eq!(feature.introduced, v(31));
+        assert!(lookup(
+            &rel,
+            &path::PathElement::Variant(String::from("rel_type"), String::from("read")),
+            &expand,
+        )
+        .is_none());
+
+        let join_type = tree::NodeType::ProtoPrimitive(
+            "substrait.JoinRel.JoinType",
+            primitive_data::PrimitiveData::Enum("JOIN_TYPE_LEFT_MARK"),
+        );
+        assert!(lookup(
+            &tree::NodeType::ProtoMessage(String::from("substrait.JoinRel")),
+            &path::PathElement::Field(String::from("type")),
+            &join_type,
+        )
+        .is_some());
+    }
+
+    #[test]
+    fn version_dependent_features() {
+        let config = config::Config::default();
+        let plan = |minor: u32| {
+            format!(
+                r#"{{
+                    "version": {{ "minorNumber": {minor}, "producer": "json-test" }},
+                    "relations": [{{ "rel": {{ "expand": {{}} }} }}]
+                }}"#
+            )
+        };
+        let flags_expand = |minor: u32| {
+            crate::parse_json(&plan(minor), &config)
+                .iter_diagnostics()
+                .any(|x| {
+                    x.cause.classification == diagnostic::Classification::Versioning
+                        && x.cause.to_string().contains("substrait.Rel.expand")
+                })
+        };
+        assert!(flags_expand(30));
+        assert!(!flags_expand(31));
+        assert!(!flags_expand(87));
+    }
+}
@@ -392,6 +392,11 @@ def compile_test(fname, data, proto_parse, proto_desc):
     # Parse diagnostic overrides.
     diag_overrides = parse_diag_overrides(data.pop("diags", None))
 
+    # Get whether extension URNs referenced by the plan are to be resolved.
+    resolve_extensions = data.pop("resolve_extensions", False)
+    if not isinstance(resolve_extensions, bool):
+        raise Exception("resolve_extensions must be a boolean")
+
     # Get plan data.
     plan = data.pop("plan", None)
     if not isinstance(plan, dict):
@@ -431,6 +436,7 @@ def compile_test(fname, data, proto_parse, proto_desc):
                     name=name,
                     plan=list(plan),
                     diag_overrides=diag_overrides,
+                    resolve_extensions=resolve_extensions,
                     instructions=instructions,
                 )
             )
@@ -149,6 +149,10 @@ struct TestDescription {
     /// List of diagnostic level overrides to apply.
     pub diag_overrides: Vec<DiagOverride>,
 
+    /// Whether extension URNs referenced by the plan are to be resolved.
+    #[serde(default)]
+    pub resolve_extensions: bool,
+
     /// The binary serialization of the plan.
     pub plan: Vec<u8>,
 
@@ -403,6 +407,9 @@ impl TestCase {
                 diag_override.max.into(),
             );
         }
+        if desc.resolve_extensions {
+            validator_config.set_max_urn_resolution_depth(None);
+        }
         let path_os_str = path.as_os_str().to_owned();
         validator_config.add_urn_resolver(move |urn| {
             // Inline test extensions are referenced with the synthetic URN